@@ -0,0 +1,55 @@
+//! Compares converting borrowed IRI-references into owned buffers, which reuses the
+//! parsing data, with parsing the borrowed string again.
+//!
+//! Run with `cargo bench --bench conversion`.
+extern crate iref;
+
+use iref::{Iri, IriBuf, IriRef, IriRefBuf};
+use std::time::Instant;
+
+const ITERATIONS: usize = 1_000_000;
+
+const SOURCES: [&str; 4] = [
+	"http://example.org/items/1",
+	"https://user@www.example.com:8443/a/very/long/path/to/some/resource.html?q=1&r=2#f",
+	"http://例え.jp/résumé?ü#ß",
+	"urn:isbn:0451450523",
+];
+
+/// Runs `f` `ITERATIONS` times, and prints the elapsed time.
+///
+/// `f` returns the total length of the IRIs it built, which is summed so that the
+/// conversions are not optimized away.
+fn measure<F: Fn() -> usize>(name: &str, f: F) {
+	let mut total = 0;
+	let start = Instant::now();
+	for _ in 0..ITERATIONS {
+		total += f()
+	}
+	let elapsed = start.elapsed();
+
+	println!("{}: {:?} (checksum {})", name, elapsed, total);
+}
+
+fn main() {
+	let iri_refs: Vec<IriRef> = SOURCES.iter().map(|s| IriRef::new(s).unwrap()).collect();
+	let iris: Vec<Iri> = SOURCES.iter().map(|s| Iri::new(s).unwrap()).collect();
+
+	measure("IriRefBuf::from(IriRef)", || {
+		iri_refs.iter().map(|r| IriRefBuf::from(*r).len()).sum()
+	});
+	measure("IriRefBuf::new(IriRef::as_str)", || {
+		iri_refs
+			.iter()
+			.map(|r| IriRefBuf::new(r.as_str()).unwrap().len())
+			.sum()
+	});
+	measure("IriBuf::from(Iri)", || {
+		iris.iter().map(|iri| IriBuf::from(*iri).len()).sum()
+	});
+	measure("IriBuf::new(Iri::as_str)", || {
+		iris.iter()
+			.map(|iri| IriBuf::new(iri.as_str()).unwrap().len())
+			.sum()
+	});
+}
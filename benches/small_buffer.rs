@@ -0,0 +1,96 @@
+//! Measures the memory used by a large collection of short IRIs, stored as `IriRefBuf`
+//! or with an inline small buffer (`SmallVec<[u8; 64]>`) instead of a `Vec<u8>`.
+//!
+//! Run with `cargo bench --bench small_buffer`.
+extern crate iref;
+extern crate smallvec;
+
+use iref::parsing::ParsedIriRef;
+use iref::{IriRef, IriRefBuf};
+use smallvec::SmallVec;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Allocator counting the allocations and the allocated bytes.
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+	unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+		ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+		ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+		System.alloc(layout)
+	}
+
+	unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+		ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+		System.dealloc(ptr, layout)
+	}
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+const COUNT: usize = 1_000_000;
+
+/// Runs `f` and returns the number of allocations it made and the bytes it kept allocated.
+fn measure<T, F: FnOnce() -> T>(f: F) -> (T, usize, usize) {
+	let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+	let allocated = ALLOCATED.load(Ordering::Relaxed);
+	let value = f();
+	(
+		value,
+		ALLOCATIONS.load(Ordering::Relaxed) - allocations,
+		ALLOCATED.load(Ordering::Relaxed) - allocated,
+	)
+}
+
+fn report(name: &str, size: usize, allocations: usize, allocated: usize) {
+	println!(
+		"{} ({} bytes): {} allocations, {} MB",
+		name,
+		size,
+		allocations,
+		allocated / 1_000_000
+	);
+}
+
+fn main() {
+	let sources: Vec<String> = (0..COUNT)
+		.map(|i| format!("http://example.org/items/{}", i))
+		.collect();
+
+	let (buffers, allocations, allocated) = measure(|| {
+		let mut buffers = Vec::with_capacity(COUNT);
+		for source in &sources {
+			buffers.push(IriRefBuf::new(source.as_str()).unwrap())
+		}
+		buffers
+	});
+	report(
+		"IriRefBuf",
+		std::mem::size_of::<IriRefBuf>(),
+		allocations,
+		allocated,
+	);
+	drop(buffers);
+
+	let (small, allocations, allocated) = measure(|| {
+		let mut small = Vec::with_capacity(COUNT);
+		for source in &sources {
+			let iri_ref = IriRef::new(source.as_str()).unwrap();
+			let data: SmallVec<[u8; 64]> = SmallVec::from_slice(iri_ref.as_str().as_bytes());
+			small.push((data, iri_ref.parsing_data()))
+		}
+		small
+	});
+	report(
+		"SmallVec<[u8; 64]> + ParsedIriRef",
+		std::mem::size_of::<(SmallVec<[u8; 64]>, ParsedIriRef)>(),
+		allocations,
+		allocated,
+	);
+	drop(small);
+}
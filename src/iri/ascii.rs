@@ -0,0 +1,71 @@
+use std::fmt::Write;
+
+/// Report of the conversion of an IRI into an URI.
+///
+/// Produced by the [`Iri::to_ascii`](crate::Iri::to_ascii) method.
+/// For each component, gives the number of non-ASCII characters that had to be
+/// percent-encoded.
+///
+/// Without the `idna` feature, hosts are percent-encoded as allowed by
+/// [RFC 3987 section 3.1](https://tools.ietf.org/html/rfc3987#section-3.1).
+/// With the `idna` feature, IDNA is applied to non-ASCII hosts instead,
+/// which is reported by the [`idna`](AsciiConversionReport::idna) flag.
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct AsciiConversionReport {
+	/// Number of percent-encoded characters in the user info.
+	pub userinfo: usize,
+
+	/// Number of percent-encoded characters in the host.
+	pub host: usize,
+
+	/// Whether the host was converted to its ASCII form with IDNA (ToASCII).
+	///
+	/// This is only ever `true` with the `idna` feature.
+	pub idna: bool,
+
+	/// Number of percent-encoded characters in the path.
+	pub path: usize,
+
+	/// Number of percent-encoded characters in the query.
+	pub query: usize,
+
+	/// Number of percent-encoded characters in the fragment.
+	pub fragment: usize,
+}
+
+impl AsciiConversionReport {
+	/// Checks if the IRI was changed by the conversion.
+	///
+	/// This is `false` if the IRI was already an URI.
+	#[inline]
+	pub fn is_changed(&self) -> bool {
+		self.userinfo > 0
+			|| self.host > 0
+			|| self.idna
+			|| self.path > 0
+			|| self.query > 0
+			|| self.fragment > 0
+	}
+}
+
+/// Append the given string to `output`, percent-encoding every non-ASCII character.
+///
+/// Returns the number of encoded characters.
+pub(crate) fn encode_non_ascii(input: &str, output: &mut String) -> usize {
+	let mut count = 0;
+
+	for c in input.chars() {
+		if c.is_ascii() {
+			output.push(c)
+		} else {
+			let mut buffer = [0; 4];
+			for b in c.encode_utf8(&mut buffer).bytes() {
+				write!(output, "%{:02X}", b).unwrap();
+			}
+
+			count += 1
+		}
+	}
+
+	count
+}
@@ -1,11 +1,13 @@
-use pct_str::PctStr;
+use pct_str::{Encoder, PctStr, PctString};
+use std::borrow::Cow;
 use std::cmp::{Ord, Ordering, PartialOrd};
 use std::convert::TryFrom;
 use std::hash::{Hash, Hasher};
 use std::ops::Range;
 use std::{cmp, fmt};
 
-use super::{Error, Host, Port, UserInfo};
+use super::decode::{pct_cmp, pct_eq_str};
+use super::{Error, Host, PercentDecode, Port, Scheme, UserInfo};
 use crate::parsing::{self, ParsedAuthority};
 
 pub struct Authority<'a> {
@@ -52,6 +54,12 @@ impl<'a> Authority<'a> {
 		unsafe { PctStr::new_unchecked(self.as_str()) }
 	}
 
+	/// Returns an iterator over the percent-decoded bytes of the authority.
+	#[inline]
+	pub fn percent_decode(&self) -> PercentDecode<'a> {
+		PercentDecode::new(&self.data[0..self.p.len()])
+	}
+
 	#[inline]
 	pub fn userinfo(&self) -> Option<UserInfo> {
 		self.p.userinfo_len.map(|len| UserInfo {
@@ -59,8 +67,42 @@ impl<'a> Authority<'a> {
 		})
 	}
 
+	/// Returns the user name part of the user info, if any.
+	///
+	/// The user info is split on its first `:`: the user name is what comes before it.
+	/// Percent-encoded colons (`%3A`) are not considered as separators.
+	///
+	/// # Example
+	///
+	/// ```
+	/// # use iref::Iri;
+	/// let iri = Iri::new("postgres://user:pw@host/db").unwrap();
+	/// let authority = iri.authority().unwrap();
+	///
+	/// assert_eq!(authority.username().unwrap().as_str(), "user");
+	/// assert_eq!(authority.password().unwrap().as_str(), "pw");
+	/// ```
 	#[inline]
-	pub fn host(&self) -> Host {
+	pub fn username(&self) -> Option<&'a PctStr> {
+		self.p
+			.userinfo_len
+			.map(|len| split_userinfo(&self.data[0..len]).0)
+	}
+
+	/// Returns the password part of the user info, if any.
+	///
+	/// The password is what comes after the first `:` of the user info.
+	/// This is deprecated by [RFC 3986](https://tools.ietf.org/html/rfc3986#section-3.2.1)
+	/// but still commonly found, for instance in database connection strings.
+	#[inline]
+	pub fn password(&self) -> Option<&'a PctStr> {
+		self.p
+			.userinfo_len
+			.and_then(|len| split_userinfo(&self.data[0..len]).1)
+	}
+
+	#[inline]
+	pub fn host(&self) -> Host<'a> {
 		let len = self.p.host_len;
 		let offset = self.p.host_offset();
 		Host {
@@ -68,8 +110,87 @@ impl<'a> Authority<'a> {
 		}
 	}
 
+	/// Returns the canonical form of the host.
+	///
+	/// See [`Host::canonical`].
 	#[inline]
-	pub fn port(&self) -> Option<Port> {
+	pub fn canonical_host(&self) -> Cow<'a, str> {
+		self.host().canonical()
+	}
+
+	/// Checks if the host designates the given DNS name.
+	///
+	/// The comparison is performed on the [canonical host](Authority::canonical_host),
+	/// ignoring ASCII case and a trailing dot in `name`, so that
+	/// `Example.COM.` matches `example.com`.
+	/// The user information and port are ignored.
+	/// A host keeping percent-encoded octets once canonicalized (such as `%FF.com`)
+	/// is not a DNS name, and never matches.
+	///
+	/// # Example
+	///
+	/// ```
+	/// # use std::convert::TryFrom;
+	/// # use iref::Authority;
+	/// let authority = Authority::try_from("user@WWW.Example.com.:8080").unwrap();
+	/// assert!(authority.host_matches("www.example.com"));
+	/// assert!(authority.host_matches("www.EXAMPLE.com."));
+	/// assert!(!authority.host_matches("example.com"));
+	/// ```
+	#[inline]
+	pub fn host_matches(&self, name: &str) -> bool {
+		let host = self.canonical_host();
+		!host.contains('%') && host.eq_ignore_ascii_case(strip_trailing_dot(name))
+	}
+
+	/// Checks if the host matches the given DNS name pattern, as found in TLS certificates.
+	///
+	/// The pattern may start with a `*.` wildcard label, standing for exactly one
+	/// non-empty label: `*.example.com` matches `a.example.com`, but neither
+	/// `a.b.example.com` nor `example.com` itself.
+	/// Only the leftmost label can be a wildcard, and it must be the whole label
+	/// (partial wildcards like `a*.example.com` are not supported and never match).
+	/// A wildcard never matches an IP address.
+	///
+	/// Without wildcard, this is the same as [`host_matches`](Authority::host_matches).
+	///
+	/// # Example
+	///
+	/// ```
+	/// # use std::convert::TryFrom;
+	/// # use iref::Authority;
+	/// let authority = Authority::try_from("a.example.com").unwrap();
+	/// assert!(authority.host_matches_wildcard("*.example.com"));
+	/// assert!(authority.host_matches_wildcard("a.example.com"));
+	/// assert!(!authority.host_matches_wildcard("*.a.example.com"));
+	///
+	/// let apex = Authority::try_from("example.com").unwrap();
+	/// assert!(!apex.host_matches_wildcard("*.example.com"));
+	/// ```
+	pub fn host_matches_wildcard(&self, pattern: &str) -> bool {
+		match pattern.strip_prefix("*.") {
+			Some(suffix) => {
+				let host = self.canonical_host();
+				if host.starts_with('[')
+					|| host.contains('%')
+					|| host.parse::<std::net::Ipv4Addr>().is_ok()
+				{
+					return false;
+				}
+
+				match host.split_once('.') {
+					Some((label, rest)) => {
+						!label.is_empty() && rest.eq_ignore_ascii_case(strip_trailing_dot(suffix))
+					}
+					None => false,
+				}
+			}
+			None => self.host_matches(pattern),
+		}
+	}
+
+	#[inline]
+	pub fn port(&self) -> Option<Port<'a>> {
 		if let Some(len) = self.p.port_len {
 			let offset = self.p.port_offset();
 			Some(Port {
@@ -79,6 +200,121 @@ impl<'a> Authority<'a> {
 			None
 		}
 	}
+
+	/// Returns the `host:port` string to connect to the server designated by this authority.
+	///
+	/// The port of the authority is used if it is present and not empty, otherwise the
+	/// given `default_port` is used (typically the [`Scheme::default_port`]).
+	/// If there is no port at all, only the host is returned.
+	/// The host is given in its [canonical form](Host::canonical), so that escapes
+	/// such as `ex%61mple.org` are decoded (and, with the `idna` feature, non-ASCII hosts
+	/// are converted to ASCII).
+	/// IPv6 hosts keep their brackets (`[::1]:8080`), as expected by
+	/// [`std::net::ToSocketAddrs`].
+	/// The user info is never included.
+	///
+	/// Hosts that are not DNS names even once canonicalized, such as `%FF.com`
+	/// (which keeps its escape) or non-ASCII hosts without the `idna` feature,
+	/// are returned as is and cannot be resolved by [`std::net::ToSocketAddrs`].
+	///
+	/// No allocation occurs unless the host is not canonical or the default port
+	/// needs to be appended.
+	///
+	/// # Example
+	///
+	/// ```
+	/// # use std::borrow::Cow;
+	/// # use std::convert::TryFrom;
+	/// # use iref::Authority;
+	/// let authority = Authority::try_from("user@example.org:8080").unwrap();
+	/// assert_eq!(authority.socket_str(Some(80)), Cow::Borrowed("example.org:8080"));
+	///
+	/// let authority = Authority::try_from("[::1]").unwrap();
+	/// assert_eq!(authority.socket_str(Some(443)), "[::1]:443");
+	/// assert_eq!(authority.socket_str(None), "[::1]");
+	/// ```
+	#[inline]
+	pub fn socket_str(&self, default_port: Option<u16>) -> Cow<'a, str> {
+		let port = self.port().filter(|port| !port.is_empty());
+
+		match (self.canonical_host(), port, default_port) {
+			(Cow::Borrowed(_), Some(port), _) => {
+				let host_offset = self.p.host_offset();
+				let end = host_offset + self.p.host_len + 1 + port.data.len();
+				Cow::Borrowed(unsafe {
+					std::str::from_utf8_unchecked(&self.data[host_offset..end])
+				})
+			}
+			(host, Some(port), _) => Cow::Owned(format!("{}:{}", host, port.as_str())),
+			(host, None, Some(port)) => Cow::Owned(format!("{}:{}", host, port)),
+			(host, None, None) => host,
+		}
+	}
+
+	/// Port number of the authority, without leading zeros, if any.
+	///
+	/// An empty port is considered absent, and the port matching the default port of the given
+	/// scheme, if any, is also considered absent.
+	#[inline]
+	pub(crate) fn normalized_port(&self, scheme: Option<Scheme>) -> Option<&str> {
+		let port = self
+			.port()
+			.map(|port| port.trimmed())
+			.filter(|port| !port.is_empty())?;
+		match scheme.and_then(|scheme| scheme.default_port()) {
+			Some(default_port) if port == default_port.to_string() => None,
+			_ => Some(port),
+		}
+	}
+
+	/// Compare two authorities after normalization.
+	///
+	/// Hosts are compared case-insensitively and an empty port is considered equivalent to no
+	/// port at all, as recommended by
+	/// [RFC 3986](https://tools.ietf.org/html/rfc3986#section-6.2.3).
+	/// Ports are compared numerically, ignoring leading zeros.
+	/// The user info part remains case-sensitive.
+	///
+	/// Contrarily to the [`PartialEq`] implementation, this is not protocol agnostic.
+	/// However the default port of a scheme is not elided here since the scheme is unknown.
+	/// Use [`Authority::eq_normalized_for_scheme`] for that.
+	#[inline]
+	pub fn eq_normalized(&self, other: &Authority) -> bool {
+		self.userinfo() == other.userinfo()
+			&& self.host().eq_ignore_ascii_case(&other.host())
+			&& self.normalized_port(None) == other.normalized_port(None)
+	}
+
+	/// Compare two authorities after normalization, in the context of the given scheme.
+	///
+	/// This is similar to [`Authority::eq_normalized`], but the default port of the scheme
+	/// (see [`Scheme::default_port`]) is also considered equivalent to no port at all.
+	///
+	/// # Example
+	///
+	/// ```
+	/// # use std::convert::TryFrom;
+	/// # use iref::{Authority, Scheme};
+	/// let a = Authority::try_from("Example.COM:80").unwrap();
+	/// let b = Authority::try_from("example.com").unwrap();
+	///
+	/// assert!(a.eq_normalized_for_scheme(&b, Scheme::try_from("http").unwrap()));
+	/// assert!(!a.eq_normalized_for_scheme(&b, Scheme::try_from("https").unwrap()));
+	/// ```
+	#[inline]
+	pub fn eq_normalized_for_scheme(&self, other: &Authority, scheme: Scheme) -> bool {
+		self.userinfo() == other.userinfo()
+			&& self.host().eq_ignore_ascii_case(&other.host())
+			&& self.normalized_port(Some(scheme)) == other.normalized_port(Some(scheme))
+	}
+}
+
+impl<'a> Authority<'a> {
+	/// Convert the authority slice into the owned version [`AuthorityBuf`].
+	#[inline]
+	pub fn to_owned(self) -> AuthorityBuf {
+		self.into()
+	}
 }
 
 impl<'a> AsRef<[u8]> for Authority<'a> {
@@ -138,7 +374,7 @@ impl<'a> PartialOrd for Authority<'a> {
 impl<'a> Ord for Authority<'a> {
 	#[inline]
 	fn cmp(&self, other: &Authority<'a>) -> Ordering {
-		self.as_pct_str().cmp(other.as_pct_str())
+		pct_cmp(self.data, other.data)
 	}
 }
 
@@ -147,7 +383,236 @@ impl<'a> Eq for Authority<'a> {}
 impl<'a> cmp::PartialEq<&'a str> for Authority<'a> {
 	#[inline]
 	fn eq(&self, other: &&'a str) -> bool {
-		self.as_pct_str() == *other
+		pct_eq_str(self.data, other)
+	}
+}
+
+/// Owned authority.
+///
+/// Can be used to build an authority independently of any IRI,
+/// and attach it later using the `set_authority` method of
+/// [`IriRefBuf`](crate::IriRefBuf) or [`IriBuf`](crate::IriBuf).
+#[derive(Clone)]
+pub struct AuthorityBuf {
+	/// The authority data.
+	data: Vec<u8>,
+
+	/// Authority positions.
+	p: ParsedAuthority,
+}
+
+impl AuthorityBuf {
+	/// Build an authority from its user info, host and port.
+	///
+	/// The user info is percent-encoded where needed: every character not allowed in the
+	/// user info (including `%` and `@`) is encoded, but `:` is kept as the user name and
+	/// password separator.
+	/// An IPv6 host may be given with or without its surrounding brackets,
+	/// which are added if missing.
+	///
+	/// Returns [`Error::InvalidHost`] if the host is not valid.
+	///
+	/// # Example
+	///
+	/// ```
+	/// # use iref::{AuthorityBuf, IriBuf};
+	/// let authority = AuthorityBuf::from_parts(Some("j doe"), "::1", Some(8080)).unwrap();
+	/// assert_eq!(authority, "j%20doe@[::1]:8080");
+	///
+	/// let mut iri = IriBuf::new("http://example.org/a").unwrap();
+	/// iri.set_authority(Some(authority.as_authority()));
+	/// assert_eq!(iri, "http://j%20doe@[::1]:8080/a");
+	/// ```
+	pub fn from_parts(
+		userinfo: Option<&str>,
+		host: &str,
+		port: Option<u16>,
+	) -> Result<AuthorityBuf, Error> {
+		let mut data = Vec::new();
+
+		let userinfo_len = userinfo.map(|userinfo| {
+			let userinfo = PctString::encode(userinfo.chars(), UserInfoEncoder);
+			data.extend_from_slice(userinfo.as_str().as_bytes());
+			data.push(b'@');
+			userinfo.as_str().len()
+		});
+
+		let host_offset = data.len();
+		if host.contains(':') && !host.starts_with('[') {
+			data.push(b'[');
+			data.extend_from_slice(host.as_bytes());
+			data.push(b']');
+		} else {
+			data.extend_from_slice(host.as_bytes());
+		}
+
+		let host_len = data.len() - host_offset;
+		if parsing::parse_host(&data, host_offset)? != host_len {
+			return Err(Error::InvalidHost);
+		}
+
+		let port_len = port.map(|port| {
+			let port = port.to_string();
+			data.push(b':');
+			data.extend_from_slice(port.as_bytes());
+			port.len()
+		});
+
+		Ok(AuthorityBuf {
+			data,
+			p: ParsedAuthority {
+				userinfo_len,
+				host_len,
+				port_len,
+			},
+		})
+	}
+
+	/// Returns a reference to the byte representation of the authority.
+	#[inline]
+	pub fn as_bytes(&self) -> &[u8] {
+		&self.data
+	}
+
+	/// Consume the authority and return its internal buffer.
+	#[inline]
+	pub fn into_bytes(self) -> Vec<u8> {
+		self.data
+	}
+
+	/// Get the authority as a string slice.
+	#[inline]
+	pub fn as_str(&self) -> &str {
+		unsafe { std::str::from_utf8_unchecked(&self.data) }
+	}
+
+	/// Borrow the authority buffer as an authority slice.
+	#[inline]
+	pub fn as_authority(&self) -> Authority<'_> {
+		Authority {
+			data: &self.data,
+			p: self.p,
+		}
+	}
+
+	#[inline]
+	pub fn userinfo(&self) -> Option<UserInfo<'_>> {
+		self.p.userinfo_len.map(|len| UserInfo {
+			data: &self.data[0..len],
+		})
+	}
+
+	#[inline]
+	pub fn host(&self) -> Host<'_> {
+		self.as_authority().host()
+	}
+
+	#[inline]
+	pub fn port(&self) -> Option<Port<'_>> {
+		self.as_authority().port()
+	}
+}
+
+/// Percent-encoder for the user info.
+///
+/// Encodes every character that is not allowed in the user info.
+struct UserInfoEncoder;
+
+impl Encoder for UserInfoEncoder {
+	#[inline]
+	fn encode(&self, c: char) -> bool {
+		!(parsing::is_unreserved(c) || parsing::is_subdelim(c) || c == ':')
+	}
+}
+
+impl AsRef<[u8]> for AuthorityBuf {
+	#[inline]
+	fn as_ref(&self) -> &[u8] {
+		self.as_bytes()
+	}
+}
+
+impl<'a> From<Authority<'a>> for AuthorityBuf {
+	#[inline]
+	fn from(authority: Authority<'a>) -> AuthorityBuf {
+		AuthorityBuf {
+			data: authority.as_bytes().to_vec(),
+			p: authority.p,
+		}
+	}
+}
+
+impl<'a> TryFrom<&'a str> for AuthorityBuf {
+	type Error = Error;
+
+	#[inline]
+	fn try_from(str: &'a str) -> Result<AuthorityBuf, Error> {
+		Authority::try_from(str).map(Into::into)
+	}
+}
+
+impl<'a> From<&'a AuthorityBuf> for Authority<'a> {
+	#[inline]
+	fn from(buffer: &'a AuthorityBuf) -> Authority<'a> {
+		buffer.as_authority()
+	}
+}
+
+impl fmt::Display for AuthorityBuf {
+	#[inline]
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		self.as_str().fmt(f)
+	}
+}
+
+impl fmt::Debug for AuthorityBuf {
+	#[inline]
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		self.as_str().fmt(f)
+	}
+}
+
+impl cmp::PartialEq for AuthorityBuf {
+	#[inline]
+	fn eq(&self, other: &AuthorityBuf) -> bool {
+		self.as_authority() == other.as_authority()
+	}
+}
+
+impl Eq for AuthorityBuf {}
+
+impl<'a> cmp::PartialEq<Authority<'a>> for AuthorityBuf {
+	#[inline]
+	fn eq(&self, other: &Authority<'a>) -> bool {
+		self.as_authority() == *other
+	}
+}
+
+impl<'a> cmp::PartialEq<&'a str> for AuthorityBuf {
+	#[inline]
+	fn eq(&self, other: &&'a str) -> bool {
+		self.as_str() == *other
+	}
+}
+
+impl PartialOrd for AuthorityBuf {
+	#[inline]
+	fn partial_cmp(&self, other: &AuthorityBuf) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl Ord for AuthorityBuf {
+	#[inline]
+	fn cmp(&self, other: &AuthorityBuf) -> Ordering {
+		self.as_authority().cmp(&other.as_authority())
+	}
+}
+
+impl Hash for AuthorityBuf {
+	#[inline]
+	fn hash<H: Hasher>(&self, hasher: &mut H) {
+		self.as_authority().hash(hasher)
 	}
 }
 
@@ -162,10 +627,12 @@ pub struct AuthorityMut<'a> {
 }
 
 impl<'a> AuthorityMut<'a> {
+	/// Returns the current value of the authority.
 	#[inline]
-	pub fn as_authority(&'a self) -> Authority<'a> {
+	pub fn as_authority(&self) -> Authority<'_> {
+		let offset = self.offset;
 		Authority {
-			data: self.data.as_slice(),
+			data: &self.data[offset..(offset + self.p.len())],
 			p: *self.p,
 		}
 	}
@@ -222,6 +689,52 @@ impl<'a> AuthorityMut<'a> {
 		}
 	}
 
+	/// Set the user name part of the user info, keeping the password if any.
+	///
+	/// Adds a user info if there was none.
+	/// Returns [`Error::InvalidUserInfo`] if the user name is not a valid user info
+	/// or contains a `:`.
+	#[inline]
+	pub fn set_username(&mut self, username: &str) -> Result<(), Error> {
+		if username.contains(':') || UserInfo::try_from(username).is_err() {
+			return Err(Error::InvalidUserInfo);
+		}
+
+		let mut userinfo = username.as_bytes().to_vec();
+		if let Some(password) = self.as_authority().password() {
+			userinfo.push(b':');
+			userinfo.extend_from_slice(password.as_str().as_bytes());
+		}
+
+		self.set_userinfo(Some(UserInfo { data: &userinfo }));
+		Ok(())
+	}
+
+	/// Set or remove the password part of the user info, keeping the user name.
+	///
+	/// If there was no user info, the user name is empty.
+	/// Returns [`Error::InvalidUserInfo`] if the password is not a valid user info.
+	#[inline]
+	pub fn set_password(&mut self, password: Option<&str>) -> Result<(), Error> {
+		let username = self.as_authority().username();
+		let mut userinfo = username
+			.map(|username| username.as_str().as_bytes().to_vec())
+			.unwrap_or_default();
+
+		match password {
+			Some(password) => {
+				UserInfo::try_from(password)?;
+				userinfo.push(b':');
+				userinfo.extend_from_slice(password.as_bytes());
+			}
+			None if username.is_none() => return Ok(()),
+			None => (),
+		}
+
+		self.set_userinfo(Some(UserInfo { data: &userinfo }));
+		Ok(())
+	}
+
 	#[inline]
 	pub fn host(&self) -> Host {
 		let offset = self.offset + self.p.host_offset();
@@ -273,9 +786,59 @@ impl<'a> AuthorityMut<'a> {
 	}
 }
 
+impl<'a> fmt::Display for AuthorityMut<'a> {
+	#[inline]
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		self.as_str().fmt(f)
+	}
+}
+
+impl<'a> fmt::Debug for AuthorityMut<'a> {
+	#[inline]
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		self.as_str().fmt(f)
+	}
+}
+
+impl<'a, 'b> cmp::PartialEq<Authority<'b>> for AuthorityMut<'a> {
+	#[inline]
+	fn eq(&self, other: &Authority<'b>) -> bool {
+		self.as_authority() == *other
+	}
+}
+
+impl<'a, 'b> cmp::PartialEq<&'b str> for AuthorityMut<'a> {
+	#[inline]
+	fn eq(&self, other: &&'b str) -> bool {
+		pct_eq_str(self.as_authority().data, other)
+	}
+}
+
+/// Split the given user info into its user name and password, if any.
+#[inline]
+fn split_userinfo(userinfo: &[u8]) -> (&PctStr, Option<&PctStr>) {
+	let pct_str = |data| unsafe { PctStr::new_unchecked(std::str::from_utf8_unchecked(data)) };
+	match userinfo.iter().position(|c| *c == b':') {
+		Some(i) => (pct_str(&userinfo[..i]), Some(pct_str(&userinfo[(i + 1)..]))),
+		None => (pct_str(userinfo), None),
+	}
+}
+
+/// Removes the trailing dot of a fully qualified DNS name, if any.
+#[inline]
+fn strip_trailing_dot(name: &str) -> &str {
+	if name.len() > 1 {
+		name.strip_suffix('.').unwrap_or(name)
+	} else {
+		name
+	}
+}
+
 #[cfg(test)]
 mod tests {
-	use crate::Iri;
+	use crate::{Authority, AuthorityBuf, Error, Iri, IriBuf, Scheme};
+	use std::borrow::Cow;
+	use std::convert::TryFrom;
 
 	#[test]
 	fn explicit_empty_with_authority_alike_path() {
@@ -284,4 +847,193 @@ mod tests {
 
 		assert!(authority.unwrap().is_empty());
 	}
+
+	#[test]
+	fn from_parts() {
+		let challenges = [
+			(Some("user"), "example.org", Some(80), "user@example.org:80"),
+			(None, "example.org", Some(80), "example.org:80"),
+			(Some("user"), "example.org", None, "user@example.org"),
+			(None, "example.org", None, "example.org"),
+			(None, "::1", None, "[::1]"),
+			(None, "[::1]", Some(8080), "[::1]:8080"),
+			(Some("u:p"), "2001:db8::7", Some(0), "u:p@[2001:db8::7]:0"),
+			(Some("a@b c%"), "h", None, "a%40b%20c%25@h"),
+			(Some(""), "", None, "@"),
+		];
+
+		for (userinfo, host, port, expected) in &challenges {
+			let authority = AuthorityBuf::from_parts(*userinfo, host, *port).unwrap();
+			assert_eq!(authority.as_str(), *expected);
+			assert_eq!(authority, Authority::try_from(*expected).unwrap());
+			assert_eq!(authority.userinfo().is_some(), userinfo.is_some());
+			assert_eq!(
+				authority.port().map(|port| port.as_str().parse().unwrap()),
+				*port
+			);
+		}
+
+		let authority = AuthorityBuf::from_parts(None, "::1", None).unwrap();
+		assert_eq!(authority.host(), "[::1]");
+
+		assert_eq!(
+			AuthorityBuf::from_parts(None, "a b", None),
+			Err(Error::InvalidHost)
+		);
+		assert_eq!(
+			AuthorityBuf::from_parts(None, "a:b:", None),
+			Err(Error::InvalidHost)
+		);
+		assert_eq!(
+			AuthorityBuf::from_parts(None, "a/b", Some(1)),
+			Err(Error::InvalidHost)
+		);
+	}
+
+	#[test]
+	fn socket_str() {
+		let challenges = [
+			("example.org:8080", Some(80), "example.org:8080", true),
+			("example.org:8080", None, "example.org:8080", true),
+			("u:p@example.org", Some(80), "example.org:80", false),
+			("u:p@example.org", None, "example.org", true),
+			("example.org:", Some(443), "example.org:443", false),
+			("example.org:", None, "example.org", true),
+			("[::1]", Some(443), "[::1]:443", false),
+			(
+				"u@[2001:db8::7]:8443",
+				Some(443),
+				"[2001:db8::7]:8443",
+				true,
+			),
+			("127.0.0.1:0", Some(80), "127.0.0.1:0", true),
+			("Ex%61mple.ORG.:8080", Some(80), "example.org:8080", false),
+			("Ex%61mple.ORG.", None, "example.org", false),
+			("[::A]", Some(80), "[::a]:80", false),
+			("%FF.com:80", None, "%FF.com:80", false),
+		];
+
+		for (authority, default_port, expected, borrowed) in &challenges {
+			let authority = Authority::try_from(*authority).unwrap();
+			let socket = authority.socket_str(*default_port);
+			assert_eq!(socket, *expected);
+			assert_eq!(matches!(socket, Cow::Borrowed(_)), *borrowed);
+		}
+
+		use std::net::ToSocketAddrs;
+		let authority = Authority::try_from("[::1]").unwrap();
+		let addr = authority
+			.socket_str(Some(8080))
+			.to_socket_addrs()
+			.unwrap()
+			.next()
+			.unwrap();
+		assert_eq!(addr.port(), 8080);
+		assert!(addr.is_ipv6());
+	}
+
+	#[test]
+	fn username_password() {
+		let authority = Authority::try_from("us%3Aer:p:w@host").unwrap();
+		assert_eq!(authority.username().unwrap().as_str(), "us%3Aer");
+		assert_eq!(authority.password().unwrap().as_str(), "p:w");
+
+		let authority = Authority::try_from("user@host").unwrap();
+		assert_eq!(authority.username().unwrap().as_str(), "user");
+		assert!(authority.password().is_none());
+
+		let authority = Authority::try_from("host").unwrap();
+		assert!(authority.username().is_none());
+		assert!(authority.password().is_none());
+	}
+
+	#[test]
+	fn set_username_password() {
+		let mut iri = IriBuf::new("postgres://user:pw@host/db").unwrap();
+		let mut authority = iri.authority_mut().unwrap();
+		authority.set_username("admin").unwrap();
+		assert_eq!(authority.as_str(), "admin:pw@host");
+		authority.set_password(None).unwrap();
+		assert_eq!(authority.as_str(), "admin@host");
+		authority.set_password(Some("s:cret")).unwrap();
+		assert_eq!(authority.as_str(), "admin:s:cret@host");
+		assert_eq!(authority.set_username("a:b"), Err(Error::InvalidUserInfo));
+		assert_eq!(
+			authority.set_password(Some("a@b")),
+			Err(Error::InvalidUserInfo)
+		);
+
+		let mut iri = IriBuf::new("postgres://host/db").unwrap();
+		{
+			let mut authority = iri.authority_mut().unwrap();
+			authority.set_password(None).unwrap();
+			assert_eq!(authority.as_str(), "host");
+			authority.set_password(Some("pw")).unwrap();
+			assert_eq!(authority.as_str(), ":pw@host");
+		}
+		assert_eq!(iri, "postgres://:pw@host/db");
+	}
+
+	#[test]
+	fn eq_normalized() {
+		let a = Authority::try_from("user@[::A:b]:").unwrap();
+		let b = Authority::try_from("user@[::a:B]").unwrap();
+		let c = Authority::try_from("USER@[::a:b]").unwrap();
+
+		assert!(a != b);
+		assert!(a.eq_normalized(&b));
+		assert!(!a.eq_normalized(&c));
+	}
+
+	#[test]
+	fn host_matches() {
+		let a = Authority::try_from("user@Example.COM.:80").unwrap();
+		assert!(a.host_matches("example.com"));
+		assert!(a.host_matches("EXAMPLE.com."));
+		assert!(!a.host_matches("example.org"));
+		assert!(!a.host_matches("www.example.com"));
+
+		let ip = Authority::try_from("[::A]:80").unwrap();
+		assert!(ip.host_matches("[::a]"));
+
+		let invalid = Authority::try_from("%FF.com").unwrap();
+		assert!(!invalid.host_matches("%FF.com"));
+		assert!(!invalid.host_matches("\u{fffd}.com"));
+		assert!(!invalid.host_matches("%ff.com"));
+	}
+
+	#[test]
+	fn host_matches_wildcard() {
+		let a = Authority::try_from("a.Example.com").unwrap();
+		assert!(a.host_matches_wildcard("*.example.com"));
+		assert!(a.host_matches_wildcard("*.EXAMPLE.com."));
+		assert!(a.host_matches_wildcard("a.example.com"));
+		assert!(!a.host_matches_wildcard("*.com"));
+		assert!(!a.host_matches_wildcard("a*.example.com"));
+
+		let nested = Authority::try_from("a.b.example.com").unwrap();
+		assert!(!nested.host_matches_wildcard("*.example.com"));
+
+		let apex = Authority::try_from("example.com").unwrap();
+		assert!(!apex.host_matches_wildcard("*.example.com"));
+
+		let ip = Authority::try_from("1.2.3.4").unwrap();
+		assert!(!ip.host_matches_wildcard("*.2.3.4"));
+
+		let invalid = Authority::try_from("%FF.example.com").unwrap();
+		assert!(!invalid.host_matches_wildcard("*.example.com"));
+		assert!(!invalid.host_matches_wildcard("%FF.example.com"));
+	}
+
+	#[test]
+	fn eq_normalized_for_scheme() {
+		let http = Scheme::try_from("http").unwrap();
+		let a = Authority::try_from("user@Example.COM:080").unwrap();
+		let b = Authority::try_from("user@example.com").unwrap();
+		let c = Authority::try_from("user@example.com:8080").unwrap();
+
+		assert!(!a.eq_normalized(&b));
+		assert!(a.eq_normalized_for_scheme(&b, http));
+		assert!(!a.eq_normalized_for_scheme(&c, http));
+	}
 }
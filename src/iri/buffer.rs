@@ -1,15 +1,18 @@
 use std::{
+	borrow::Cow,
 	cmp::{Ord, Ordering, PartialOrd},
 	convert::TryFrom,
 	fmt,
 	hash::{Hash, Hasher},
-	ops::Deref,
+	ops::{AddAssign, Deref},
 	str::FromStr,
 };
 
 use crate::{
-	iri::Iri, parsing::ParsedIriRef, AsIri, AsIriRef, Authority, AuthorityMut, Error, Fragment,
-	IriRef, IriRefBuf, Path, PathMut, Query, Scheme,
+	iri::Iri,
+	parsing::{ParseOptions, ParsedIriRef},
+	AsIri, AsIriRef, Authority, AuthorityMut, EncodingPolicy, Error, Fragment, Host, IriRef,
+	IriRefBuf, NormalizeOptions, Path, PathMut, PctNormalizationPolicy, Query, QueryBuf, Scheme,
 };
 
 /// Owned IRI.
@@ -28,6 +31,49 @@ impl IriBuf {
 		}
 	}
 
+	/// Creates a new IRI by parsing and copying the input buffer, with the given parsing options.
+	///
+	/// See [`IriRef::new_with_options`] for more details.
+	#[inline]
+	pub fn new_with_options<S: AsRef<[u8]> + ?Sized>(
+		buffer: &S,
+		options: &ParseOptions,
+	) -> Result<Self, Error> {
+		let iri_ref = IriRefBuf::new_with_options(buffer, options)?;
+		if iri_ref.scheme().is_some() {
+			Ok(Self(iri_ref))
+		} else {
+			Err(Error::MissingScheme)
+		}
+	}
+
+	/// Creates a new IRI by parsing and copying the input buffer, and reports whether it
+	/// was already in normalized form.
+	///
+	/// The returned IRI is the input as is. The returned flag is `true` if normalizing it
+	/// (see [`IriBuf::normalize`]) would not change anything, that is if the input needs
+	/// no case folding of its scheme or host, no percent-encoding normalization and no dot
+	/// segment removal.
+	///
+	/// # Example
+	///
+	/// ```
+	/// # use iref::IriBuf;
+	/// let (iri, normalized) = IriBuf::new_reporting("http://example.org/a/b").unwrap();
+	/// assert!(normalized);
+	///
+	/// let (iri, normalized) = IriBuf::new_reporting("HTTP://example.org/a/../%62").unwrap();
+	/// assert!(!normalized);
+	/// assert_eq!(iri, "HTTP://example.org/a/../%62");
+	/// ```
+	pub fn new_reporting<S: AsRef<[u8]> + ?Sized>(buffer: &S) -> Result<(Self, bool), Error> {
+		let iri = Self::new(buffer)?;
+		let mut normalized = iri.clone();
+		normalized.normalize();
+		let is_normalized = normalized.as_bytes() == iri.as_bytes();
+		Ok((iri, is_normalized))
+	}
+
 	/// Creates a new IRI by parsing and the input buffer.
 	#[inline]
 	pub fn from_vec(buffer: Vec<u8>) -> Result<Self, (Error, Vec<u8>)> {
@@ -67,6 +113,12 @@ impl IriBuf {
 		self.0.into_raw_parts()
 	}
 
+	/// Consume the IRI and return its internal buffer.
+	#[inline]
+	pub fn into_bytes(self) -> Vec<u8> {
+		self.0.into_bytes()
+	}
+
 	/// Creates a new IRI using `buffer` and the parsing information `p`.
 	/// The parsing information is not checked against `buffer`.
 	///
@@ -87,6 +139,42 @@ impl IriBuf {
 		IriBuf(iri_ref)
 	}
 
+	/// Creates a new IRI `scheme:opaque` from a scheme and an opaque part,
+	/// such as `mailto:`, `tel:` or `urn:` IRIs.
+	///
+	/// The opaque part must be a valid rootless path: it cannot start with a `/`,
+	/// which would make the path absolute or introduce an authority.
+	/// The query and fragment are not part of the opaque part, and can be set afterward.
+	/// An empty opaque part gives the same IRI as [`IriBuf::from_scheme`].
+	///
+	/// # Example
+	///
+	/// ```
+	/// # use std::convert::TryFrom;
+	/// # use iref::{Error, IriBuf, Scheme};
+	/// let mailto = Scheme::try_from("mailto").unwrap();
+	///
+	/// let iri = IriBuf::from_scheme_and_opaque(mailto, "user@example.org").unwrap();
+	/// assert_eq!(iri, "mailto:user@example.org");
+	/// assert!(iri.authority().is_none());
+	///
+	/// assert_eq!(
+	/// 	IriBuf::from_scheme_and_opaque(mailto, "//example.org"),
+	/// 	Err(Error::InvalidPath)
+	/// );
+	/// ```
+	#[inline]
+	pub fn from_scheme_and_opaque(scheme: Scheme, opaque: &str) -> Result<Self, Error> {
+		let path = Path::try_from(opaque)?;
+		if path.is_absolute() {
+			return Err(Error::InvalidPath);
+		}
+
+		let mut iri = Self::from_scheme(scheme);
+		iri.set_path(path);
+		Ok(iri)
+	}
+
 	#[inline]
 	pub fn as_iri(&self) -> Iri {
 		Iri(self.0.as_iri_ref())
@@ -102,12 +190,78 @@ impl IriBuf {
 		self.0.scheme().unwrap()
 	}
 
+	/// Get the host of the IRI, if it has an authority.
+	///
+	/// See [`Iri::host`].
+	#[inline]
+	pub fn host(&self) -> Option<&str> {
+		self.as_iri().host()
+	}
+
+	/// Get the port of the IRI, if it has an authority with a port.
+	///
+	/// See [`Iri::port`].
+	#[inline]
+	pub fn port(&self) -> Option<&str> {
+		self.as_iri().port()
+	}
+
 	/// Set the scheme of the IRI.
+	///
+	/// The rest of the IRI, including the port, is left untouched.
+	/// Use [`IriBuf::set_scheme_adjusting_port`] to remove a port that was the default
+	/// port of the previous scheme.
 	#[inline]
 	pub fn set_scheme(&mut self, scheme: Scheme) {
 		self.0.set_scheme(Some(scheme))
 	}
 
+	/// Set the scheme of the IRI, removing the port if it was the default port of the
+	/// previous scheme.
+	///
+	/// The port is removed only if it is the default port of the previous scheme
+	/// (see [`Scheme::default_port`]) and the new scheme has a different default port,
+	/// so that upgrading `http://example.org:80/` gives `https://example.org/`.
+	/// Any other port is kept, as well as the port when the new scheme has no known
+	/// default port.
+	///
+	/// # Example
+	///
+	/// ```
+	/// # use std::convert::TryFrom;
+	/// # use iref::{IriBuf, Scheme};
+	/// let https = Scheme::try_from("https").unwrap();
+	///
+	/// let mut iri = IriBuf::new("http://example.org:80/a").unwrap();
+	/// iri.set_scheme_adjusting_port(https);
+	/// assert_eq!(iri, "https://example.org/a");
+	///
+	/// let mut iri = IriBuf::new("http://example.org:8080/a").unwrap();
+	/// iri.set_scheme_adjusting_port(https);
+	/// assert_eq!(iri, "https://example.org:8080/a");
+	/// ```
+	pub fn set_scheme_adjusting_port(&mut self, scheme: Scheme) {
+		let old_default = self.scheme().default_port();
+		let new_default = scheme.default_port();
+
+		if let (Some(old_default), Some(new_default)) = (old_default, new_default) {
+			if old_default != new_default {
+				if let Some(mut authority) = self.authority_mut() {
+					let is_default = match authority.port() {
+						Some(port) => port.trimmed() == old_default.to_string(),
+						None => false,
+					};
+
+					if is_default {
+						authority.set_port(None)
+					}
+				}
+			}
+		}
+
+		self.set_scheme(scheme)
+	}
+
 	#[inline]
 	pub fn authority_mut(&mut self) -> Option<AuthorityMut> {
 		self.0.authority_mut()
@@ -122,6 +276,124 @@ impl IriBuf {
 		self.0.set_authority(authority)
 	}
 
+	/// Set the host of the IRI, preserving the user info and port.
+	///
+	/// The host must be a valid registered name or IP literal, otherwise
+	/// [`Error::InvalidHost`] is returned.
+	/// If the IRI has no authority, one is created with the given host, unless the scheme
+	/// forbids it (see [`Scheme::authority_required`]) in which case
+	/// [`Error::UnexpectedAuthority`] is returned, or the path is rootless (such as `a/b`),
+	/// in which case [`Error::InvalidPath`] is returned since the path would be merged
+	/// into the host.
+	/// The IRI is unchanged if an error is returned.
+	///
+	/// # Example
+	///
+	/// ```
+	/// # use iref::{Error, IriBuf};
+	/// let mut iri = IriBuf::new("https://user@internal:8080/api?q#f").unwrap();
+	/// iri.set_host("example.org").unwrap();
+	/// assert_eq!(iri, "https://user@example.org:8080/api?q#f");
+	///
+	/// let mut iri = IriBuf::new("file:/etc/hosts").unwrap();
+	/// iri.set_host("localhost").unwrap();
+	/// assert_eq!(iri, "file://localhost/etc/hosts");
+	///
+	/// let mut iri = IriBuf::new("mailto:someone@example.org").unwrap();
+	/// assert_eq!(iri.set_host("example.org"), Err(Error::UnexpectedAuthority));
+	/// ```
+	pub fn set_host(&mut self, host: &str) -> Result<(), Error> {
+		if self.authority().is_none() && self.scheme().authority_required() == Some(false) {
+			Host::try_from(host)?;
+			return Err(Error::UnexpectedAuthority);
+		}
+
+		self.0.set_host(host)
+	}
+
+	/// Set the port of the IRI, creating the authority if needed.
+	///
+	/// See [`IriRefBuf::set_port`]. Returns [`Error::UnexpectedAuthority`] if an authority
+	/// must be created but the scheme forbids it.
+	pub fn set_port(&mut self, port: Option<&str>) -> Result<(), Error> {
+		if port.is_some()
+			&& self.authority().is_none()
+			&& self.scheme().authority_required() == Some(false)
+		{
+			return Err(Error::UnexpectedAuthority);
+		}
+
+		self.0.set_port(port)
+	}
+
+	/// Set the user info of the IRI, creating the authority if needed.
+	///
+	/// See [`IriRefBuf::set_userinfo`]. Returns [`Error::UnexpectedAuthority`] if an
+	/// authority must be created but the scheme forbids it.
+	pub fn set_userinfo(&mut self, userinfo: Option<&str>) -> Result<(), Error> {
+		if userinfo.is_some()
+			&& self.authority().is_none()
+			&& self.scheme().authority_required() == Some(false)
+		{
+			return Err(Error::UnexpectedAuthority);
+		}
+
+		self.0.set_userinfo(userinfo)
+	}
+
+	/// Normalizes the IRI.
+	///
+	/// See [`IriRefBuf::normalize`].
+	#[inline]
+	pub fn normalize(&mut self) {
+		self.0.normalize()
+	}
+
+	/// Normalizes the IRI with the given options.
+	///
+	/// See [`IriRefBuf::normalize_with`].
+	#[inline]
+	pub fn normalize_with(&mut self, options: NormalizeOptions) {
+		self.0.normalize_with(options)
+	}
+
+	/// Normalizes the percent-encoding of the IRI.
+	///
+	/// See [`IriRefBuf::normalize_percent_encoding`].
+	#[inline]
+	pub fn normalize_percent_encoding(&mut self) {
+		self.0.normalize_percent_encoding()
+	}
+
+	/// Normalizes the percent-encoding of the IRI, preserving the characters
+	/// declared active by the given policy.
+	///
+	/// See [`IriRefBuf::normalize_percent_encoding_with`].
+	#[inline]
+	pub fn normalize_percent_encoding_with<P: PctNormalizationPolicy + ?Sized>(
+		&mut self,
+		policy: &P,
+	) {
+		self.0.normalize_percent_encoding_with(policy)
+	}
+
+	/// Percent-encodes, in place, every non-ASCII character of the user info, path,
+	/// query and fragment.
+	///
+	/// See [`IriRefBuf::encode_non_ascii`].
+	#[inline]
+	pub fn encode_non_ascii(&mut self) {
+		self.0.encode_non_ascii()
+	}
+
+	/// Removes the dot segments of the path, leaving the other components untouched.
+	///
+	/// See [`IriRefBuf::normalize_path`].
+	#[inline]
+	pub fn normalize_path(&mut self) {
+		self.0.normalize_path()
+	}
+
 	#[inline]
 	pub fn path_mut(&mut self) -> PathMut {
 		self.0.path_mut()
@@ -133,15 +405,93 @@ impl IriBuf {
 		self.0.set_path(path)
 	}
 
+	/// Append the given suffix at the end of the path, joined with exactly one `/`.
+	///
+	/// Unlike [`IriRef::resolved`], the last segment of the path is kept:
+	/// appending `c/d` to `http://x/a/b` gives `http://x/a/b/c/d`,
+	/// where resolution would give `http://x/a/c/d`.
+	///
+	/// See [`IriRefBuf::append_path`].
+	///
+	/// # Example
+	///
+	/// ```
+	/// # use iref::IriBuf;
+	/// let mut iri = IriBuf::new("http://x/a/b#f").unwrap();
+	/// iri.append_path("c/d").unwrap();
+	/// assert_eq!(iri, "http://x/a/b/c/d#f");
+	/// ```
+	#[inline]
+	pub fn append_path(&mut self, suffix: &str) -> Result<(), Error> {
+		self.0.append_path(suffix)
+	}
+
+	/// Truncate the IRI to its base, removing the last path segment, the query and the
+	/// fragment.
+	///
+	/// See [`IriRefBuf::truncate_to_base`].
+	#[inline]
+	pub fn truncate_to_base(&mut self) {
+		self.0.truncate_to_base()
+	}
+
 	#[inline]
 	pub fn set_query(&mut self, query: Option<Query>) {
 		self.0.set_query(query)
 	}
 
+	/// Removes the query from the IRI and returns it, if any.
+	///
+	/// See [`IriRefBuf::take_query`].
+	#[inline]
+	pub fn take_query(&mut self) -> Option<QueryBuf> {
+		self.0.take_query()
+	}
+
 	#[inline]
 	pub fn set_fragment(&mut self, fragment: Option<Fragment>) {
 		self.0.set_fragment(fragment)
 	}
+
+	/// Set the query of the IRI from a string.
+	///
+	/// See [`IriRefBuf::set_query_str`].
+	#[inline]
+	pub fn set_query_str(&mut self, query: Option<&str>) -> Result<(), Error> {
+		self.0.set_query_str(query)
+	}
+
+	/// Set the query of the IRI from a string, using the given percent-encoding policy.
+	///
+	/// See [`IriRefBuf::set_query_str_with`].
+	#[inline]
+	pub fn set_query_str_with(
+		&mut self,
+		query: Option<&str>,
+		policy: EncodingPolicy,
+	) -> Result<(), Error> {
+		self.0.set_query_str_with(query, policy)
+	}
+
+	/// Set the fragment of the IRI from a string.
+	///
+	/// See [`IriRefBuf::set_fragment_str`].
+	#[inline]
+	pub fn set_fragment_str(&mut self, fragment: Option<&str>) -> Result<(), Error> {
+		self.0.set_fragment_str(fragment)
+	}
+
+	/// Set the fragment of the IRI from a string, using the given percent-encoding policy.
+	///
+	/// See [`IriRefBuf::set_fragment_str_with`].
+	#[inline]
+	pub fn set_fragment_str_with(
+		&mut self,
+		fragment: Option<&str>,
+		policy: EncodingPolicy,
+	) -> Result<(), Error> {
+		self.0.set_fragment_str_with(fragment, policy)
+	}
 }
 
 impl TryFrom<Vec<u8>> for IriBuf {
@@ -160,6 +510,29 @@ impl TryFrom<String> for IriBuf {
 	}
 }
 
+impl<'a> TryFrom<&'a str> for IriBuf {
+	type Error = Error;
+
+	#[inline]
+	fn try_from(s: &'a str) -> Result<Self, Error> {
+		Self::new(s)
+	}
+}
+
+impl From<IriBuf> for Vec<u8> {
+	#[inline]
+	fn from(iri: IriBuf) -> Vec<u8> {
+		iri.into_bytes()
+	}
+}
+
+impl From<IriBuf> for String {
+	#[inline]
+	fn from(iri: IriBuf) -> String {
+		iri.0.into()
+	}
+}
+
 impl FromStr for IriBuf {
 	type Err = Error;
 
@@ -175,6 +548,20 @@ impl AsIri for IriBuf {
 	}
 }
 
+impl AsRef<[u8]> for IriBuf {
+	#[inline]
+	fn as_ref(&self) -> &[u8] {
+		self.as_bytes()
+	}
+}
+
+impl AsRef<str> for IriBuf {
+	#[inline]
+	fn as_ref(&self) -> &str {
+		self.as_str()
+	}
+}
+
 impl AsIriRef for IriBuf {
 	#[inline]
 	fn as_iri_ref(&self) -> IriRef {
@@ -246,6 +633,90 @@ impl<'a> PartialEq<&'a str> for IriBuf {
 	}
 }
 
+impl PartialEq<[u8]> for IriBuf {
+	#[inline]
+	fn eq(&self, other: &[u8]) -> bool {
+		if let Ok(other) = Iri::new(other) {
+			self == &other
+		} else {
+			false
+		}
+	}
+}
+
+impl<'a> PartialEq<&'a [u8]> for IriBuf {
+	#[inline]
+	fn eq(&self, other: &&'a [u8]) -> bool {
+		self == *other
+	}
+}
+
+impl<'a, 'b> PartialEq<IriRef<'b>> for Cow<'a, IriBuf> {
+	#[inline]
+	fn eq(&self, other: &IriRef<'b>) -> bool {
+		self.as_iri_ref() == *other
+	}
+}
+
+impl<'a, 'b> PartialEq<Iri<'b>> for Cow<'a, IriBuf> {
+	#[inline]
+	fn eq(&self, other: &Iri<'b>) -> bool {
+		self.as_iri_ref() == other.as_iri_ref()
+	}
+}
+
+impl<'a> PartialEq<IriRefBuf> for Cow<'a, IriBuf> {
+	#[inline]
+	fn eq(&self, other: &IriRefBuf) -> bool {
+		self.as_iri_ref() == other.as_iri_ref()
+	}
+}
+
+impl<'a> PartialEq<IriBuf> for Cow<'a, IriBuf> {
+	#[inline]
+	fn eq(&self, other: &IriBuf) -> bool {
+		self.as_iri_ref() == other.as_iri_ref()
+	}
+}
+
+impl<'a, 'b> PartialEq<Cow<'a, IriBuf>> for IriRef<'b> {
+	#[inline]
+	fn eq(&self, other: &Cow<'a, IriBuf>) -> bool {
+		*self == other.as_iri_ref()
+	}
+}
+
+impl<'a, 'b> PartialEq<Cow<'a, IriBuf>> for Iri<'b> {
+	#[inline]
+	fn eq(&self, other: &Cow<'a, IriBuf>) -> bool {
+		self.as_iri_ref() == other.as_iri_ref()
+	}
+}
+
+impl<'a> PartialEq<Cow<'a, IriBuf>> for IriRefBuf {
+	#[inline]
+	fn eq(&self, other: &Cow<'a, IriBuf>) -> bool {
+		self.as_iri_ref() == other.as_iri_ref()
+	}
+}
+
+impl<'a> PartialEq<Cow<'a, IriBuf>> for IriBuf {
+	#[inline]
+	fn eq(&self, other: &Cow<'a, IriBuf>) -> bool {
+		self.as_iri_ref() == other.as_iri_ref()
+	}
+}
+
+/// Append a path segment.
+///
+/// See the [`IriRefBuf`] implementation for more details.
+impl<'a> AddAssign<&'a str> for IriBuf {
+	#[inline]
+	fn add_assign(&mut self, segment: &'a str) {
+		self.0 += segment
+	}
+}
+
 impl PartialOrd for IriBuf {
 	#[inline]
 	fn partial_cmp(&self, other: &IriBuf) -> Option<Ordering> {
@@ -281,11 +752,17 @@ impl PartialOrd<IriRefBuf> for IriBuf {
 	}
 }
 
+impl<'a> From<&'a IriBuf> for IriBuf {
+	#[inline]
+	fn from(iri: &'a IriBuf) -> IriBuf {
+		iri.clone()
+	}
+}
+
 impl<'a> From<Iri<'a>> for IriBuf {
 	#[inline]
 	fn from(iri: Iri<'a>) -> IriBuf {
-		let iri_ref_buf = iri.into();
-		IriBuf(iri_ref_buf)
+		iri.to_owned()
 	}
 }
 
@@ -310,6 +787,15 @@ impl<'a> TryFrom<IriRef<'a>> for IriBuf {
 	}
 }
 
+impl<'a> TryFrom<&'a IriRef<'a>> for IriBuf {
+	type Error = Error;
+
+	#[inline]
+	fn try_from(iri_ref: &'a IriRef<'a>) -> Result<IriBuf, Error> {
+		IriBuf::try_from(*iri_ref)
+	}
+}
+
 impl TryFrom<IriRefBuf> for IriBuf {
 	type Error = IriRefBuf;
 
@@ -323,6 +809,15 @@ impl TryFrom<IriRefBuf> for IriBuf {
 	}
 }
 
+impl<'a> TryFrom<&'a IriRefBuf> for IriBuf {
+	type Error = Error;
+
+	#[inline]
+	fn try_from(iri_ref: &'a IriRefBuf) -> Result<IriBuf, Error> {
+		IriBuf::try_from(iri_ref.as_iri_ref())
+	}
+}
+
 impl Hash for IriBuf {
 	#[inline]
 	fn hash<H: Hasher>(&self, hasher: &mut H) {
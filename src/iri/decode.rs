@@ -0,0 +1,203 @@
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::hash::Hasher;
+use std::iter::FusedIterator;
+
+/// Iterator over the percent-decoded bytes of an IRI component.
+///
+/// Each `%XX` sequence is replaced by the byte it encodes,
+/// other bytes are returned unchanged.
+/// Decoded bytes are not guaranteed to form a valid UTF-8 sequence.
+///
+/// Produced by the `percent_decode` method of each component.
+#[derive(Clone)]
+pub struct PercentDecode<'a> {
+	data: std::slice::Iter<'a, u8>,
+}
+
+impl<'a> PercentDecode<'a> {
+	#[inline]
+	pub(crate) fn new(data: &'a [u8]) -> PercentDecode<'a> {
+		PercentDecode { data: data.iter() }
+	}
+
+	/// Collect the decoded bytes.
+	#[inline]
+	pub fn into_bytes(self) -> Vec<u8> {
+		self.collect()
+	}
+}
+
+#[inline]
+fn hex_value(b: u8) -> Option<u8> {
+	(b as char).to_digit(16).map(|d| d as u8)
+}
+
+impl<'a> Iterator for PercentDecode<'a> {
+	type Item = u8;
+
+	#[inline]
+	fn next(&mut self) -> Option<u8> {
+		let b = *self.data.next()?;
+		if b == b'%' {
+			let mut lookahead = self.data.clone();
+			if let (Some(h), Some(l)) = (
+				lookahead.next().copied().and_then(hex_value),
+				lookahead.next().copied().and_then(hex_value),
+			) {
+				self.data = lookahead;
+				return Some(h * 0x10 + l);
+			}
+		}
+
+		Some(b)
+	}
+
+	#[inline]
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		let len = self.data.len();
+		(len / 3, Some(len))
+	}
+}
+
+impl<'a> FusedIterator for PercentDecode<'a> {}
+
+// The following functions work on the percent-decoded bytes of components.
+// Unlike the `PctStr` comparison, hashing and decoding functions, they do not
+// assume that the decoded bytes are valid UTF-8 (which does not hold for `%FF`),
+// and never panic.
+
+/// Checks that two percent-encoded strings have the same decoded bytes.
+#[inline]
+pub(crate) fn pct_eq(a: &[u8], b: &[u8]) -> bool {
+	a == b || PercentDecode::new(a).eq(PercentDecode::new(b))
+}
+
+/// Checks that the decoded bytes of a percent-encoded string are equal to `other`.
+#[inline]
+pub(crate) fn pct_eq_str(a: &[u8], other: &str) -> bool {
+	PercentDecode::new(a).eq(other.bytes())
+}
+
+/// Compares the decoded bytes of two percent-encoded strings.
+///
+/// For valid UTF-8 sequences, this is the same as comparing the decoded characters.
+#[inline]
+pub(crate) fn pct_cmp(a: &[u8], b: &[u8]) -> Ordering {
+	PercentDecode::new(a).cmp(PercentDecode::new(b))
+}
+
+/// Hashes the decoded bytes of a percent-encoded string.
+#[inline]
+pub(crate) fn pct_hash<H: Hasher>(data: &[u8], hasher: &mut H) {
+	for b in PercentDecode::new(data) {
+		hasher.write_u8(b)
+	}
+
+	// Same terminator as `str`, to make the hash prefix-free.
+	hasher.write_u8(0xff)
+}
+
+/// Percent-decodes the given string, replacing invalid UTF-8 sequences with
+/// `U+FFFD REPLACEMENT CHARACTER`.
+#[inline]
+pub(crate) fn decode_lossy(data: &str) -> Cow<'_, str> {
+	if data.contains('%') {
+		let bytes = PercentDecode::new(data.as_bytes()).into_bytes();
+		Cow::Owned(match String::from_utf8(bytes) {
+			Ok(decoded) => decoded,
+			Err(e) => String::from_utf8_lossy(e.as_bytes()).into_owned(),
+		})
+	} else {
+		Cow::Borrowed(data)
+	}
+}
+
+/// Iterator over the percent-encoded octets of an IRI-reference.
+///
+/// Each `%XX` sequence is returned as its byte offset in the IRI-reference,
+/// with the byte it encodes.
+///
+/// Produced by the [`IriRef::percent_escapes`](crate::IriRef::percent_escapes) method.
+#[derive(Clone)]
+pub struct PercentEscapes<'a> {
+	data: &'a [u8],
+	offset: usize,
+}
+
+impl<'a> PercentEscapes<'a> {
+	#[inline]
+	pub(crate) fn new(data: &'a [u8]) -> PercentEscapes<'a> {
+		PercentEscapes { data, offset: 0 }
+	}
+}
+
+impl<'a> Iterator for PercentEscapes<'a> {
+	type Item = (usize, u8);
+
+	#[inline]
+	fn next(&mut self) -> Option<(usize, u8)> {
+		while self.offset < self.data.len() {
+			let i = self.offset;
+			self.offset += 1;
+
+			if self.data[i] == b'%' {
+				if let (Some(h), Some(l)) = (
+					self.data.get(i + 1).copied().and_then(hex_value),
+					self.data.get(i + 2).copied().and_then(hex_value),
+				) {
+					self.offset += 2;
+					return Some((i, h * 0x10 + l));
+				}
+			}
+		}
+
+		None
+	}
+
+	#[inline]
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		(0, Some((self.data.len() - self.offset) / 3))
+	}
+}
+
+impl<'a> FusedIterator for PercentEscapes<'a> {}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn decode() {
+		let decoded: Vec<u8> = PercentDecode::new(b"a%20b%C3%A9%ff%2").collect();
+		assert_eq!(decoded, b"a b\xc3\xa9\xff%2")
+	}
+
+	#[test]
+	fn invalid_utf8() {
+		assert!(pct_eq(b"%FF", b"%ff"));
+		assert!(!pct_eq(b"%FF", b"%FE"));
+		assert!(pct_eq_str(b"a%20%C3%A9", "a é"));
+		assert!(!pct_eq_str(b"%FF", "\u{fffd}"));
+		assert_eq!(pct_cmp(b"%FF", b"%C3%A9"), Ordering::Greater);
+		assert_eq!(decode_lossy("a%20%FF%C3%A9"), "a \u{fffd}é");
+		assert!(matches!(decode_lossy("abc"), Cow::Borrowed("abc")));
+	}
+
+	#[test]
+	fn escapes() {
+		let escapes: Vec<_> = PercentEscapes::new(b"/%2e%2E%2f/%2500?%C3%A9%2").collect();
+		assert_eq!(
+			escapes,
+			[
+				(1, b'.'),
+				(4, b'.'),
+				(7, b'/'),
+				(11, b'%'),
+				(17, 0xc3),
+				(20, 0xa9)
+			]
+		);
+		assert_eq!(PercentEscapes::new(b"abc").count(), 0);
+	}
+}
@@ -1,4 +1,5 @@
-use super::Error;
+use super::decode::{pct_cmp, pct_eq, pct_hash};
+use super::{Error, PercentDecode};
 use crate::parsing;
 use pct_str::PctStr;
 use std::cmp::{Ord, Ordering, PartialOrd};
@@ -31,11 +32,23 @@ impl<'a> Fragment<'a> {
 		unsafe { PctStr::new_unchecked(self.as_str()) }
 	}
 
+	/// Returns an iterator over the percent-decoded bytes of the fragment.
+	#[inline]
+	pub fn percent_decode(&self) -> PercentDecode<'a> {
+		PercentDecode::new(self.data)
+	}
+
 	/// Checks if the fragment is empty.
 	#[inline]
 	pub fn is_empty(&self) -> bool {
 		self.data.is_empty()
 	}
+
+	/// Convert the fragment slice into the owned version [`FragmentBuf`].
+	#[inline]
+	pub fn to_owned(self) -> FragmentBuf {
+		self.into()
+	}
 }
 
 impl<'a> AsRef<[u8]> for Fragment<'a> {
@@ -73,7 +86,7 @@ impl<'a> fmt::Debug for Fragment<'a> {
 
 impl<'a> cmp::PartialEq for Fragment<'a> {
 	fn eq(&self, other: &Fragment) -> bool {
-		self.as_pct_str() == other.as_pct_str()
+		pct_eq(self.data, other.data)
 	}
 }
 
@@ -93,12 +106,151 @@ impl<'a> PartialOrd for Fragment<'a> {
 
 impl<'a> Ord for Fragment<'a> {
 	fn cmp(&self, other: &Fragment<'a>) -> Ordering {
-		self.as_pct_str().cmp(other.as_pct_str())
+		pct_cmp(self.data, other.data)
 	}
 }
 
 impl<'a> Hash for Fragment<'a> {
 	fn hash<H: Hasher>(&self, hasher: &mut H) {
-		self.as_pct_str().hash(hasher)
+		pct_hash(self.data, hasher)
+	}
+}
+
+/// Owned fragment.
+///
+/// Can be used to store the fragment of an IRI independently of it,
+/// and reattach it later using the `set_fragment` method of [`IriRefBuf`](crate::IriRefBuf)
+/// or [`IriBuf`](crate::IriBuf).
+#[derive(Clone)]
+pub struct FragmentBuf {
+	/// The fragment data.
+	data: Vec<u8>,
+}
+
+impl FragmentBuf {
+	/// Returns a reference to the byte representation of the fragment.
+	#[inline]
+	pub fn as_bytes(&self) -> &[u8] {
+		&self.data
+	}
+
+	/// Consume the fragment and return its internal buffer.
+	#[inline]
+	pub fn into_bytes(self) -> Vec<u8> {
+		self.data
+	}
+
+	/// Get the fragment as a string slice.
+	#[inline]
+	pub fn as_str(&self) -> &str {
+		unsafe { std::str::from_utf8_unchecked(&self.data) }
+	}
+
+	/// Get the fragment as a percent-encoded string slice.
+	#[inline]
+	pub fn as_pct_str(&self) -> &PctStr {
+		unsafe { PctStr::new_unchecked(self.as_str()) }
+	}
+
+	/// Borrow the fragment buffer as a fragment slice.
+	#[inline]
+	pub fn as_fragment(&self) -> Fragment<'_> {
+		Fragment { data: &self.data }
+	}
+
+	/// Checks if the fragment is empty.
+	#[inline]
+	pub fn is_empty(&self) -> bool {
+		self.data.is_empty()
+	}
+}
+
+impl AsRef<[u8]> for FragmentBuf {
+	#[inline]
+	fn as_ref(&self) -> &[u8] {
+		self.as_bytes()
+	}
+}
+
+impl<'a> From<Fragment<'a>> for FragmentBuf {
+	#[inline]
+	fn from(fragment: Fragment<'a>) -> FragmentBuf {
+		FragmentBuf {
+			data: fragment.data.to_vec(),
+		}
+	}
+}
+
+impl<'a> TryFrom<&'a str> for FragmentBuf {
+	type Error = Error;
+
+	#[inline]
+	fn try_from(str: &'a str) -> Result<FragmentBuf, Error> {
+		Fragment::try_from(str).map(Into::into)
+	}
+}
+
+impl<'a> From<&'a FragmentBuf> for Fragment<'a> {
+	#[inline]
+	fn from(buffer: &'a FragmentBuf) -> Fragment<'a> {
+		buffer.as_fragment()
+	}
+}
+
+impl fmt::Display for FragmentBuf {
+	#[inline]
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		self.as_str().fmt(f)
+	}
+}
+
+impl fmt::Debug for FragmentBuf {
+	#[inline]
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		self.as_str().fmt(f)
+	}
+}
+
+impl cmp::PartialEq for FragmentBuf {
+	#[inline]
+	fn eq(&self, other: &FragmentBuf) -> bool {
+		self.as_fragment() == other.as_fragment()
+	}
+}
+
+impl Eq for FragmentBuf {}
+
+impl<'a> cmp::PartialEq<Fragment<'a>> for FragmentBuf {
+	#[inline]
+	fn eq(&self, other: &Fragment<'a>) -> bool {
+		self.as_fragment() == *other
+	}
+}
+
+impl<'a> cmp::PartialEq<&'a str> for FragmentBuf {
+	#[inline]
+	fn eq(&self, other: &&'a str) -> bool {
+		self.as_str() == *other
+	}
+}
+
+impl PartialOrd for FragmentBuf {
+	#[inline]
+	fn partial_cmp(&self, other: &FragmentBuf) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl Ord for FragmentBuf {
+	#[inline]
+	fn cmp(&self, other: &FragmentBuf) -> Ordering {
+		self.as_fragment().cmp(&other.as_fragment())
+	}
+}
+
+impl Hash for FragmentBuf {
+	#[inline]
+	fn hash<H: Hasher>(&self, hasher: &mut H) {
+		self.as_fragment().hash(hasher)
 	}
 }
@@ -1,6 +1,8 @@
-use super::Error;
+use super::decode::{pct_cmp, pct_eq, pct_hash};
+use super::{Error, PercentDecode};
 use crate::parsing;
 use pct_str::PctStr;
+use std::borrow::Cow;
 use std::cmp::{Ord, Ordering, PartialOrd};
 use std::convert::TryFrom;
 use std::hash::{Hash, Hasher};
@@ -31,11 +33,190 @@ impl<'a> Host<'a> {
 		unsafe { PctStr::new_unchecked(self.as_str()) }
 	}
 
+	/// Returns an iterator over the percent-decoded bytes of the host.
+	#[inline]
+	pub fn percent_decode(&self) -> PercentDecode<'a> {
+		PercentDecode::new(self.data)
+	}
+
 	/// Checks if the host is empty.
 	#[inline]
 	pub fn is_empty(&self) -> bool {
 		self.data.is_empty()
 	}
+
+	/// Compare two hosts, ignoring the case of ASCII letters.
+	///
+	/// Hosts are case-insensitive, so `Example.COM` and `example.com` designate the same host.
+	/// Percent-encoded characters are decoded before comparison.
+	#[inline]
+	pub fn eq_ignore_ascii_case(&self, other: &Host) -> bool {
+		self.percent_decode()
+			.map(|b| b.to_ascii_lowercase())
+			.eq(other.percent_decode().map(|b| b.to_ascii_lowercase()))
+	}
+
+	/// Returns the canonical form of the host, suitable to decide if two hosts are the same.
+	///
+	/// The canonical host is computed as follows:
+	///  - percent-encoded characters allowed in a registered name are decoded,
+	///    other escapes (such as `%2F` or the invalid UTF-8 `%FF`) are kept with
+	///    uppercase hexadecimal digits;
+	///  - the trailing dot of a fully qualified domain name (such as `example.com.`) is removed;
+	///  - ASCII letters are lowercased.
+	///
+	/// IP literals (such as `[::1]`) are only lowercased.
+	/// The host is borrowed if it is already canonical.
+	///
+	/// With the `idna` feature enabled, the host is also converted to its ASCII form
+	/// (ToASCII): the ideographic full stops (`。`, `．` and `｡`) are label separators,
+	/// non-ASCII labels are lowercased and encoded with
+	/// [Punycode](https://tools.ietf.org/html/rfc3492) behind the `xn--` prefix.
+	/// The full mapping and validation of [UTS #46](https://unicode.org/reports/tr46/)
+	/// are not performed.
+	///
+	/// # Example
+	///
+	/// ```
+	/// # use std::convert::TryFrom;
+	/// # use iref::Host;
+	/// let host = Host::try_from("WWW.Example.COM.").unwrap();
+	/// assert_eq!(host.canonical(), "www.example.com");
+	///
+	/// let host = Host::try_from("例え.JP").unwrap();
+	/// # #[cfg(not(feature = "idna"))]
+	/// assert_eq!(host.canonical(), "例え.jp");
+	/// # #[cfg(feature = "idna")]
+	/// # assert_eq!(host.canonical(), "xn--r8jz45g.jp");
+	/// ```
+	pub fn canonical(&self) -> Cow<'a, str> {
+		let host: &'a str = unsafe { std::str::from_utf8_unchecked(self.data) };
+
+		if host.starts_with('[') {
+			return if host.bytes().any(|b| b.is_ascii_uppercase()) {
+				Cow::Owned(host.to_ascii_lowercase())
+			} else {
+				Cow::Borrowed(host)
+			};
+		}
+
+		let is_canonical = !(host.len() > 1 && host.ends_with('.'))
+			&& host
+				.bytes()
+				.all(|b| b.is_ascii() && b != b'%' && !b.is_ascii_uppercase());
+		if is_canonical {
+			return Cow::Borrowed(host);
+		}
+
+		let mut canonical = decode_reg_name(host);
+
+		#[cfg(feature = "idna")]
+		{
+			canonical = canonical.replace(&['\u{3002}', '\u{ff0e}', '\u{ff61}'][..], ".");
+		}
+
+		if canonical.len() > 1 && canonical.ends_with('.') {
+			canonical.pop();
+		}
+
+		#[cfg(feature = "idna")]
+		{
+			canonical = canonical
+				.split('.')
+				.map(|label| {
+					if label.is_ascii() || label.contains('%') {
+						label.to_string()
+					} else {
+						let label = label.to_lowercase();
+						match super::punycode::encode(&label) {
+							Some(encoded) => format!("xn--{}", encoded),
+							None => label,
+						}
+					}
+				})
+				.collect::<Vec<_>>()
+				.join(".");
+		}
+
+		Cow::Owned(lowercase_ascii(&canonical))
+	}
+}
+
+/// Decodes the escapes of the given registered name that encode characters allowed
+/// in a registered name.
+///
+/// The other escapes are kept, with uppercase hexadecimal digits.
+fn decode_reg_name(host: &str) -> String {
+	let mut result = String::with_capacity(host.len());
+	let mut escaped = Vec::new();
+	let mut chars = host.char_indices().peekable();
+
+	while let Some((i, c)) = chars.next() {
+		if c != '%' {
+			result.push(c);
+			continue;
+		}
+
+		// Escapes are decoded together since a character may span several of them.
+		escaped.clear();
+		escaped.extend(PercentDecode::new(&host.as_bytes()[i..(i + 3)]));
+		chars.nth(1);
+		while let Some((i, '%')) = chars.peek().copied() {
+			escaped.extend(PercentDecode::new(&host.as_bytes()[i..(i + 3)]));
+			chars.nth(2);
+		}
+
+		let mut bytes = &escaped[..];
+		while !bytes.is_empty() {
+			let (valid, invalid_len) = match std::str::from_utf8(bytes) {
+				Ok(valid) => (valid, 0),
+				Err(e) => (
+					unsafe { std::str::from_utf8_unchecked(&bytes[..e.valid_up_to()]) },
+					e.error_len().unwrap_or(bytes.len() - e.valid_up_to()),
+				),
+			};
+
+			for c in valid.chars() {
+				if parsing::is_unreserved(c) || parsing::is_subdelim(c) {
+					result.push(c)
+				} else {
+					let mut buffer = [0; 4];
+					push_escapes(c.encode_utf8(&mut buffer).as_bytes(), &mut result)
+				}
+			}
+
+			let invalid = &bytes[valid.len()..(valid.len() + invalid_len)];
+			push_escapes(invalid, &mut result);
+			bytes = &bytes[(valid.len() + invalid_len)..];
+		}
+	}
+
+	result
+}
+
+fn push_escapes(bytes: &[u8], output: &mut String) {
+	for b in bytes {
+		output.push_str(&format!("%{:02X}", b))
+	}
+}
+
+/// Lowercases the ASCII letters of the given string, except for the hexadecimal
+/// digits of escapes.
+fn lowercase_ascii(host: &str) -> String {
+	let mut escape = 0;
+	host.chars()
+		.map(|c| {
+			if c == '%' {
+				escape = 2;
+				c
+			} else if escape > 0 {
+				escape -= 1;
+				c
+			} else {
+				c.to_ascii_lowercase()
+			}
+		})
+		.collect()
 }
 
 impl<'a> AsRef<[u8]> for Host<'a> {
@@ -76,7 +257,7 @@ impl<'a> fmt::Debug for Host<'a> {
 impl<'a> cmp::PartialEq for Host<'a> {
 	#[inline]
 	fn eq(&self, other: &Host) -> bool {
-		self.as_pct_str() == other.as_pct_str()
+		pct_eq(self.data, other.data)
 	}
 }
 
@@ -99,13 +280,13 @@ impl<'a> PartialOrd for Host<'a> {
 impl<'a> Ord for Host<'a> {
 	#[inline]
 	fn cmp(&self, other: &Host<'a>) -> Ordering {
-		self.as_pct_str().cmp(other.as_pct_str())
+		pct_cmp(self.data, other.data)
 	}
 }
 
 impl<'a> Hash for Host<'a> {
 	#[inline]
 	fn hash<H: Hasher>(&self, hasher: &mut H) {
-		self.as_pct_str().hash(hasher)
+		pct_hash(self.data, hasher)
 	}
 }
@@ -1,29 +1,39 @@
+mod ascii;
 mod authority;
 mod buffer;
+mod decode;
 mod fragment;
 mod host;
 mod path;
 mod port;
+#[cfg(feature = "idna")]
+mod punycode;
 mod query;
+#[cfg(feature = "serde")]
+mod query_de;
 mod scheme;
 mod segment;
 mod userinfo;
 
-use crate::{IriRef, IriRefBuf};
+use crate::{parsing::ParseOptions, IriRef, IriRefBuf};
 use std::cmp::{Ord, Ordering, PartialOrd};
-use std::convert::TryFrom;
+use std::convert::{TryFrom, TryInto};
 use std::error::Error as StdError;
 use std::fmt;
 use std::hash::{Hash, Hasher};
 use std::ops::Deref;
 
+pub use self::ascii::*;
 pub use self::authority::*;
 pub use self::buffer::*;
+pub use self::decode::*;
 pub use self::fragment::*;
 pub use self::host::*;
 pub use self::path::*;
 pub use self::port::*;
 pub use self::query::*;
+#[cfg(feature = "serde")]
+pub use self::query_de::*;
 pub use self::scheme::*;
 pub use self::segment::*;
 pub use self::userinfo::*;
@@ -39,8 +49,12 @@ pub enum Error {
 
 	/// The IRI part support percent-encoding, but the input data as an invalid percent-encoded
 	/// character.
+	/// Every `%` must be followed by exactly two hexadecimal digits.
 	/// This can occur for instance while trying to parse a query with the invalid percent encoded
-	/// character `%9a`: `Query::try_from("Hello Error %9a")`.
+	/// character `%2G`: `Query::try_from("Hello%2G")`.
+	///
+	/// Use [`find_invalid_pct_encoded`](crate::parsing::find_invalid_pct_encoded) to locate
+	/// the malformed sequence.
 	InvalidPercentEncoding,
 
 	/// Occurs when one is trying to convert an [`IriRef`] with no scheme into an [`Iri`],
@@ -81,6 +95,60 @@ pub enum Error {
 
 	/// Occurs when a [`Fragment`] part is not syntactically valid.
 	InvalidFragment,
+
+	/// Occurs when an IRI has no authority while its scheme requires one.
+	///
+	/// See [`Iri::validate_scheme_constraints`].
+	MissingAuthority,
+
+	/// Occurs when an IRI has an authority while its scheme forbids it.
+	///
+	/// See [`Iri::validate_scheme_constraints`].
+	UnexpectedAuthority,
+
+	/// Occurs when an IRI has an empty path while its scheme forbids it.
+	///
+	/// See [`Iri::validate_scheme_constraints`].
+	MissingPath,
+
+	/// Occurs when the input contains a bidirectional formatting character.
+	///
+	/// See [`IriRefBuf::new_checked`].
+	BidiFormattingCharacter,
+
+	/// Occurs when a resolution chain is longer than the allowed maximum.
+	///
+	/// See [`Iri::resolve_chain`].
+	ChainTooLong,
+
+	/// Occurs when some parsing data does not match the data it describes.
+	///
+	/// See [`ParsedIriRef::check_against`](crate::parsing::ParsedIriRef::check_against).
+	InvalidParsingData,
+
+	/// Occurs when an [`IriTemplate`](crate::IriTemplate) is not syntactically valid,
+	/// or when a prefix modifier is applied to a composite value during its expansion.
+	InvalidTemplate,
+
+	/// Occurs when a reference expected to stay on the same origin has a scheme or an
+	/// authority.
+	///
+	/// See [`Iri::resolve_local`].
+	NonLocalReference,
+
+	/// Occurs when the path of a reference has `..` segments going above its root, which
+	/// cannot be removed without changing its meaning.
+	///
+	/// See [`IriRefBuf::try_normalize`].
+	PathEscapesRoot,
+
+	/// Occurs when the input is longer than the maximum length given in the
+	/// [`ParseOptions`](crate::parsing::ParseOptions).
+	TooLong,
+
+	/// Occurs when the input [`Path`] has more segments than the maximum given in the
+	/// [`ParseOptions`](crate::parsing::ParseOptions).
+	TooManySegments,
 }
 
 impl fmt::Display for Error {
@@ -98,6 +166,17 @@ impl fmt::Display for Error {
 			Error::InvalidPath => "Invalid path",
 			Error::InvalidQuery => "Invalid query",
 			Error::InvalidFragment => "Invalid fragment",
+			Error::MissingAuthority => "Missing authority",
+			Error::UnexpectedAuthority => "Unexpected authority",
+			Error::MissingPath => "Missing path",
+			Error::BidiFormattingCharacter => "Bidirectional formatting character",
+			Error::ChainTooLong => "Resolution chain too long",
+			Error::InvalidParsingData => "Invalid parsing data",
+			Error::InvalidTemplate => "Invalid template",
+			Error::NonLocalReference => "Non-local reference",
+			Error::PathEscapesRoot => "Path escapes its root",
+			Error::TooLong => "Input too long",
+			Error::TooManySegments => "Too many path segments",
 		})
 	}
 }
@@ -130,7 +209,7 @@ impl StdError for Error {}
 /// # }
 /// ```
 #[derive(Clone, Copy)]
-pub struct Iri<'a>(IriRef<'a>);
+pub struct Iri<'a>(pub(crate) IriRef<'a>);
 
 impl<'a> Iri<'a> {
 	/// Create a new IRI slice from a bytes slice.
@@ -146,6 +225,22 @@ impl<'a> Iri<'a> {
 		}
 	}
 
+	/// Create a new IRI slice from a bytes slice, with the given parsing options.
+	///
+	/// See [`IriRef::new_with_options`] for more details.
+	#[inline]
+	pub fn new_with_options<S: AsRef<[u8]> + ?Sized>(
+		buffer: &'a S,
+		options: &ParseOptions,
+	) -> Result<Iri<'a>, Error> {
+		let iri_ref = IriRef::new_with_options(buffer, options)?;
+		if iri_ref.scheme().is_some() {
+			Ok(Iri(iri_ref))
+		} else {
+			Err(Error::MissingScheme)
+		}
+	}
+
 	/// Create a new IRI from a string.
 	///
 	/// This replaces a [`std::str::FromStr`] implementation as the trait is
@@ -181,6 +276,455 @@ impl<'a> Iri<'a> {
 	pub fn scheme(&self) -> Scheme {
 		self.0.scheme().unwrap()
 	}
+
+	/// Get the host of the IRI, if it has an authority.
+	///
+	/// This is a shortcut for `iri.authority().map(|a| a.host())`.
+	///
+	/// # Example
+	///
+	/// ```
+	/// # use iref::Iri;
+	/// assert_eq!(Iri::new("https://example.org:8080/path").unwrap().host(), Some("example.org"));
+	/// assert_eq!(Iri::new("mailto:someone@example.org").unwrap().host(), None);
+	/// ```
+	#[inline]
+	pub fn host(&self) -> Option<&'a str> {
+		self.authority()
+			.map(|authority| unsafe { std::str::from_utf8_unchecked(authority.host().data) })
+	}
+
+	/// Get the port of the IRI, if it has an authority with a port.
+	///
+	/// This is a shortcut for `iri.authority().and_then(|a| a.port())`.
+	///
+	/// # Example
+	///
+	/// ```
+	/// # use iref::Iri;
+	/// assert_eq!(Iri::new("https://example.org:8080/path").unwrap().port(), Some("8080"));
+	/// assert_eq!(Iri::new("https://example.org/path").unwrap().port(), None);
+	/// ```
+	#[inline]
+	pub fn port(&self) -> Option<&'a str> {
+		self.authority()
+			.and_then(|authority| authority.port())
+			.map(|port| unsafe { std::str::from_utf8_unchecked(port.data) })
+	}
+
+	/// Checks the scheme-specific structural rules of this IRI.
+	///
+	/// Some well-known schemes require an authority with a non-empty host (such as `http`),
+	/// while others forbid an authority and require a non-empty path (such as `mailto`).
+	/// See [`Scheme::authority_required`] for the list of known schemes.
+	/// IRIs with other schemes are always valid.
+	///
+	/// This catches IRIs that are syntactically valid but semantically wrong for their scheme.
+	///
+	/// # Example
+	///
+	/// ```
+	/// # use iref::{Iri, Error};
+	/// assert!(Iri::new("http://example.org/path").unwrap().validate_scheme_constraints().is_ok());
+	/// assert_eq!(
+	/// 	Iri::new("http:/path").unwrap().validate_scheme_constraints(),
+	/// 	Err(Error::MissingAuthority)
+	/// );
+	/// assert_eq!(
+	/// 	Iri::new("mailto:").unwrap().validate_scheme_constraints(),
+	/// 	Err(Error::MissingPath)
+	/// );
+	/// ```
+	pub fn validate_scheme_constraints(&self) -> Result<(), Error> {
+		match self.scheme().authority_required() {
+			Some(true) => match self.authority() {
+				Some(authority) if authority.host().is_empty() => Err(Error::InvalidHost),
+				Some(_) => Ok(()),
+				None => Err(Error::MissingAuthority),
+			},
+			Some(false) => {
+				if self.authority().is_some() {
+					Err(Error::UnexpectedAuthority)
+				} else if self.path().as_bytes().is_empty() {
+					Err(Error::MissingPath)
+				} else {
+					Ok(())
+				}
+			}
+			None => Ok(()),
+		}
+	}
+
+	/// Returns a copy of this IRI without its port if it is the default port of the scheme.
+	///
+	/// The default port of a scheme is given by [`Scheme::default_port`].
+	/// Everything else is left untouched, and the IRI is unchanged if the port is not the
+	/// default one or if the scheme has no known default port.
+	///
+	/// # Example
+	///
+	/// ```
+	/// # use iref::Iri;
+	/// let iri = Iri::new("https://example.org:443/foo").unwrap();
+	/// assert_eq!(iri.without_default_port(), "https://example.org/foo");
+	///
+	/// let iri = Iri::new("http://example.org:443/foo").unwrap();
+	/// assert_eq!(iri.without_default_port(), "http://example.org:443/foo");
+	/// ```
+	#[inline]
+	pub fn without_default_port(&self) -> IriBuf {
+		let mut iri = IriBuf::from(*self);
+
+		if let Some(default_port) = self.scheme().default_port() {
+			if let Some(mut authority) = iri.authority_mut() {
+				let is_default = match authority.port() {
+					Some(port) => port.trimmed() == default_port.to_string(),
+					None => false,
+				};
+
+				if is_default {
+					authority.set_port(None)
+				}
+			}
+		}
+
+		iri
+	}
+
+	/// Checks if the two IRIs designate the same resource, in the pragmatic sense used by
+	/// web browsers ("are these the same page").
+	///
+	/// This is a heuristic, distinct from the syntax-based normalization of
+	/// [RFC 3986](https://tools.ietf.org/html/rfc3986#section-6.2.2).
+	/// The following rules are applied:
+	///  - the schemes are compared case-insensitively (`HTTP` is `http`);
+	///  - the authorities are compared with [`Authority::eq_normalized_for_scheme`]:
+	///    the host is case-insensitive (ASCII only), the user info is case-sensitive,
+	///    an empty port is no port, and the default port of the scheme
+	///    (see [`Scheme::default_port`]) is no port;
+	///  - when an authority is present, an empty path is equivalent to `/`;
+	///  - the paths are compared as in the [`PartialEq`] implementation of [`Path`],
+	///    ignoring percent-encoding and dot segments;
+	///  - the queries are compared ignoring percent-encoding, and an absent query
+	///    differs from an empty one;
+	///  - the fragments are ignored, since they designate a part of the resource.
+	///    Use [`Iri::same_resource_and_fragment`] to also compare them.
+	///
+	/// Different schemes are never equivalent, so `http://x` and `https://x` are not
+	/// the same resource.
+	///
+	/// # Example
+	///
+	/// ```
+	/// # use iref::Iri;
+	/// let a = Iri::new("HTTP://Example.org:80#top").unwrap();
+	/// let b = Iri::new("http://example.org/").unwrap();
+	/// assert!(a.same_resource(&b));
+	///
+	/// let c = Iri::new("https://example.org/").unwrap();
+	/// assert!(!b.same_resource(&c));
+	/// ```
+	#[inline]
+	pub fn same_resource(&self, other: &Iri) -> bool {
+		let scheme = self.scheme();
+		if !scheme
+			.as_str()
+			.eq_ignore_ascii_case(other.scheme().as_str())
+		{
+			return false;
+		}
+
+		let self_authority = self.authority();
+		let other_authority = other.authority();
+		let has_authority = match (&self_authority, &other_authority) {
+			(None, None) => false,
+			(Some(a), Some(b)) if a.eq_normalized_for_scheme(b, scheme) => true,
+			_ => return false,
+		};
+
+		fn or_root(path: Path<'_>, has_authority: bool) -> Path<'_> {
+			if has_authority && path.is_empty() {
+				Path { data: b"/" }
+			} else {
+				path
+			}
+		}
+
+		or_root(self.path(), has_authority) == or_root(other.path(), has_authority)
+			&& self.query() == other.query()
+	}
+
+	/// Checks if the two IRIs designate the same resource and have equivalent fragments.
+	///
+	/// This applies the same rules as [`Iri::same_resource`], and additionally requires the
+	/// fragments to be equal up to percent-encoding.
+	/// An absent fragment differs from an empty one.
+	///
+	/// # Example
+	///
+	/// ```
+	/// # use iref::Iri;
+	/// let a = Iri::new("http://example.org:80#t%6Fp").unwrap();
+	/// assert!(a.same_resource_and_fragment(&Iri::new("http://example.org/#top").unwrap()));
+	/// assert!(!a.same_resource_and_fragment(&Iri::new("http://example.org/").unwrap()));
+	/// ```
+	#[inline]
+	pub fn same_resource_and_fragment(&self, other: &Iri) -> bool {
+		self.same_resource(other) && self.fragment() == other.fragment()
+	}
+
+	/// Returns the bytes of the normalized IRI.
+	///
+	/// The IRI is normalized with [`IriRefBuf::normalize`], performing the syntax-based
+	/// normalization of [RFC 3986](https://tools.ietf.org/html/rfc3986#section-6.2.2).
+	/// Equivalent IRIs up to this normalization give the same bytes, which can be fed to a hash
+	/// function or signed.
+	///
+	/// # Example
+	///
+	/// ```
+	/// # use iref::Iri;
+	/// let a = Iri::new("HTTP://Example.org/a/./b/../%7Ec").unwrap();
+	/// let b = Iri::new("http://example.org/a/~c").unwrap();
+	///
+	/// assert_eq!(a.to_canonical_bytes(), b"http://example.org/a/~c");
+	/// assert_eq!(a.to_canonical_bytes(), b.to_canonical_bytes());
+	/// ```
+	#[inline]
+	pub fn to_canonical_bytes(&self) -> Vec<u8> {
+		let mut iri = IriBuf::from(*self);
+		iri.normalize();
+		iri.0.into_bytes()
+	}
+
+	/// Convert this IRI into an URI.
+	///
+	/// Every non-ASCII character is percent-encoded, following
+	/// [RFC 3987 section 3.1](https://tools.ietf.org/html/rfc3987#section-3.1).
+	/// See [`Iri::to_ascii`] to know what components were changed.
+	///
+	/// # Example
+	///
+	/// ```
+	/// # use iref::Iri;
+	/// let iri = Iri::new("http://example.org/résumé?q=ü").unwrap();
+	/// assert_eq!(iri.to_uri().as_str(), "http://example.org/r%C3%A9sum%C3%A9?q=%C3%BC");
+	/// ```
+	#[inline]
+	pub fn to_uri(&self) -> IriBuf {
+		self.to_ascii().0
+	}
+
+	/// Convert this IRI into an URI, reporting what components were changed.
+	///
+	/// The conversion is the one performed by [`Iri::to_uri`].
+	/// The returned report gives, for each component, the number of characters that
+	/// had to be percent-encoded.
+	///
+	/// With the `idna` feature, a non-ASCII host is replaced by its
+	/// [canonical form](Host::canonical) (using Punycode) instead of being
+	/// percent-encoded, and the report [`idna`](AsciiConversionReport::idna) flag is set.
+	///
+	/// # Example
+	///
+	/// ```
+	/// # use iref::Iri;
+	/// let iri = Iri::new("http://exämple.org/résumé").unwrap();
+	/// let (uri, report) = iri.to_ascii();
+	///
+	/// # #[cfg(not(feature = "idna"))]
+	/// # {
+	/// assert_eq!(uri.as_str(), "http://ex%C3%A4mple.org/r%C3%A9sum%C3%A9");
+	/// assert_eq!(report.host, 1);
+	/// assert!(!report.idna);
+	/// # }
+	/// # #[cfg(feature = "idna")]
+	/// # {
+	/// # assert_eq!(uri.as_str(), "http://xn--exmple-cua.org/r%C3%A9sum%C3%A9");
+	/// # assert_eq!(report.host, 0);
+	/// # assert!(report.idna);
+	/// # }
+	/// assert_eq!(report.path, 2);
+	/// assert_eq!(report.query, 0);
+	/// ```
+	pub fn to_ascii(&self) -> (IriBuf, AsciiConversionReport) {
+		let mut report = AsciiConversionReport::default();
+		let mut uri = String::with_capacity(self.len());
+
+		uri.push_str(self.scheme().as_str());
+		uri.push(':');
+
+		if let Some(authority) = self.authority() {
+			uri.push_str("//");
+
+			if let Some(userinfo) = authority.userinfo() {
+				report.userinfo = ascii::encode_non_ascii(userinfo.as_str(), &mut uri);
+				uri.push('@');
+			}
+
+			let host = authority.host();
+
+			#[cfg(feature = "idna")]
+			let host = if host.as_str().is_ascii() {
+				std::borrow::Cow::Borrowed(host.as_str())
+			} else {
+				report.idna = true;
+				host.canonical()
+			};
+
+			#[cfg(feature = "idna")]
+			let host: &str = &host;
+
+			#[cfg(not(feature = "idna"))]
+			let host = host.as_str();
+
+			report.host = ascii::encode_non_ascii(host, &mut uri);
+
+			if let Some(port) = authority.port() {
+				uri.push(':');
+				uri.push_str(port.as_str());
+			}
+		}
+
+		report.path = ascii::encode_non_ascii(self.path().as_str(), &mut uri);
+
+		if let Some(query) = self.query() {
+			uri.push('?');
+			report.query = ascii::encode_non_ascii(query.as_str(), &mut uri);
+		}
+
+		if let Some(fragment) = self.fragment() {
+			uri.push('#');
+			report.fragment = ascii::encode_non_ascii(fragment.as_str(), &mut uri);
+		}
+
+		(IriBuf::from_string(uri).unwrap(), report)
+	}
+
+	/// Resolve all the given IRI references against this IRI.
+	///
+	/// This is equivalent to calling [`IriRef::resolved`] on each reference,
+	/// but the base IRI directory is only computed once and shared across resolutions.
+	/// The resolved IRIs are returned in the same order as the input references.
+	///
+	/// # Example
+	///
+	/// ```
+	/// # use iref::{Iri, IriRef};
+	/// let base = Iri::new("http://a/b/c/d;p?q").unwrap();
+	/// let refs = ["g", "../g", "#s"].iter().map(|r| IriRef::new(r).unwrap());
+	/// let resolved: Vec<_> = base.resolve_all(refs).collect();
+	///
+	/// assert_eq!(resolved, ["http://a/b/c/g", "http://a/b/g", "http://a/b/c/d;p?q#s"]);
+	/// ```
+	#[inline]
+	pub fn resolve_all<'b, I: IntoIterator<Item = IriRef<'b>>>(
+		&self,
+		refs: I,
+	) -> ResolveAll<'a, I::IntoIter> {
+		ResolveAll {
+			base_iri: *self,
+			base_directory: None,
+			refs: refs.into_iter(),
+		}
+	}
+
+	/// Resolve a chain of IRI references, each one against the result of the previous
+	/// resolution, starting with this IRI.
+	///
+	/// This models the following of redirections, where each step gives a new reference
+	/// relative to the current location.
+	/// Returns [`Error::ChainTooLong`] if the chain has more than `max` references.
+	///
+	/// # Example
+	///
+	/// ```
+	/// # use iref::{Iri, IriRef, Error};
+	/// let base = Iri::new("http://a/b/c").unwrap();
+	/// let refs = ["d/e", "../f?g", "//h/i"];
+	/// let chain = || refs.iter().map(|r| IriRef::new(r).unwrap());
+	///
+	/// assert_eq!(base.resolve_chain(chain(), 3).unwrap(), "http://h/i");
+	/// assert_eq!(base.resolve_chain(chain().take(2), 3).unwrap(), "http://a/b/f?g");
+	/// assert_eq!(base.resolve_chain(chain(), 2), Err(Error::ChainTooLong));
+	/// ```
+	pub fn resolve_chain<'b, I: IntoIterator<Item = IriRef<'b>>>(
+		&self,
+		refs: I,
+		max: usize,
+	) -> Result<IriBuf, Error> {
+		let mut current = IriBuf::from(*self);
+
+		for (i, iri_ref) in refs.into_iter().enumerate() {
+			if i >= max {
+				return Err(Error::ChainTooLong);
+			}
+
+			let mut resolved: IriRefBuf = iri_ref.into();
+			resolved.resolve_with(current.as_iri(), &mut None);
+			current = resolved.try_into().unwrap();
+		}
+
+		Ok(current)
+	}
+
+	/// Resolve a reference that must stay on the same origin as this IRI.
+	///
+	/// Returns [`Error::NonLocalReference`] if the reference has a scheme or an authority,
+	/// meaning that it could navigate away from the scheme, host and port of this IRI
+	/// (even `//host` references, that keep the scheme).
+	/// Otherwise the reference is resolved against this IRI, and the result always shares
+	/// its scheme and authority.
+	///
+	/// This is useful to follow untrusted references, such as a "next" location given to a
+	/// login page, without opening a redirection to any other site.
+	///
+	/// # Example
+	///
+	/// ```
+	/// # use iref::{Error, Iri, IriRef};
+	/// let base = Iri::new("https://example.org/login").unwrap();
+	///
+	/// let next = IriRef::new("/account?tab=1").unwrap();
+	/// assert_eq!(base.resolve_local(next).unwrap(), "https://example.org/account?tab=1");
+	///
+	/// let next = IriRef::new("//evil.example.com/").unwrap();
+	/// assert_eq!(base.resolve_local(next), Err(Error::NonLocalReference));
+	/// ```
+	pub fn resolve_local(&self, reference: IriRef) -> Result<IriBuf, Error> {
+		if reference.scheme().is_some() || reference.authority().is_some() {
+			return Err(Error::NonLocalReference);
+		}
+
+		Ok(reference.resolved(*self))
+	}
+}
+
+/// Iterator over the resolution of IRI references against a common base IRI.
+///
+/// Produced by the [`Iri::resolve_all`] method.
+pub struct ResolveAll<'a, I> {
+	base_iri: Iri<'a>,
+	base_directory: Option<IriBuf>,
+	refs: I,
+}
+
+impl<'a, 'b, I: Iterator<Item = IriRef<'b>>> Iterator for ResolveAll<'a, I> {
+	type Item = IriBuf;
+
+	#[inline]
+	fn next(&mut self) -> Option<IriBuf> {
+		self.refs.next().map(|iri_ref| {
+			let mut iri_ref: IriRefBuf = iri_ref.into();
+			iri_ref.resolve_with(self.base_iri, &mut self.base_directory);
+			iri_ref.try_into().unwrap()
+		})
+	}
+
+	#[inline]
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		self.refs.size_hint()
+	}
 }
 
 impl<'a> Deref for Iri<'a> {
@@ -243,6 +787,20 @@ impl<'a> PartialEq<&'a str> for Iri<'a> {
 	}
 }
 
+impl<'a> PartialEq<[u8]> for Iri<'a> {
+	#[inline]
+	fn eq(&self, other: &[u8]) -> bool {
+		self.as_iri_ref().eq(other)
+	}
+}
+
+impl<'a, 'b> PartialEq<&'b [u8]> for Iri<'a> {
+	#[inline]
+	fn eq(&self, other: &&'b [u8]) -> bool {
+		self.as_iri_ref().eq(other)
+	}
+}
+
 impl<'a> PartialOrd for Iri<'a> {
 	#[inline]
 	fn partial_cmp(&self, other: &Iri<'a>) -> Option<Ordering> {
@@ -285,6 +843,13 @@ impl<'a> From<&'a IriBuf> for Iri<'a> {
 	}
 }
 
+impl<'a> From<&'a Iri<'a>> for Iri<'a> {
+	#[inline]
+	fn from(iri: &'a Iri<'a>) -> Iri<'a> {
+		*iri
+	}
+}
+
 impl<'a> TryFrom<IriRef<'a>> for Iri<'a> {
 	type Error = IriRef<'a>;
 
@@ -298,6 +863,15 @@ impl<'a> TryFrom<IriRef<'a>> for Iri<'a> {
 	}
 }
 
+impl<'a> TryFrom<&'a IriRef<'a>> for Iri<'a> {
+	type Error = Error;
+
+	#[inline]
+	fn try_from(iri_ref: &'a IriRef<'a>) -> Result<Iri<'a>, Error> {
+		Iri::try_from(*iri_ref).map_err(|_| Error::InvalidScheme)
+	}
+}
+
 impl<'a> TryFrom<&'a IriRefBuf> for Iri<'a> {
 	type Error = Error;
 
@@ -318,6 +892,20 @@ impl<'a> Hash for Iri<'a> {
 	}
 }
 
+impl<'a> AsRef<[u8]> for Iri<'a> {
+	#[inline]
+	fn as_ref(&self) -> &[u8] {
+		self.as_bytes()
+	}
+}
+
+impl<'a> AsRef<str> for Iri<'a> {
+	#[inline]
+	fn as_ref(&self) -> &str {
+		self.as_str()
+	}
+}
+
 impl<'a> AsIri for Iri<'a> {
 	#[inline]
 	fn as_iri(&self) -> Iri {
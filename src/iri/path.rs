@@ -1,11 +1,17 @@
-use super::{Error, Segment};
+use super::decode::{decode_lossy, pct_eq, pct_eq_str, pct_hash};
+use super::{Error, PercentDecode, Segment};
 use crate::{parsing, AsIriRef, IriRef, IriRefBuf};
-use pct_str::PctStr;
+use pct_str::{Encoder, PctStr, PctString};
 use smallvec::SmallVec;
+use std::borrow::Cow;
+use std::cell::RefCell;
 use std::cmp::{Ord, Ordering, PartialOrd};
 use std::convert::TryFrom;
+use std::fmt::Write;
 use std::hash::{Hash, Hasher};
-use std::iter::IntoIterator;
+use std::iter::{FromIterator, IntoIterator};
+use std::ops::Range;
+use std::rc::Rc;
 use std::{cmp, fmt};
 
 #[derive(Clone, Copy)]
@@ -61,6 +67,43 @@ impl<'a> Path<'a> {
 		unsafe { PctStr::new_unchecked(self.as_str()) }
 	}
 
+	/// Returns an iterator over the percent-decoded bytes of the path.
+	///
+	/// # Example
+	///
+	/// ```
+	/// # use std::convert::TryFrom;
+	/// # use iref::Path;
+	/// let path = Path::try_from("/a%20b/%C3%A9").unwrap();
+	/// let bytes: Vec<u8> = path.percent_decode().collect();
+	///
+	/// assert_eq!(bytes, "/a b/é".as_bytes());
+	/// ```
+	#[inline]
+	pub fn percent_decode(&self) -> PercentDecode<'a> {
+		PercentDecode::new(self.data)
+	}
+
+	/// Returns a value displaying the percent-decoded path, without intermediate allocation.
+	///
+	/// Segments are separated by `/`. To keep the separators unambiguous, `%2F` (an encoded
+	/// `/` inside a segment) is *not* decoded, and neither is `%25` (an encoded `%`), so that
+	/// `%252F` is not displayed as `%2F`. Invalid UTF-8 sequences are displayed as the
+	/// replacement character `U+FFFD`.
+	///
+	/// # Example
+	///
+	/// ```
+	/// # use std::convert::TryFrom;
+	/// # use iref::Path;
+	/// let path = Path::try_from("/a%20b/c%2Fd/%C3%A9%25").unwrap();
+	/// assert_eq!(path.display_decoded().to_string(), "/a b/c%2Fd/é%25");
+	/// ```
+	#[inline]
+	pub fn display_decoded(&self) -> DisplayDecoded<'a> {
+		DisplayDecoded { data: self.data }
+	}
+
 	/// Get the path slice as an IRI reference.
 	#[inline]
 	pub fn as_iri_ref(&self) -> IriRef {
@@ -234,6 +277,27 @@ impl<'a> Path<'a> {
 		NormalizedSegments::new(self)
 	}
 
+	/// Checks if removing the dot segments of the path requires going above its first
+	/// segment, as with `../a` or `/a/../../b`.
+	///
+	/// Such `..` segments are either kept (in relative paths) or dropped (in absolute paths)
+	/// by [`normalized_segments`](Self::normalized_segments).
+	pub(crate) fn escapes_root(&self) -> bool {
+		let mut depth = 0usize;
+		for segment in self.into_iter() {
+			match segment.data {
+				b"." => (),
+				b".." => match depth.checked_sub(1) {
+					Some(d) => depth = d,
+					None => return true,
+				},
+				_ => depth += 1,
+			}
+		}
+
+		false
+	}
+
 	/// Returns the name of the final segment of the path, if there is one.
 	///
 	/// If the path is a normal file, this is the file name. If it's the path of a directory, this
@@ -245,6 +309,74 @@ impl<'a> Path<'a> {
 		self.into_iter().next_back().map(|s| s.into_str())
 	}
 
+	/// Returns the last segment of the path as written, which is empty if the path ends
+	/// with a `/`, or `None` if the path is empty.
+	#[inline]
+	fn last_segment_str(&self) -> Option<&'a str> {
+		let data = self.into_bytes();
+		if data.is_empty() {
+			None
+		} else {
+			let start = data.iter().rposition(|c| *c == b'/').map_or(0, |i| i + 1);
+			Some(unsafe { std::str::from_utf8_unchecked(&data[start..]) })
+		}
+	}
+
+	/// Checks if the final segment of the path is the given segment.
+	///
+	/// The decoded bytes of the final segment are compared, so `index%2Ehtml`
+	/// ends with `index.html`.
+	/// A path ending with a `/` ends with an empty segment.
+	/// Dot segments are not normalized.
+	///
+	/// # Example
+	///
+	/// ```
+	/// # use std::convert::TryFrom;
+	/// # use iref::Path;
+	/// assert!(Path::try_from("/www/index.html").unwrap().ends_with_segment("index.html"));
+	/// assert!(Path::try_from("/www/my%20doc").unwrap().ends_with_segment("my doc"));
+	/// assert!(!Path::try_from("/www/index.html/").unwrap().ends_with_segment("index.html"));
+	/// ```
+	#[inline]
+	pub fn ends_with_segment(&self, segment: &str) -> bool {
+		match self.last_segment_str() {
+			Some(last) => pct_eq_str(last.as_bytes(), segment),
+			None => false,
+		}
+	}
+
+	/// Returns the extension of the final segment of the path, if any.
+	///
+	/// The extension is what follows the last `.` of the final segment, as written
+	/// (without percent-decoding).
+	/// As with [`std::path::Path::extension`], there is no extension if the segment has no
+	/// `.`, or if its only `.` is its first character (such as `.htaccess`),
+	/// or if it is `..`.
+	/// A path ending with a `/` has no extension.
+	///
+	/// # Example
+	///
+	/// ```
+	/// # use std::convert::TryFrom;
+	/// # use iref::Path;
+	/// assert_eq!(Path::try_from("/www/archive.tar.gz").unwrap().extension(), Some("gz"));
+	/// assert_eq!(Path::try_from("/www.d/README").unwrap().extension(), None);
+	/// assert_eq!(Path::try_from("/www/a.html/").unwrap().extension(), None);
+	/// ```
+	#[inline]
+	pub fn extension(&self) -> Option<&'a str> {
+		let last = self.last_segment_str()?;
+		if last == ".." {
+			return None;
+		}
+
+		match last.rfind('.') {
+			Some(0) | None => None,
+			Some(i) => Some(&last[(i + 1)..]),
+		}
+	}
+
 	/// Returns the path without its final component, if there is one.
 	#[inline]
 	pub fn parent(&self) -> Option<Path<'a>> {
@@ -303,7 +435,7 @@ impl<'a> Path<'a> {
 		loop {
 			match (self_it.next(), prefix_it.next()) {
 				(Some(self_seg), Some(prefix_seg))
-					if self_seg.as_pct_str() == prefix_seg.as_pct_str() => {}
+					if pct_eq(self_seg.as_ref(), prefix_seg.as_ref()) => {}
 				(_, Some(_)) => return None,
 				(Some(seg), None) => buf.as_path_mut().push(seg),
 				(None, None) => break,
@@ -312,6 +444,52 @@ impl<'a> Path<'a> {
 
 		Some(buf)
 	}
+
+	/// Returns the remaining relative path after the given prefix, if any.
+	///
+	/// Returns `Some(rest)` if the segments of this path begin with the segments of `prefix`.
+	/// Returns `None` otherwise.
+	/// The comparison is done segment by segment, so `/v2` is not a prefix of `/v20`.
+	/// A trailing `/` in `prefix` is ignored.
+	///
+	/// Contrarily to [`Path::suffix`], no normalization occurs and the result borrows
+	/// this path.
+	///
+	/// # Example
+	/// ```
+	/// # use std::convert::TryFrom;
+	/// use iref::Path;
+	///
+	/// let path = Path::try_from("/v2/a/b").unwrap();
+	///
+	/// assert_eq!(path.strip_prefix(Path::try_from("/v2").unwrap()).unwrap(), "a/b");
+	/// assert!(Path::try_from("/v20/a").unwrap().strip_prefix(Path::try_from("/v2").unwrap()).is_none());
+	/// ```
+	#[inline]
+	pub fn strip_prefix(&self, prefix: Path) -> Option<Path<'a>> {
+		if self.is_absolute() != prefix.is_absolute() {
+			return None;
+		}
+
+		let mut rest = self.data.strip_prefix(b"/").unwrap_or(self.data);
+		let prefix = prefix.data.strip_prefix(b"/").unwrap_or(prefix.data);
+		let prefix = prefix.strip_suffix(b"/").unwrap_or(prefix);
+
+		if !prefix.is_empty() {
+			for prefix_segment in prefix.split(|c| *c == b'/') {
+				let len = rest.iter().position(|c| *c == b'/').unwrap_or(rest.len());
+				let (segment, next) = rest.split_at(len);
+
+				if !pct_eq(segment, prefix_segment) {
+					return None;
+				}
+
+				rest = next.strip_prefix(b"/").unwrap_or(next);
+			}
+		}
+
+		Some(Path { data: rest })
+	}
 }
 
 impl<'a> AsRef<[u8]> for Path<'a> {
@@ -465,6 +643,78 @@ impl<'a> Iterator for NormalizedSegments<'a> {
 	}
 }
 
+/// Display of a percent-decoded path.
+///
+/// Produced by the [`Path::display_decoded`] method.
+#[derive(Clone, Copy)]
+pub struct DisplayDecoded<'a> {
+	data: &'a [u8],
+}
+
+/// Writes the given bytes, replacing invalid UTF-8 sequences with `U+FFFD`.
+fn write_lossy(f: &mut fmt::Formatter, mut bytes: &[u8]) -> fmt::Result {
+	loop {
+		match std::str::from_utf8(bytes) {
+			Ok(s) => return f.write_str(s),
+			Err(e) => {
+				let valid = e.valid_up_to();
+				f.write_str(unsafe { std::str::from_utf8_unchecked(&bytes[..valid]) })?;
+				f.write_char('\u{fffd}')?;
+				match e.error_len() {
+					Some(len) => bytes = &bytes[(valid + len)..],
+					None => return Ok(()),
+				}
+			}
+		}
+	}
+}
+
+impl<'a> fmt::Display for DisplayDecoded<'a> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let data = self.data;
+		let hex_value = |b: u8| (b as char).to_digit(16).unwrap() as u8;
+		let mut decoded: SmallVec<[u8; 12]> = SmallVec::new();
+		let mut i = 0;
+
+		while i < data.len() {
+			if data[i] == b'%' {
+				let octet = hex_value(data[i + 1]) << 4 | hex_value(data[i + 2]);
+				if octet == b'/' || octet == b'%' {
+					write_lossy(f, &decoded)?;
+					decoded.clear();
+					f.write_char('%')?;
+					f.write_char(data[i + 1].to_ascii_uppercase() as char)?;
+					f.write_char(data[i + 2].to_ascii_uppercase() as char)?;
+				} else {
+					decoded.push(octet)
+				}
+
+				i += 3
+			} else {
+				write_lossy(f, &decoded)?;
+				decoded.clear();
+
+				let end = data[i..]
+					.iter()
+					.position(|b| *b == b'%')
+					.map(|len| i + len)
+					.unwrap_or(data.len());
+				f.write_str(unsafe { std::str::from_utf8_unchecked(&data[i..end]) })?;
+				i = end
+			}
+		}
+
+		write_lossy(f, &decoded)
+	}
+}
+
+impl<'a> fmt::Debug for DisplayDecoded<'a> {
+	#[inline]
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "\"{}\"", self)
+	}
+}
+
 impl<'a> fmt::Display for Path<'a> {
 	#[inline]
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -554,7 +804,7 @@ impl<'a> Ord for Path<'a> {
 impl<'a> Hash for Path<'a> {
 	#[inline]
 	fn hash<H: Hasher>(&self, hasher: &mut H) {
-		self.as_pct_str().hash(hasher)
+		pct_hash(self.data, hasher)
 	}
 }
 
@@ -571,6 +821,12 @@ impl<'a> PathMut<'a> {
 		&self.buffer.data[offset..(offset + len)]
 	}
 
+	/// Returns the path as a string slice.
+	#[inline]
+	pub fn as_str(&self) -> &str {
+		unsafe { std::str::from_utf8_unchecked(self.as_bytes()) }
+	}
+
 	/// Get the inner path.
 	#[inline]
 	pub fn as_path(&self) -> Path {
@@ -628,6 +884,64 @@ impl<'a> PathMut<'a> {
 		}
 	}
 
+	/// Make the path absolute by adding a `/` at its start, if it is relative.
+	///
+	/// The empty path becomes `/`.
+	///
+	/// # Example
+	///
+	/// ```
+	/// # use iref::IriRefBuf;
+	/// let mut iri_ref = IriRefBuf::new("a/b?q").unwrap();
+	/// iri_ref.path_mut().make_absolute();
+	/// assert_eq!(iri_ref, "/a/b?q");
+	/// ```
+	#[inline]
+	pub fn make_absolute(&mut self) {
+		if self.is_relative() {
+			let offset = self.buffer.p.path_offset();
+			self.buffer.replace(offset..offset, b"/");
+			self.buffer.p.path_len += 1;
+		}
+	}
+
+	/// Make the path relative by removing the `/` at its start, if it is absolute.
+	///
+	/// As stated in [RFC 3986 section 3.3](https://tools.ietf.org/html/rfc3986#section-3.3),
+	/// when an authority is present the path must either be empty or absolute.
+	/// Returns [`Error::InvalidPath`] if the IRI has an authority and the path is absolute,
+	/// in which case the path is left unchanged.
+	///
+	/// If the resulting path could be mistaken for a scheme (as in `a:b` without scheme),
+	/// it is prefixed with `./`.
+	///
+	/// # Example
+	///
+	/// ```
+	/// # use iref::{Error, IriRefBuf};
+	/// let mut iri_ref = IriRefBuf::new("/a/b?q").unwrap();
+	/// iri_ref.path_mut().make_relative().unwrap();
+	/// assert_eq!(iri_ref, "a/b?q");
+	///
+	/// let mut iri_ref = IriRefBuf::new("//example.org/a/b").unwrap();
+	/// assert_eq!(iri_ref.path_mut().make_relative(), Err(Error::InvalidPath));
+	/// ```
+	#[inline]
+	pub fn make_relative(&mut self) -> Result<(), Error> {
+		if self.is_absolute() {
+			if self.buffer.authority().is_some() {
+				return Err(Error::InvalidPath);
+			}
+
+			let offset = self.buffer.p.path_offset();
+			self.buffer.replace(offset..(offset + 1), b"");
+			self.buffer.p.path_len -= 1;
+			self.disambiguate()
+		}
+
+		Ok(())
+	}
+
 	/// Produces an iterator over the segments of the IRI path.
 	///
 	/// Note that this is an IRI path, not an IRI reference path: no normalization occurs with
@@ -697,6 +1011,12 @@ impl<'a> PathMut<'a> {
 				self.push(Segment::current())
 			}
 
+			// if the IRI has an authority, the path must be empty or absolute,
+			// otherwise the segment would be appended to the authority.
+			if self.is_empty() && self.buffer.authority().is_some() {
+				self.make_absolute()
+			}
+
 			// make sure it ends with a slash.
 			self.open();
 
@@ -714,21 +1034,24 @@ impl<'a> PathMut<'a> {
 	#[inline]
 	pub fn pop(&mut self) {
 		if !self.is_empty() {
-			let end = self.buffer.p.path_offset() + self.buffer.p.path_len;
-			let mut start = end - 1;
+			let offset = self.buffer.p.path_offset();
+			let end = offset + self.buffer.p.path_len;
 
-			// We remove the terminating `/`.
-			if self.is_open() {
-				start -= 1;
-			}
+			// We ignore the terminating `/`.
+			let mut start = if self.is_open() { end - 1 } else { end };
 
-			// Find the last segment start position.
-			while start > 0 && self.buffer.data[start] != b'/' {
+			// Find the last segment start position, without leaving the path.
+			while start > offset && self.buffer.data[start - 1] != b'/' {
 				start -= 1;
 			}
 
-			if start > 0 || self.buffer.data[start] == b'/' {
-				start += 1;
+			if self.is_relative() && self.buffer.data[start..end].starts_with(b"..") {
+				let last = &self.buffer.data[start..end];
+				if last == b".." || last == b"../" {
+					// A relative path cannot go above its first `..` segment.
+					self.push(Segment::parent());
+					return;
+				}
 			}
 
 			self.buffer.replace(start..end, &[]);
@@ -773,6 +1096,97 @@ impl<'a> PathMut<'a> {
 		}
 	}
 
+	/// Transform each segment of the path with the given function.
+	///
+	/// The function is given each segment as a percent-encoded string,
+	/// and returns the new segment as a decoded string.
+	/// The result is then percent-encoded where needed (including `/` and `%`) before
+	/// replacing the original segment.
+	/// Separators are preserved: the empty segments introduced by a leading or
+	/// trailing `/` are not given to the function, so the path remains absolute and/or open
+	/// if it was.
+	///
+	/// If needed, the path is disambiguated afterward (by prefixing it with `./`) so that it
+	/// is not confused with an authority or a scheme.
+	///
+	/// # Example
+	///
+	/// ```
+	/// # use iref::IriBuf;
+	/// let mut iri = IriBuf::new("http://example.org/Foo/BAR%20Baz/").unwrap();
+	/// iri.path_mut().map_segments(|segment| segment.decode().to_lowercase().into());
+	///
+	/// assert_eq!(iri, "http://example.org/foo/bar%20baz/");
+	/// ```
+	pub fn map_segments<F: FnMut(&PctStr) -> Cow<str>>(&mut self, mut f: F) {
+		let old_path = self.as_bytes().to_vec();
+		let count = old_path.iter().filter(|c| **c == b'/').count() + 1;
+		let mut new_path = Vec::with_capacity(old_path.len());
+
+		for (i, segment) in old_path.split(|c| *c == b'/').enumerate() {
+			if i > 0 {
+				new_path.push(b'/')
+			}
+
+			if !segment.is_empty() || (i > 0 && i + 1 < count) {
+				let segment =
+					unsafe { PctStr::new_unchecked(std::str::from_utf8_unchecked(segment)) };
+				let new_segment = PctString::encode(f(segment).chars(), SegmentEncoder);
+				new_path.extend_from_slice(new_segment.as_str().as_bytes());
+			}
+		}
+
+		let offset = self.buffer.p.path_offset();
+		self.buffer
+			.replace(offset..(offset + self.buffer.p.path_len), &new_path);
+		self.buffer.p.path_len = new_path.len();
+		self.disambiguate()
+	}
+
+	/// Returns an iterator over the segments of the path, allowing each of them to be
+	/// replaced in place.
+	///
+	/// Just like [`map_segments`](Self::map_segments), the empty segments introduced by
+	/// a leading or trailing `/` are not returned.
+	///
+	/// Each call to [`SegmentMut::set`] is applied to the underlying buffer immediately,
+	/// and the position of the following segments is updated accordingly, so segments can
+	/// be edited in any order, even after the iteration is over.
+	/// The path is disambiguated (see `map_segments`) once the iterator and all the
+	/// returned segments are dropped.
+	///
+	/// # Example
+	///
+	/// ```
+	/// # use iref::IriBuf;
+	/// let mut iri = IriBuf::new("http://example.org/users/42/posts/").unwrap();
+	///
+	/// let mut after_users = false;
+	/// for mut segment in iri.path_mut().segments_mut() {
+	/// 	if after_users {
+	/// 		segment.set("alice bob");
+	/// 	}
+	///
+	/// 	after_users = segment.decode() == "users";
+	/// }
+	///
+	/// assert_eq!(iri, "http://example.org/users/alice%20bob/posts/");
+	/// ```
+	pub fn segments_mut(&mut self) -> SegmentsMut<'_> {
+		let lens = self
+			.as_bytes()
+			.split(|c| *c == b'/')
+			.map(<[u8]>::len)
+			.collect();
+		SegmentsMut {
+			state: Rc::new(RefCell::new(SegmentsMutState {
+				buffer: &mut *self.buffer,
+				lens,
+			})),
+			index: 0,
+		}
+	}
+
 	#[inline]
 	pub fn normalize(&mut self) {
 		let mut buffer: SmallVec<[u8; REMOVE_DOTS_BUFFER_LEN]> = SmallVec::new();
@@ -789,6 +1203,132 @@ impl<'a> PathMut<'a> {
 	}
 }
 
+/// Buffer shared by a [`SegmentsMut`] iterator and the segments it returns.
+struct SegmentsMutState<'a> {
+	buffer: &'a mut IriRefBuf,
+
+	/// Byte length of each `/` separated segment of the path.
+	lens: Vec<usize>,
+}
+
+impl<'a> SegmentsMutState<'a> {
+	/// Returns the byte range of the `i`-th segment in the buffer.
+	#[inline]
+	fn range(&self, i: usize) -> Range<usize> {
+		let start = self.buffer.p.path_offset() + self.lens[..i].iter().sum::<usize>() + i;
+		start..(start + self.lens[i])
+	}
+}
+
+impl<'a> Drop for SegmentsMutState<'a> {
+	#[inline]
+	fn drop(&mut self) {
+		PathMut {
+			buffer: &mut *self.buffer,
+		}
+		.disambiguate()
+	}
+}
+
+/// Iterator over the mutable segments of a path.
+///
+/// The iterator and its segments share the mutably borrowed buffer, and are
+/// neither [`Send`] nor [`Sync`].
+///
+/// See [`PathMut::segments_mut`].
+pub struct SegmentsMut<'a> {
+	state: Rc<RefCell<SegmentsMutState<'a>>>,
+	index: usize,
+}
+
+impl<'a> Iterator for SegmentsMut<'a> {
+	type Item = SegmentMut<'a>;
+
+	#[inline]
+	fn next(&mut self) -> Option<SegmentMut<'a>> {
+		let count = self.state.borrow().lens.len();
+		while self.index < count {
+			let i = self.index;
+			self.index += 1;
+
+			if self.state.borrow().lens[i] > 0 || (i > 0 && i + 1 < count) {
+				return Some(SegmentMut {
+					state: self.state.clone(),
+					index: i,
+				});
+			}
+		}
+
+		None
+	}
+}
+
+/// Path segment that can be replaced in place.
+///
+/// See [`PathMut::segments_mut`].
+pub struct SegmentMut<'a> {
+	state: Rc<RefCell<SegmentsMutState<'a>>>,
+	index: usize,
+}
+
+impl<'a> SegmentMut<'a> {
+	/// Returns the percent-encoded segment.
+	#[inline]
+	pub fn to_pct_string(&self) -> PctString {
+		self.with_pct_str(|segment| PctString::new(segment.as_str()).unwrap())
+	}
+
+	/// Returns the decoded segment.
+	///
+	/// Octets that do not form valid UTF-8 are decoded as `U+FFFD REPLACEMENT CHARACTER`.
+	#[inline]
+	pub fn decode(&self) -> String {
+		self.with_pct_str(|segment| decode_lossy(segment.as_str()).into_owned())
+	}
+
+	#[inline]
+	fn with_pct_str<T, F: FnOnce(&PctStr) -> T>(&self, f: F) -> T {
+		let state = self.state.borrow();
+		let data = &state.buffer.data[state.range(self.index)];
+		f(unsafe { PctStr::new_unchecked(std::str::from_utf8_unchecked(data)) })
+	}
+
+	/// Replace the segment with the given decoded value.
+	///
+	/// The value is percent-encoded where needed (including `/` and `%`).
+	/// The change is applied to the path immediately.
+	pub fn set(&mut self, value: &str) {
+		let new_segment = PctString::encode(value.chars(), SegmentEncoder);
+		let new_segment = new_segment.as_str().as_bytes();
+
+		let mut state = self.state.borrow_mut();
+		let range = state.range(self.index);
+		let old_len = range.end - range.start;
+		state.buffer.replace(range, new_segment);
+		state.buffer.p.path_len = state.buffer.p.path_len - old_len + new_segment.len();
+		state.lens[self.index] = new_segment.len();
+	}
+}
+
+impl<'a> fmt::Debug for SegmentMut<'a> {
+	#[inline]
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		self.with_pct_str(|segment| segment.as_str().fmt(f))
+	}
+}
+
+/// Percent-encoder for path segments.
+///
+/// Encodes every character that is not allowed in a path segment.
+struct SegmentEncoder;
+
+impl Encoder for SegmentEncoder {
+	#[inline]
+	fn encode(&self, c: char) -> bool {
+		!(parsing::is_unreserved(c) || parsing::is_subdelim(c) || c == ':' || c == '@')
+	}
+}
+
 impl<'a> AsRef<[u8]> for PathMut<'a> {
 	#[inline]
 	fn as_ref(&self) -> &[u8] {
@@ -817,6 +1357,27 @@ impl<'a> PartialEq<Path<'a>> for PathMut<'a> {
 	}
 }
 
+impl<'a, 'b> PartialEq<&'b str> for PathMut<'a> {
+	#[inline]
+	fn eq(&self, other: &&'b str) -> bool {
+		self.as_path() == *other
+	}
+}
+
+impl<'a> fmt::Display for PathMut<'a> {
+	#[inline]
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		self.as_str().fmt(f)
+	}
+}
+
+impl<'a> fmt::Debug for PathMut<'a> {
+	#[inline]
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		self.as_str().fmt(f)
+	}
+}
+
 /// A path buffer, that can be manipulated independently of an IRI.
 #[derive(PartialEq, Eq, Hash, PartialOrd, Ord, Clone)]
 pub struct PathBuf {
@@ -909,6 +1470,59 @@ impl<'a> From<NormalizedSegments<'a>> for PathBuf {
 	}
 }
 
+/// Build a relative path from its decoded segments.
+///
+/// Segments are joined with `/`, and each segment is percent-encoded where needed
+/// (including `/` and `%`), as [`PathMut::push`] would.
+///
+/// # Example
+///
+/// ```
+/// # use iref::PathBuf;
+/// let path: PathBuf = ["a", "b c", "d/e"].iter().copied().collect();
+/// assert_eq!(path, "a/b%20c/d%2Fe");
+/// ```
+impl<'s> FromIterator<&'s str> for PathBuf {
+	fn from_iter<I: IntoIterator<Item = &'s str>>(segments: I) -> PathBuf {
+		let mut buf = PathBuf::new();
+		let mut path = buf.as_path_mut();
+		for segment in segments {
+			let encoded = PctString::encode(segment.chars(), SegmentEncoder);
+			path.push(Segment {
+				data: encoded.as_str().as_bytes(),
+				open: false,
+			})
+		}
+
+		buf
+	}
+}
+
+/// Build a relative path from its segments, which are already percent-encoded.
+///
+/// This can be used to collect the [`segments`](Path::segments) of a path into another.
+///
+/// # Example
+///
+/// ```
+/// # use std::convert::TryFrom;
+/// # use iref::{Path, PathBuf};
+/// let path = Path::try_from("/a/b%20c/d").unwrap();
+/// let relative: PathBuf = path.segments().skip(1).collect();
+/// assert_eq!(relative, "b%20c/d");
+/// ```
+impl<'s> FromIterator<Segment<'s>> for PathBuf {
+	fn from_iter<I: IntoIterator<Item = Segment<'s>>>(segments: I) -> PathBuf {
+		let mut buf = PathBuf::new();
+		let mut path = buf.as_path_mut();
+		for segment in segments {
+			path.push(segment)
+		}
+
+		buf
+	}
+}
+
 impl fmt::Display for PathBuf {
 	#[inline]
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -946,9 +1560,175 @@ impl<'a> PartialEq<&'a str> for PathBuf {
 
 #[cfg(test)]
 mod tests {
-	use crate::{Iri, IriBuf, IriRefBuf, Path, PathBuf};
+	use crate::{Error, Iri, IriBuf, IriRefBuf, Path, PathBuf};
 	use std::convert::{TryFrom, TryInto};
 
+	#[test]
+	fn make_absolute_relative() {
+		let challenges = [
+			("a/b?q", "/a/b?q", "a/b?q"),
+			("/a/b", "/a/b", "a/b"),
+			("", "/", ""),
+			("/", "/", ""),
+			("?q#f", "/?q#f", "?q#f"),
+			("s:a/b", "s:/a/b", "s:a/b"),
+			("s:/a/b", "s:/a/b", "s:a/b"),
+			("/a:b", "/a:b", "./a:b"),
+			("s:/a:b", "s:/a:b", "s:a:b"),
+			("/.//a", "/.//a", ".//a"),
+		];
+
+		for (input, absolute, relative) in &challenges {
+			let mut iri_ref = IriRefBuf::new(input).unwrap();
+			iri_ref.path_mut().make_absolute();
+			assert_eq!(iri_ref.as_str(), *absolute);
+			assert!(iri_ref.path().is_absolute());
+
+			let mut iri_ref = IriRefBuf::new(input).unwrap();
+			iri_ref.path_mut().make_relative().unwrap();
+			assert_eq!(iri_ref.as_str(), *relative);
+			assert!(iri_ref.path().is_relative());
+			assert_eq!(IriRefBuf::new(iri_ref.as_str()).unwrap(), iri_ref);
+		}
+
+		let mut iri = IriBuf::new("http://a/b/c").unwrap();
+		assert_eq!(iri.path_mut().make_relative(), Err(Error::InvalidPath));
+		assert_eq!(iri, "http://a/b/c");
+
+		let mut iri = IriBuf::new("http://a").unwrap();
+		iri.path_mut().make_relative().unwrap();
+		assert_eq!(iri, "http://a");
+		iri.path_mut().make_absolute();
+		assert_eq!(iri, "http://a/");
+	}
+
+	#[test]
+	fn ends_with_segment() {
+		let challenges = [
+			("/a/index.html", "index.html", true),
+			("index.html", "index.html", true),
+			("/a/index%2Ehtml", "index.html", true),
+			("/a/index.html", "html", false),
+			("/a/index.html/", "index.html", false),
+			("/a/", "", true),
+			("/", "", true),
+			("", "", false),
+			("/a/..", "..", true),
+			("/a/%FF", "\u{fffd}", false),
+			("/a/%C3%A9", "é", true),
+		];
+
+		for (path, segment, expected) in &challenges {
+			let path = Path::try_from(*path).unwrap();
+			assert_eq!(path.ends_with_segment(segment), *expected, "{}", path)
+		}
+	}
+
+	#[test]
+	fn extension() {
+		let challenges = [
+			("/a/index.html", Some("html")),
+			("/a/archive.tar.gz", Some("gz")),
+			("/a.d/README", None),
+			("/a/index.html/", None),
+			("/a/.htaccess", None),
+			("/a/file.", Some("")),
+			("/a/..", None),
+			("/a/.", None),
+			("file.txt", Some("txt")),
+			("/a/b%2Etxt", None),
+			("", None),
+			("/", None),
+		];
+
+		for (path, expected) in &challenges {
+			let path = Path::try_from(*path).unwrap();
+			assert_eq!(path.extension(), *expected, "{}", path)
+		}
+	}
+
+	#[test]
+	fn map_segments() {
+		let mut iri = IriBuf::new("http://x/users/42/posts/").unwrap();
+		iri.path_mut().map_segments(|segment| {
+			if segment == "42" {
+				"alice bob/carol".into()
+			} else {
+				segment.as_str().to_uppercase().into()
+			}
+		});
+		assert_eq!(iri.as_str(), "http://x/USERS/alice%20bob%2Fcarol/POSTS/");
+
+		let mut iri_ref = IriRefBuf::new("a//b").unwrap();
+		iri_ref
+			.path_mut()
+			.map_segments(|segment| format!("[{}]", segment).into());
+		assert_eq!(iri_ref.as_str(), "%5Ba%5D/%5B%5D/%5Bb%5D");
+
+		let mut iri = IriBuf::new("scheme:/a/b").unwrap();
+		iri.path_mut().map_segments(|segment| {
+			if segment == "a" {
+				"".into()
+			} else {
+				segment.decode().into()
+			}
+		});
+		assert_eq!(iri.as_str(), "scheme:/.//b");
+	}
+
+	#[test]
+	fn segments_mut() {
+		let mut iri = IriBuf::new("http://x/a/b/c/?q").unwrap();
+		let mut path = iri.path_mut();
+		let mut segments: Vec<_> = path.segments_mut().collect();
+		assert_eq!(segments.len(), 3);
+		segments[2].set("long/segment");
+		segments[0].set("");
+		assert_eq!(segments[1].decode(), "b");
+		segments[1].set("%");
+		drop(segments);
+		assert_eq!(path.as_bytes(), b"//%25/long%2Fsegment/");
+		assert_eq!(iri.as_str(), "http://x//%25/long%2Fsegment/?q");
+
+		let mut iri = IriBuf::new("scheme:/a/b").unwrap();
+		for mut segment in iri.path_mut().segments_mut() {
+			if segment.decode() == "a" {
+				segment.set("")
+			}
+		}
+		assert_eq!(iri.as_str(), "scheme:/.//b");
+
+		let mut iri = IriBuf::new("scheme:/%FF%C3/%C3%A9").unwrap();
+		let mut path = iri.path_mut();
+		let segments: Vec<_> = path.segments_mut().collect();
+		assert_eq!(segments[0].decode(), "\u{fffd}\u{fffd}");
+		assert_eq!(segments[1].decode(), "é");
+	}
+
+	#[test]
+	fn strip_prefix() {
+		let challenges = [
+			("/v2/a/b", "/v2", Some("a/b")),
+			("/v2/a/b", "/v2/", Some("a/b")),
+			("/v2/a/b/", "/v2", Some("a/b/")),
+			("/v2/a/b", "/v2/a/b", Some("")),
+			("/v2/", "/v2", Some("")),
+			("/v2", "/v2/", Some("")),
+			("/v2/a", "/", Some("v2/a")),
+			("a/b", "a", Some("b")),
+			("/v2/%61", "/v2/a", Some("")),
+			("/v20/a", "/v2", None),
+			("/v2", "/v2/a", None),
+			("/v2/a", "v2", None),
+		];
+
+		for (path, prefix, expected) in &challenges {
+			let path = Path::try_from(*path).unwrap();
+			let prefix = Path::try_from(*prefix).unwrap();
+			assert_eq!(path.strip_prefix(prefix).map(Path::into_str), *expected)
+		}
+	}
+
 	#[test]
 	fn empty() {
 		let iri = Iri::new("scheme:").unwrap();
@@ -1097,6 +1877,28 @@ mod tests {
 		assert_eq!(iri.as_str(), "scheme:foo/");
 	}
 
+	#[test]
+	fn pop_rootless() {
+		let challenges = [
+			("scheme:foo", "scheme:"),
+			("scheme:foo/", "scheme:"),
+			("scheme:a:b/", "scheme:"),
+			("scheme:", "scheme:.."),
+			("scheme:../", "scheme:../.."),
+			("scheme:../..", "scheme:../../.."),
+			("scheme:../a", "scheme:../"),
+			("scheme:/", "scheme:/"),
+			("scheme:/a", "scheme:/"),
+			("a/b", "a/"),
+		];
+
+		for (input, expected) in &challenges {
+			let mut iri_ref = IriRefBuf::new(input).unwrap();
+			iri_ref.path_mut().pop();
+			assert_eq!(iri_ref.as_str(), *expected, "{}", input);
+		}
+	}
+
 	#[test]
 	fn pop_open_empty_segment_edge_case() {
 		let mut iri = IriBuf::new("scheme:////").unwrap();
@@ -1276,4 +2078,41 @@ mod tests {
 		let path = Path::try_from("/a/b/baz").unwrap();
 		assert!(path.suffix(prefix).is_none());
 	}
+
+	#[test]
+	fn display_decoded() {
+		let path = Path::try_from("a%252Fb/%2f/%C3%A9%FF%41").unwrap();
+		assert_eq!(path.display_decoded().to_string(), "a%252Fb/%2F/é\u{fffd}A");
+		assert_eq!(
+			format!("{:?}", Path::try_from("/%C3").unwrap().display_decoded()),
+			"\"/\u{fffd}\""
+		);
+	}
+
+	#[test]
+	fn push_after_authority() {
+		let mut iri = IriBuf::new("http://example.org?q").unwrap();
+		iri.path_mut().push("a".try_into().unwrap());
+		assert_eq!(iri, "http://example.org/a?q");
+		assert_eq!(iri.path(), "/a");
+	}
+
+	#[test]
+	fn collect_str_segments() {
+		let path: PathBuf = ["a", "b c", "d"].iter().copied().collect();
+		assert_eq!(path, "a/b%20c/d");
+
+		let path: PathBuf = ["a:b", "50%"].iter().copied().collect();
+		assert_eq!(path, "./a:b/50%25");
+
+		let path: PathBuf = std::iter::empty::<&str>().collect();
+		assert_eq!(path, "");
+	}
+
+	#[test]
+	fn collect_segments() {
+		let path = Path::try_from("a/b%20c/d").unwrap();
+		let collected: PathBuf = path.segments().collect();
+		assert_eq!(collected, path);
+	}
 }
@@ -29,6 +29,20 @@ impl<'a> Port<'a> {
 	pub fn is_empty(&self) -> bool {
 		self.data.is_empty()
 	}
+
+	/// Returns the port number without its leading zeros.
+	///
+	/// The port `0` is returned as `0` and the empty port as the empty string.
+	#[inline]
+	pub(crate) fn trimmed(self) -> &'a str {
+		let port = unsafe { std::str::from_utf8_unchecked(self.data) };
+		let trimmed = port.trim_start_matches('0');
+		if trimmed.is_empty() && !self.is_empty() {
+			"0"
+		} else {
+			trimmed
+		}
+	}
 }
 
 impl<'a> AsRef<[u8]> for Port<'a> {
@@ -0,0 +1,120 @@
+//! Punycode encoding, as defined by [RFC 3492](https://tools.ietf.org/html/rfc3492).
+
+const BASE: u32 = 36;
+const T_MIN: u32 = 1;
+const T_MAX: u32 = 26;
+const SKEW: u32 = 38;
+const DAMP: u32 = 700;
+const INITIAL_BIAS: u32 = 72;
+const INITIAL_N: u32 = 128;
+
+/// Bias adaptation function.
+fn adapt(mut delta: u32, num_points: u32, first_time: bool) -> u32 {
+	delta /= if first_time { DAMP } else { 2 };
+	delta += delta / num_points;
+
+	let mut k = 0;
+	while delta > ((BASE - T_MIN) * T_MAX) / 2 {
+		delta /= BASE - T_MIN;
+		k += BASE;
+	}
+
+	k + (((BASE - T_MIN + 1) * delta) / (delta + SKEW))
+}
+
+/// Basic code point representing the given digit value.
+fn digit(d: u32) -> char {
+	if d < 26 {
+		(b'a' + d as u8) as char
+	} else {
+		(b'0' + (d - 26) as u8) as char
+	}
+}
+
+/// Encode the given label with Punycode, without the `xn--` prefix.
+///
+/// Returns `None` if the encoding overflows.
+pub(crate) fn encode(input: &str) -> Option<String> {
+	let code_points: Vec<u32> = input.chars().map(|c| c as u32).collect();
+	let mut output: String = input.chars().filter(char::is_ascii).collect();
+
+	let basic_len = output.len() as u32;
+	let mut h = basic_len;
+	if basic_len > 0 {
+		output.push('-');
+	}
+
+	let mut n = INITIAL_N;
+	let mut delta: u32 = 0;
+	let mut bias = INITIAL_BIAS;
+	let len = code_points.len() as u32;
+
+	while h < len {
+		let m = code_points.iter().copied().filter(|c| *c >= n).min()?;
+		delta = delta.checked_add((m - n).checked_mul(h + 1)?)?;
+		n = m;
+
+		for c in &code_points {
+			if *c < n {
+				delta = delta.checked_add(1)?;
+			}
+
+			if *c == n {
+				let mut q = delta;
+				let mut k = BASE;
+				loop {
+					let t = if k <= bias {
+						T_MIN
+					} else if k >= bias + T_MAX {
+						T_MAX
+					} else {
+						k - bias
+					};
+
+					if q < t {
+						break;
+					}
+
+					output.push(digit(t + (q - t) % (BASE - t)));
+					q = (q - t) / (BASE - t);
+					k += BASE;
+				}
+
+				output.push(digit(q));
+				bias = adapt(delta, h + 1, h == basic_len);
+				delta = 0;
+				h += 1;
+			}
+		}
+
+		delta = delta.checked_add(1)?;
+		n += 1;
+	}
+
+	Some(output)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::encode;
+
+	#[test]
+	fn rfc_samples() {
+		let samples = [
+			("", ""),
+			("abc", "abc-"),
+			("ü", "tda"),
+			("bücher", "bcher-kva"),
+			("例え", "r8jz45g"),
+			("münchen", "mnchen-3ya"),
+			(
+				"\u{4ed6}\u{4eec}\u{4e3a}\u{4ec0}\u{4e48}\u{4e0d}\u{8bf4}\u{4e2d}\u{6587}",
+				"ihqwcrb4cv8a8dqg056pqjye",
+			),
+		];
+
+		for (input, expected) in samples.iter() {
+			assert_eq!(encode(input).unwrap(), *expected)
+		}
+	}
+}
@@ -1,4 +1,5 @@
-use super::Error;
+use super::decode::{decode_lossy, pct_cmp, pct_eq, pct_eq_str, pct_hash};
+use super::{Error, PercentDecode};
 use crate::parsing;
 use pct_str::PctStr;
 use std::cmp::{Ord, Ordering, PartialOrd};
@@ -31,11 +32,133 @@ impl<'a> Query<'a> {
 		unsafe { PctStr::new_unchecked(self.as_str()) }
 	}
 
+	/// Returns an iterator over the percent-decoded bytes of the query.
+	#[inline]
+	pub fn percent_decode(&self) -> PercentDecode<'a> {
+		PercentDecode::new(self.data)
+	}
+
 	/// Checks if the query is empty.
 	#[inline]
 	pub fn is_empty(&self) -> bool {
 		self.data.is_empty()
 	}
+
+	/// Checks if the query contains a parameter with the given key.
+	///
+	/// The query is understood as a list of `key=value` parameters separated by `&`.
+	/// Keys are compared after percent-decoding, so searching for `a b` matches `a%20b`.
+	/// A parameter without `=` is a key with no value.
+	///
+	/// # Example
+	///
+	/// ```
+	/// # use std::convert::TryFrom;
+	/// # use iref::Query;
+	/// let query = Query::try_from("a%20b=1&flag").unwrap();
+	///
+	/// assert!(query.contains_key("a b"));
+	/// assert!(query.contains_key("flag"));
+	/// assert!(!query.contains_key("a"));
+	/// ```
+	#[inline]
+	pub fn contains_key(&self, key: &str) -> bool {
+		self.params()
+			.any(|(param_key, _)| pct_eq_str(param_key.as_str().as_bytes(), key))
+	}
+
+	/// Iterate over the `key=value` parameters of the query.
+	///
+	/// The query is understood as a list of `key=value` parameters separated by `&`.
+	/// A parameter without `=` is a key with no value.
+	/// Keys and values are not percent-decoded.
+	///
+	/// # Example
+	///
+	/// ```
+	/// # use std::convert::TryFrom;
+	/// # use iref::Query;
+	/// let query = Query::try_from("a=1&b&c=").unwrap();
+	/// let params: Vec<_> = query
+	/// 	.params()
+	/// 	.map(|(key, value)| (key.as_str(), value.map(|v| v.as_str())))
+	/// 	.collect();
+	///
+	/// assert_eq!(params, [("a", Some("1")), ("b", None), ("c", Some(""))]);
+	/// ```
+	#[inline]
+	pub fn params(&self) -> QueryParams<'a> {
+		QueryParams {
+			data: if self.is_empty() {
+				None
+			} else {
+				Some(self.data)
+			},
+		}
+	}
+
+	/// Iterate over the decoded parameters of the query, following the
+	/// `application/x-www-form-urlencoded` conventions.
+	///
+	/// Contrarily to [`params`](Self::params), keys and values are percent-decoded and
+	/// `+` is decoded as a space (a literal `+` is written `%2B`).
+	/// Empty parameters are skipped, and a parameter without `=` has an empty value.
+	/// Percent-encoded octets that do not form valid UTF-8 are decoded as
+	/// `U+FFFD REPLACEMENT CHARACTER`.
+	///
+	/// # Example
+	///
+	/// ```
+	/// # use std::convert::TryFrom;
+	/// # use iref::Query;
+	/// let query = Query::try_from("a+b=c+d&&e%2Bf").unwrap();
+	/// let params: Vec<_> = query.form_params().collect();
+	///
+	/// assert_eq!(
+	/// 	params,
+	/// 	[("a b".to_string(), "c d".to_string()), ("e+f".to_string(), String::new())]
+	/// );
+	/// ```
+	#[inline]
+	pub fn form_params(&self) -> FormParams<'a> {
+		FormParams {
+			params: self.params(),
+		}
+	}
+
+	/// Returns the canonical form of the query, suitable for cache keys and signatures.
+	///
+	/// The query is understood as a list of `key=value` parameters separated by `&`.
+	/// The canonical query is computed as follows:
+	///  - the percent-encoding is normalized as in [`IriRefBuf::normalize`](crate::IriRefBuf::normalize):
+	///    percent-encoded unreserved and private characters are decoded and the hexadecimal
+	///    digits of the remaining percent-encoded octets are uppercased;
+	///  - the parameters are sorted by their percent-decoded key.
+	///    The sort is stable: parameters with the same key keep their relative order.
+	///
+	/// Values are preserved, and reserved characters such as `+`, `&` and `=` keep their
+	/// (percent-encoded or not) form.
+	///
+	/// # Example
+	///
+	/// ```
+	/// # use std::convert::TryFrom;
+	/// # use iref::Query;
+	/// let query = Query::try_from("b=2&%61=%7e&b=1&a%2b=%2b").unwrap();
+	/// assert_eq!(query.to_canonical(), "a=~&a%2B=%2B&b=2&b=1");
+	/// ```
+	#[inline]
+	pub fn to_canonical(&self) -> QueryBuf {
+		QueryBuf {
+			data: crate::reference::canonical_query(*self),
+		}
+	}
+
+	/// Convert the query slice into the owned version [`QueryBuf`].
+	#[inline]
+	pub fn to_owned(self) -> QueryBuf {
+		self.into()
+	}
 }
 
 impl<'a> AsRef<[u8]> for Query<'a> {
@@ -76,7 +199,7 @@ impl<'a> fmt::Debug for Query<'a> {
 impl<'a> cmp::PartialEq for Query<'a> {
 	#[inline]
 	fn eq(&self, other: &Query) -> bool {
-		self.as_pct_str() == other.as_pct_str()
+		pct_eq(self.data, other.data)
 	}
 }
 
@@ -99,13 +222,343 @@ impl<'a> PartialOrd for Query<'a> {
 impl<'a> Ord for Query<'a> {
 	#[inline]
 	fn cmp(&self, other: &Query<'a>) -> Ordering {
-		self.as_pct_str().cmp(other.as_pct_str())
+		pct_cmp(self.data, other.data)
 	}
 }
 
 impl<'a> Hash for Query<'a> {
 	#[inline]
 	fn hash<H: Hasher>(&self, hasher: &mut H) {
-		self.as_pct_str().hash(hasher)
+		pct_hash(self.data, hasher)
+	}
+}
+
+/// Owned query.
+///
+/// Can be used to store the query of an IRI independently of it,
+/// and reattach it later using the `set_query` method of [`IriRefBuf`](crate::IriRefBuf)
+/// or [`IriBuf`](crate::IriBuf).
+#[derive(Clone)]
+pub struct QueryBuf {
+	/// The query data.
+	data: Vec<u8>,
+}
+
+impl QueryBuf {
+	/// Returns a reference to the byte representation of the query.
+	#[inline]
+	pub fn as_bytes(&self) -> &[u8] {
+		&self.data
+	}
+
+	/// Consume the query and return its internal buffer.
+	#[inline]
+	pub fn into_bytes(self) -> Vec<u8> {
+		self.data
+	}
+
+	/// Get the query as a string slice.
+	#[inline]
+	pub fn as_str(&self) -> &str {
+		unsafe { std::str::from_utf8_unchecked(&self.data) }
+	}
+
+	/// Get the query as a percent-encoded string slice.
+	#[inline]
+	pub fn as_pct_str(&self) -> &PctStr {
+		unsafe { PctStr::new_unchecked(self.as_str()) }
+	}
+
+	/// Borrow the query buffer as a query slice.
+	#[inline]
+	pub fn as_query(&self) -> Query<'_> {
+		Query { data: &self.data }
+	}
+
+	/// Checks if the query is empty.
+	#[inline]
+	pub fn is_empty(&self) -> bool {
+		self.data.is_empty()
+	}
+
+	/// Iterate over the `key=value` parameters of the query.
+	///
+	/// See [`Query::params`] for more details.
+	#[inline]
+	pub fn params(&self) -> QueryParams<'_> {
+		self.as_query().params()
+	}
+
+	/// Iterate over the decoded form parameters of the query.
+	///
+	/// See [`Query::form_params`].
+	#[inline]
+	pub fn form_params(&self) -> FormParams<'_> {
+		self.as_query().form_params()
+	}
+
+	/// Returns the canonical form of the query.
+	///
+	/// See [`Query::to_canonical`].
+	#[inline]
+	pub fn to_canonical(&self) -> QueryBuf {
+		self.as_query().to_canonical()
+	}
+}
+
+/// Iterator over the parameters of a query.
+///
+/// Produced by the [`Query::params`] and [`QueryBuf::params`] methods.
+#[derive(Clone)]
+pub struct QueryParams<'a> {
+	/// Remaining parameters, or `None` if there are none left.
+	data: Option<&'a [u8]>,
+}
+
+impl<'a> Iterator for QueryParams<'a> {
+	type Item = (&'a PctStr, Option<&'a PctStr>);
+
+	#[inline]
+	fn next(&mut self) -> Option<(&'a PctStr, Option<&'a PctStr>)> {
+		let data = self.data?;
+		let param = match data.iter().position(|c| *c == b'&') {
+			Some(i) => {
+				self.data = Some(&data[(i + 1)..]);
+				&data[..i]
+			}
+			None => {
+				self.data = None;
+				data
+			}
+		};
+
+		let pct_str =
+			|bytes| unsafe { PctStr::new_unchecked(std::str::from_utf8_unchecked(bytes)) };
+		match param.iter().position(|c| *c == b'=') {
+			Some(i) => Some((pct_str(&param[..i]), Some(pct_str(&param[(i + 1)..])))),
+			None => Some((pct_str(param), None)),
+		}
+	}
+}
+
+/// Iterator over the `application/x-www-form-urlencoded` parameters of a query.
+///
+/// Produced by the [`Query::form_params`] and [`QueryBuf::form_params`] methods.
+#[derive(Clone)]
+pub struct FormParams<'a> {
+	params: QueryParams<'a>,
+}
+
+impl<'a> Iterator for FormParams<'a> {
+	type Item = (String, String);
+
+	#[inline]
+	fn next(&mut self) -> Option<(String, String)> {
+		loop {
+			match self.params.next()? {
+				(key, None) if key.is_empty() => (),
+				(key, value) => {
+					return Some((decode_form(key), value.map(decode_form).unwrap_or_default()))
+				}
+			}
+		}
+	}
+}
+
+/// Percent-decode a form key or value, decoding `+` as a space.
+#[inline]
+fn decode_form(pct_str: &PctStr) -> String {
+	decode_lossy(&pct_str.as_str().replace('+', " ")).into_owned()
+}
+
+impl<'a> std::iter::FusedIterator for QueryParams<'a> {}
+
+impl AsRef<[u8]> for QueryBuf {
+	#[inline]
+	fn as_ref(&self) -> &[u8] {
+		self.as_bytes()
+	}
+}
+
+impl<'a> From<Query<'a>> for QueryBuf {
+	#[inline]
+	fn from(query: Query<'a>) -> QueryBuf {
+		QueryBuf {
+			data: query.data.to_vec(),
+		}
+	}
+}
+
+impl<'a> TryFrom<&'a str> for QueryBuf {
+	type Error = Error;
+
+	#[inline]
+	fn try_from(str: &'a str) -> Result<QueryBuf, Error> {
+		Query::try_from(str).map(Into::into)
+	}
+}
+
+impl<'a> From<&'a QueryBuf> for Query<'a> {
+	#[inline]
+	fn from(buffer: &'a QueryBuf) -> Query<'a> {
+		buffer.as_query()
+	}
+}
+
+impl fmt::Display for QueryBuf {
+	#[inline]
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		self.as_str().fmt(f)
+	}
+}
+
+impl fmt::Debug for QueryBuf {
+	#[inline]
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		self.as_str().fmt(f)
+	}
+}
+
+impl cmp::PartialEq for QueryBuf {
+	#[inline]
+	fn eq(&self, other: &QueryBuf) -> bool {
+		self.as_query() == other.as_query()
+	}
+}
+
+impl Eq for QueryBuf {}
+
+impl<'a> cmp::PartialEq<Query<'a>> for QueryBuf {
+	#[inline]
+	fn eq(&self, other: &Query<'a>) -> bool {
+		self.as_query() == *other
+	}
+}
+
+impl<'a> cmp::PartialEq<&'a str> for QueryBuf {
+	#[inline]
+	fn eq(&self, other: &&'a str) -> bool {
+		self.as_str() == *other
+	}
+}
+
+impl PartialOrd for QueryBuf {
+	#[inline]
+	fn partial_cmp(&self, other: &QueryBuf) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl Ord for QueryBuf {
+	#[inline]
+	fn cmp(&self, other: &QueryBuf) -> Ordering {
+		self.as_query().cmp(&other.as_query())
+	}
+}
+
+impl Hash for QueryBuf {
+	#[inline]
+	fn hash<H: Hasher>(&self, hasher: &mut H) {
+		self.as_query().hash(hasher)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn contains_key() {
+		let query = Query::try_from("a%20b=1&c=%3D&&d&e=f=g").unwrap();
+
+		assert!(query.contains_key("a b"));
+		assert!(query.contains_key("c"));
+		assert!(query.contains_key(""));
+		assert!(query.contains_key("d"));
+		assert!(query.contains_key("e"));
+		assert!(!query.contains_key("a%20b"));
+		assert!(!query.contains_key("="));
+		assert!(!query.contains_key("f"));
+		assert!(!Query::try_from("").unwrap().contains_key(""));
+
+		let query = Query::try_from("%FF=1&%C3%A9=2").unwrap();
+		assert!(query.contains_key("é"));
+		assert!(!query.contains_key("\u{fffd}"));
+	}
+
+	#[test]
+	fn to_canonical() {
+		let challenges = [
+			("", ""),
+			("a", "a"),
+			("b=1&a=2", "a=2&b=1"),
+			("%62=1&%41=2&a=3&%61=4", "A=2&a=3&a=4&b=1"),
+			("k=2&K=1&k=1&%6B=0", "K=1&k=2&k=1&k=0"),
+			("x=%c3%a9&%E2%82%AC=%2f%2F&&y", "&x=é&y&€=%2F%2F"),
+			("q=a+b&q=a%20b&q=a%2bb", "q=a+b&q=a%20b&q=a%2Bb"),
+			("%ee%80%80=1", "\u{e000}=1"),
+			("%FF=1&a=2", "a=2&%FF=1"),
+			("%ff=1&%FE=2&%c3=3", "%C3=3&%FE=2&%FF=1"),
+		];
+
+		for (input, expected) in challenges.iter() {
+			let query = Query::try_from(*input).unwrap();
+			assert_eq!(query.to_canonical().as_str(), *expected, "{}", input);
+			assert_eq!(query.to_canonical().to_canonical(), query.to_canonical());
+		}
+	}
+
+	#[test]
+	fn params() {
+		let query = Query::try_from("a%20b=1&&d&e=f=g").unwrap();
+		let params: Vec<_> = query
+			.params()
+			.map(|(key, value)| (key.as_str(), value.map(PctStr::as_str)))
+			.collect();
+
+		assert_eq!(
+			params,
+			[
+				("a%20b", Some("1")),
+				("", None),
+				("d", None),
+				("e", Some("f=g"))
+			]
+		);
+		assert_eq!(Query::try_from("").unwrap().params().count(), 0);
+	}
+
+	#[test]
+	fn form_params() {
+		let query = Query::try_from("a+b=c+d").unwrap();
+		let params: Vec<_> = query
+			.params()
+			.map(|(key, value)| (key.as_str(), value.map(PctStr::as_str)))
+			.collect();
+		assert_eq!(params, [("a+b", Some("c+d"))]);
+
+		let params: Vec<_> = query.form_params().collect();
+		assert_eq!(params, [("a b".to_string(), "c d".to_string())]);
+
+		let query = Query::try_from("&x%2By=%26+&=&z").unwrap();
+		let params: Vec<_> = query.form_params().collect();
+		assert_eq!(
+			params,
+			[
+				("x+y".to_string(), "& ".to_string()),
+				(String::new(), String::new()),
+				("z".to_string(), String::new())
+			]
+		);
+
+		let query = Query::try_from("a=%FF&%FF+b").unwrap();
+		let params: Vec<_> = query.form_params().collect();
+		assert_eq!(
+			params,
+			[
+				("a".to_string(), "\u{fffd}".to_string()),
+				("\u{fffd} b".to_string(), String::new())
+			]
+		);
 	}
 }
@@ -0,0 +1,423 @@
+use serde::de::{
+	self, DeserializeOwned, DeserializeSeed, IntoDeserializer, MapAccess, SeqAccess, Visitor,
+};
+use serde::forward_to_deserialize_any;
+use std::fmt;
+
+use super::{PercentDecode, Query};
+
+/// Query deserialization error.
+///
+/// Raised by the [`Query::deserialize`] method.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct DeserializeError(String);
+
+impl fmt::Display for DeserializeError {
+	#[inline]
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		self.0.fmt(f)
+	}
+}
+
+impl std::error::Error for DeserializeError {}
+
+impl de::Error for DeserializeError {
+	#[inline]
+	fn custom<T: fmt::Display>(msg: T) -> DeserializeError {
+		DeserializeError(msg.to_string())
+	}
+}
+
+impl<'a> Query<'a> {
+	/// Deserialize the query parameters into the given type.
+	///
+	/// The query is understood as a list of `key=value` parameters separated by `&`.
+	/// Keys and values are percent-decoded (note that `+` is **not** decoded as a space).
+	/// A parameter without `=` has an empty value.
+	/// Deserializing a key or value whose decoded bytes are not valid UTF-8 (as in `%FF`)
+	/// fails with an error.
+	/// Repeated keys are gathered so they can be deserialized into a sequence (such as a `Vec`
+	/// field). When a single value is expected, the last one is used.
+	/// Values are parsed from their string representation when a number or a boolean is
+	/// expected.
+	///
+	/// # Example
+	///
+	/// ```
+	/// # use std::convert::TryFrom;
+	/// # use std::collections::BTreeMap;
+	/// # use iref::Query;
+	/// let query = Query::try_from("page=2&size=10&tag=a%20b&tag=c").unwrap();
+	/// let params: BTreeMap<String, Vec<String>> = query.deserialize().unwrap();
+	///
+	/// assert_eq!(params["page"], ["2"]);
+	/// assert_eq!(params["tag"], ["a b", "c"]);
+	/// ```
+	#[inline]
+	pub fn deserialize<T: DeserializeOwned>(&self) -> Result<T, DeserializeError> {
+		T::deserialize(QueryDeserializer::new(*self))
+	}
+}
+
+/// Deserializer over the parameters of a query.
+///
+/// See [`Query::deserialize`] for more details.
+pub struct QueryDeserializer {
+	params: Vec<(Vec<u8>, Vec<Vec<u8>>)>,
+}
+
+impl QueryDeserializer {
+	/// Creates a new deserializer over the parameters of the given query.
+	pub fn new(query: Query) -> QueryDeserializer {
+		let mut params: Vec<(Vec<u8>, Vec<Vec<u8>>)> = Vec::new();
+
+		for param in query.as_str().split('&').filter(|param| !param.is_empty()) {
+			let (key, value) = match param.find('=') {
+				Some(i) => (&param[..i], &param[(i + 1)..]),
+				None => (param, ""),
+			};
+
+			// Keys and values are kept as decoded bytes, and only converted into strings
+			// when deserialized, since they may not be valid UTF-8.
+			let key = PercentDecode::new(key.as_bytes()).into_bytes();
+			let value = PercentDecode::new(value.as_bytes()).into_bytes();
+			match params.iter_mut().find(|(k, _)| *k == key) {
+				Some((_, values)) => values.push(value),
+				None => params.push((key, vec![value])),
+			}
+		}
+
+		QueryDeserializer { params }
+	}
+}
+
+/// Converts decoded bytes into a string, failing if they are not valid UTF-8.
+#[inline]
+fn into_string(bytes: Vec<u8>) -> Result<String, DeserializeError> {
+	String::from_utf8(bytes).map_err(|e| {
+		de::Error::custom(format_args!(
+			"invalid UTF-8 in percent-decoded query parameter: {}",
+			e.utf8_error()
+		))
+	})
+}
+
+impl<'de> de::Deserializer<'de> for QueryDeserializer {
+	type Error = DeserializeError;
+
+	#[inline]
+	fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeserializeError> {
+		visitor.visit_map(Params {
+			params: self.params.into_iter(),
+			values: None,
+		})
+	}
+
+	forward_to_deserialize_any! {
+		bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+		bytes byte_buf option unit unit_struct newtype_struct seq tuple
+		tuple_struct map struct enum identifier ignored_any
+	}
+}
+
+/// Map access over the query parameters.
+struct Params {
+	params: std::vec::IntoIter<(Vec<u8>, Vec<Vec<u8>>)>,
+	values: Option<Vec<Vec<u8>>>,
+}
+
+impl<'de> MapAccess<'de> for Params {
+	type Error = DeserializeError;
+
+	#[inline]
+	fn next_key_seed<K: DeserializeSeed<'de>>(
+		&mut self,
+		seed: K,
+	) -> Result<Option<K::Value>, DeserializeError> {
+		match self.params.next() {
+			Some((key, values)) => {
+				self.values = Some(values);
+				seed.deserialize(Values(vec![key])).map(Some)
+			}
+			None => Ok(None),
+		}
+	}
+
+	#[inline]
+	fn next_value_seed<V: DeserializeSeed<'de>>(
+		&mut self,
+		seed: V,
+	) -> Result<V::Value, DeserializeError> {
+		seed.deserialize(Values(self.values.take().unwrap_or_default()))
+	}
+
+	#[inline]
+	fn size_hint(&self) -> Option<usize> {
+		Some(self.params.len())
+	}
+}
+
+/// Deserializer over the (decoded) values of a parameter.
+struct Values(Vec<Vec<u8>>);
+
+impl Values {
+	/// Returns the last value, used when a single value is expected.
+	#[inline]
+	fn last(mut self) -> Result<String, DeserializeError> {
+		into_string(
+			self.0
+				.pop()
+				.ok_or_else(|| de::Error::custom("missing parameter value"))?,
+		)
+	}
+}
+
+macro_rules! deserialize_parsed {
+	($($method:ident => $visit:ident,)*) => {
+		$(
+			#[inline]
+			fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeserializeError> {
+				let value = self.last()?;
+				match value.parse() {
+					Ok(v) => visitor.$visit(v),
+					Err(_) => Err(de::Error::invalid_value(de::Unexpected::Str(&value), &visitor)),
+				}
+			}
+		)*
+	};
+}
+
+impl<'de> de::Deserializer<'de> for Values {
+	type Error = DeserializeError;
+
+	#[inline]
+	fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeserializeError> {
+		visitor.visit_string(self.last()?)
+	}
+
+	deserialize_parsed! {
+		deserialize_bool => visit_bool,
+		deserialize_i8 => visit_i8,
+		deserialize_i16 => visit_i16,
+		deserialize_i32 => visit_i32,
+		deserialize_i64 => visit_i64,
+		deserialize_i128 => visit_i128,
+		deserialize_u8 => visit_u8,
+		deserialize_u16 => visit_u16,
+		deserialize_u32 => visit_u32,
+		deserialize_u64 => visit_u64,
+		deserialize_u128 => visit_u128,
+		deserialize_f32 => visit_f32,
+		deserialize_f64 => visit_f64,
+		deserialize_char => visit_char,
+	}
+
+	#[inline]
+	fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeserializeError> {
+		visitor.visit_some(self)
+	}
+
+	#[inline]
+	fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeserializeError> {
+		visitor.visit_unit()
+	}
+
+	#[inline]
+	fn deserialize_newtype_struct<V: Visitor<'de>>(
+		self,
+		_name: &'static str,
+		visitor: V,
+	) -> Result<V::Value, DeserializeError> {
+		visitor.visit_newtype_struct(self)
+	}
+
+	#[inline]
+	fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeserializeError> {
+		visitor.visit_seq(ValuesSeq(self.0.into_iter()))
+	}
+
+	#[inline]
+	fn deserialize_tuple<V: Visitor<'de>>(
+		self,
+		_len: usize,
+		visitor: V,
+	) -> Result<V::Value, DeserializeError> {
+		self.deserialize_seq(visitor)
+	}
+
+	#[inline]
+	fn deserialize_enum<V: Visitor<'de>>(
+		self,
+		_name: &'static str,
+		_variants: &'static [&'static str],
+		visitor: V,
+	) -> Result<V::Value, DeserializeError> {
+		visitor.visit_enum(self.last()?.into_deserializer())
+	}
+
+	forward_to_deserialize_any! {
+		str string bytes byte_buf unit_struct tuple_struct map struct identifier
+		ignored_any
+	}
+}
+
+/// Sequence access over the values of a repeated parameter.
+struct ValuesSeq(std::vec::IntoIter<Vec<u8>>);
+
+impl<'de> SeqAccess<'de> for ValuesSeq {
+	type Error = DeserializeError;
+
+	#[inline]
+	fn next_element_seed<T: DeserializeSeed<'de>>(
+		&mut self,
+		seed: T,
+	) -> Result<Option<T::Value>, DeserializeError> {
+		match self.0.next() {
+			Some(value) => seed.deserialize(Values(vec![value])).map(Some),
+			None => Ok(None),
+		}
+	}
+
+	#[inline]
+	fn size_hint(&self) -> Option<usize> {
+		Some(self.0.len())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::collections::BTreeMap;
+	use std::convert::TryFrom;
+
+	#[test]
+	fn deserialize_map() {
+		let query = Query::try_from("page=2&size=10&&flag&page=3").unwrap();
+
+		let params: BTreeMap<String, u32> = Query::try_from("page=2&size=10&page=3")
+			.unwrap()
+			.deserialize()
+			.unwrap();
+		assert_eq!(params["page"], 3);
+		assert_eq!(params["size"], 10);
+
+		let params: BTreeMap<String, Vec<String>> = query.deserialize().unwrap();
+		assert_eq!(params["page"], ["2", "3"]);
+		assert_eq!(params["flag"], [""]);
+
+		let params: BTreeMap<String, Option<String>> = query.deserialize().unwrap();
+		assert_eq!(params["size"], Some("10".to_string()));
+
+		assert!(query.deserialize::<BTreeMap<String, u32>>().is_err());
+	}
+
+	#[test]
+	fn deserialize_decoded() {
+		let query = Query::try_from("a%20b=c%26d&e=%C3%A9").unwrap();
+		let params: Vec<(String, String)> = query
+			.deserialize::<BTreeMap<String, String>>()
+			.unwrap()
+			.into_iter()
+			.collect();
+
+		assert_eq!(
+			params,
+			[
+				("a b".to_string(), "c&d".to_string()),
+				("e".to_string(), "é".to_string())
+			]
+		);
+	}
+
+	#[test]
+	fn deserialize_invalid_utf8() {
+		let query = Query::try_from("a=%FF&b=1").unwrap();
+		assert!(query.deserialize::<BTreeMap<String, String>>().is_err());
+
+		let query = Query::try_from("%FF=1").unwrap();
+		assert!(query.deserialize::<BTreeMap<String, String>>().is_err());
+
+		let query = Query::try_from("a=%C3%A9&b=%ff").unwrap();
+		assert!(query
+			.deserialize::<BTreeMap<String, Vec<String>>>()
+			.is_err());
+	}
+
+	/// Parameters of a search, deserialized as `#[derive(Deserialize)]` would.
+	#[derive(Debug, PartialEq)]
+	struct Search {
+		page: u32,
+		tags: Vec<String>,
+		q: Option<String>,
+	}
+
+	impl<'de> de::Deserialize<'de> for Search {
+		fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Search, D::Error> {
+			struct SearchVisitor;
+
+			impl<'de> Visitor<'de> for SearchVisitor {
+				type Value = Search;
+
+				fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+					f.write_str("struct Search")
+				}
+
+				fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Search, A::Error> {
+					let mut page = None;
+					let mut tags = None;
+					let mut q = None;
+
+					while let Some(key) = map.next_key::<String>()? {
+						match key.as_str() {
+							"page" => page = Some(map.next_value()?),
+							"tags" => tags = Some(map.next_value()?),
+							"q" => q = Some(map.next_value()?),
+							_ => {
+								map.next_value::<Vec<String>>()?;
+							}
+						}
+					}
+
+					Ok(Search {
+						page: page.ok_or_else(|| de::Error::custom("missing field `page`"))?,
+						tags: tags.unwrap_or_default(),
+						q: q.unwrap_or_default(),
+					})
+				}
+			}
+
+			deserializer.deserialize_struct("Search", &["page", "tags", "q"], SearchVisitor)
+		}
+	}
+
+	#[test]
+	fn deserialize_struct() {
+		let search: Search = Query::try_from("tags=a&page=2&other&tags=b%20c")
+			.unwrap()
+			.deserialize()
+			.unwrap();
+		assert_eq!(
+			search,
+			Search {
+				page: 2,
+				tags: vec!["a".to_string(), "b c".to_string()],
+				q: None
+			}
+		);
+
+		let search: Search = Query::try_from("page=1&q=%C3%A9")
+			.unwrap()
+			.deserialize()
+			.unwrap();
+		assert_eq!(search.q.as_deref(), Some("é"));
+
+		assert!(Query::try_from("q=x")
+			.unwrap()
+			.deserialize::<Search>()
+			.is_err());
+		assert!(Query::try_from("page=1&q=%FF")
+			.unwrap()
+			.deserialize::<Search>()
+			.is_err());
+	}
+}
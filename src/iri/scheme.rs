@@ -29,6 +29,90 @@ impl<'a> Scheme<'a> {
 	pub fn is_empty(&self) -> bool {
 		self.data.is_empty()
 	}
+
+	/// Returns the default port associated to this scheme, if any.
+	///
+	/// Only a few well-known schemes are recognized: `ftp`, `http`, `https`, `ws` and `wss`.
+	/// Schemes are case-insensitive.
+	#[inline]
+	pub fn default_port(&self) -> Option<u16> {
+		const DEFAULT_PORTS: [(&str, u16); 5] = [
+			("ftp", 21),
+			("http", 80),
+			("https", 443),
+			("ws", 80),
+			("wss", 443),
+		];
+
+		DEFAULT_PORTS
+			.iter()
+			.find(|(name, _)| name.eq_ignore_ascii_case(self.as_str()))
+			.map(|(_, port)| *port)
+	}
+
+	/// Checks if IRIs with this scheme require an authority.
+	///
+	/// Returns `Some(true)` if the scheme requires an authority (such as `http`),
+	/// `Some(false)` if it forbids it (such as `mailto` or `urn`),
+	/// and `None` if the scheme is unknown or does not care.
+	#[inline]
+	pub fn authority_required(&self) -> Option<bool> {
+		const WITH_AUTHORITY: [&str; 5] = ["ftp", "http", "https", "ws", "wss"];
+		// `news` is not listed since RFC 5538 allows both `news:group` and
+		// `news://server/group`.
+		const WITHOUT_AUTHORITY: [&str; 4] = ["data", "mailto", "tel", "urn"];
+
+		let is = |name: &&str| name.eq_ignore_ascii_case(self.as_str());
+		if WITH_AUTHORITY.iter().any(is) {
+			Some(true)
+		} else if WITHOUT_AUTHORITY.iter().any(is) {
+			Some(false)
+		} else {
+			None
+		}
+	}
+}
+
+/// Well-known schemes.
+///
+/// # Example
+///
+/// ```
+/// # use iref::{IriRefBuf, Scheme};
+/// let mut iri_ref = IriRefBuf::new("//example.org/").unwrap();
+/// iri_ref.set_scheme(Some(Scheme::HTTPS));
+/// assert_eq!(iri_ref, "https://example.org/");
+/// ```
+impl Scheme<'static> {
+	/// The `http` scheme.
+	pub const HTTP: Scheme<'static> = Scheme { data: b"http" };
+
+	/// The `https` scheme.
+	pub const HTTPS: Scheme<'static> = Scheme { data: b"https" };
+
+	/// The `ws` scheme.
+	pub const WS: Scheme<'static> = Scheme { data: b"ws" };
+
+	/// The `wss` scheme.
+	pub const WSS: Scheme<'static> = Scheme { data: b"wss" };
+
+	/// The `ftp` scheme.
+	pub const FTP: Scheme<'static> = Scheme { data: b"ftp" };
+
+	/// The `file` scheme.
+	pub const FILE: Scheme<'static> = Scheme { data: b"file" };
+
+	/// The `data` scheme.
+	pub const DATA: Scheme<'static> = Scheme { data: b"data" };
+
+	/// The `mailto` scheme.
+	pub const MAILTO: Scheme<'static> = Scheme { data: b"mailto" };
+
+	/// The `tel` scheme.
+	pub const TEL: Scheme<'static> = Scheme { data: b"tel" };
+
+	/// The `urn` scheme.
+	pub const URN: Scheme<'static> = Scheme { data: b"urn" };
 }
 
 impl<'a> AsRef<[u8]> for Scheme<'a> {
@@ -102,3 +186,52 @@ impl<'a> Hash for Scheme<'a> {
 		self.as_str().hash(hasher)
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn well_known_schemes() {
+		let schemes = [
+			Scheme::HTTP,
+			Scheme::HTTPS,
+			Scheme::WS,
+			Scheme::WSS,
+			Scheme::FTP,
+			Scheme::FILE,
+			Scheme::DATA,
+			Scheme::MAILTO,
+			Scheme::TEL,
+			Scheme::URN,
+		];
+
+		for scheme in schemes.iter() {
+			assert_eq!(Scheme::try_from(scheme.as_str()).unwrap(), *scheme);
+		}
+
+		assert_eq!(Scheme::HTTPS.default_port(), Some(443));
+		assert_eq!(Scheme::URN.authority_required(), Some(false));
+	}
+
+	#[test]
+	fn authority_required() {
+		let challenges = [
+			("http", Some(true)),
+			("HTTPS", Some(true)),
+			("ftp", Some(true)),
+			("mailto", Some(false)),
+			("Tel", Some(false)),
+			("data", Some(false)),
+			("urn", Some(false)),
+			("news", None),
+			("file", None),
+			("foo", None),
+		];
+
+		for (scheme, expected) in challenges.iter() {
+			let scheme = Scheme::try_from(*scheme).unwrap();
+			assert_eq!(scheme.authority_required(), *expected, "{}", scheme);
+		}
+	}
+}
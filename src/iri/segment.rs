@@ -1,4 +1,5 @@
-use super::Error;
+use super::decode::{pct_cmp, pct_eq, pct_eq_str, pct_hash};
+use super::{Error, PercentDecode};
 use crate::parsing;
 use pct_str::PctStr;
 use std::cmp::{Ord, Ordering, PartialOrd};
@@ -63,6 +64,12 @@ impl<'a> Segment<'a> {
 		unsafe { PctStr::new_unchecked(self.as_str()) }
 	}
 
+	/// Returns an iterator over the percent-decoded bytes of the segment.
+	#[inline]
+	pub fn percent_decode(&self) -> PercentDecode<'a> {
+		PercentDecode::new(self.data)
+	}
+
 	#[inline]
 	pub fn is_open(&self) -> bool {
 		self.open
@@ -135,7 +142,7 @@ impl<'a> fmt::Debug for Segment<'a> {
 impl<'a> cmp::PartialEq for Segment<'a> {
 	#[inline]
 	fn eq(&self, other: &Segment) -> bool {
-		self.open == other.open && self.as_pct_str() == other.as_pct_str()
+		self.open == other.open && pct_eq(self.data, other.data)
 	}
 }
 
@@ -144,7 +151,7 @@ impl<'a> Eq for Segment<'a> {}
 impl<'a> cmp::PartialEq<&'a str> for Segment<'a> {
 	#[inline]
 	fn eq(&self, other: &&'a str) -> bool {
-		self.as_pct_str() == *other
+		pct_eq_str(self.data, other)
 	}
 }
 
@@ -158,13 +165,13 @@ impl<'a> PartialOrd for Segment<'a> {
 impl<'a> Ord for Segment<'a> {
 	#[inline]
 	fn cmp(&self, other: &Segment<'a>) -> Ordering {
-		self.as_pct_str().cmp(other.as_pct_str())
+		pct_cmp(self.data, other.data)
 	}
 }
 
 impl<'a> Hash for Segment<'a> {
 	#[inline]
 	fn hash<H: Hasher>(&self, hasher: &mut H) {
-		self.as_pct_str().hash(hasher)
+		pct_hash(self.data, hasher)
 	}
 }
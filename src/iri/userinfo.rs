@@ -1,4 +1,5 @@
-use super::Error;
+use super::decode::{pct_cmp, pct_eq, pct_hash};
+use super::{Error, PercentDecode};
 use crate::parsing;
 use pct_str::PctStr;
 use std::cmp::{Ord, Ordering, PartialOrd};
@@ -31,6 +32,12 @@ impl<'a> UserInfo<'a> {
 		unsafe { PctStr::new_unchecked(self.as_str()) }
 	}
 
+	/// Returns an iterator over the percent-decoded bytes of the user info.
+	#[inline]
+	pub fn percent_decode(&self) -> PercentDecode<'a> {
+		PercentDecode::new(self.data)
+	}
+
 	/// Checks if the userinfo is empty.
 	#[inline]
 	pub fn is_empty(&self) -> bool {
@@ -76,7 +83,7 @@ impl<'a> fmt::Debug for UserInfo<'a> {
 impl<'a> cmp::PartialEq for UserInfo<'a> {
 	#[inline]
 	fn eq(&self, other: &UserInfo) -> bool {
-		self.as_pct_str() == other.as_pct_str()
+		pct_eq(self.data, other.data)
 	}
 }
 
@@ -99,13 +106,13 @@ impl<'a> PartialOrd for UserInfo<'a> {
 impl<'a> Ord for UserInfo<'a> {
 	#[inline]
 	fn cmp(&self, other: &UserInfo<'a>) -> Ordering {
-		self.as_pct_str().cmp(other.as_pct_str())
+		pct_cmp(self.data, other.data)
 	}
 }
 
 impl<'a> Hash for UserInfo<'a> {
 	#[inline]
 	fn hash<H: Hasher>(&self, hasher: &mut H) {
-		self.as_pct_str().hash(hasher)
+		pct_hash(self.data, hasher)
 	}
 }
@@ -135,6 +135,26 @@
 //! # }
 //! ```
 //!
+//! The four IRI types can be converted into one another with the `From` and `TryFrom`
+//! traits, either by value or by reference.
+//! Conversions into `Iri` and `IriBuf` are fallible (`TryFrom`) when the input is an
+//! IRI reference, since it may have no scheme, and infallible (`From`) otherwise:
+//!
+//! | From \ Into  | `Iri`     | `IriRef` | `IriBuf`  | `IriRefBuf` |
+//! |--------------|-----------|----------|-----------|-------------|
+//! | `Iri`        | -         | `From`   | `From`    | `From`      |
+//! | `&Iri`       | `From`    | `From`   | `From`    | `From`      |
+//! | `IriRef`     | `TryFrom` | -        | `TryFrom` | `From`      |
+//! | `&IriRef`    | `TryFrom` | `From`   | `TryFrom` | `From`      |
+//! | `IriBuf`     |           |          | -         | `From`      |
+//! | `&IriBuf`    | `From`    | `From`   | `From`    | `From`      |
+//! | `IriRefBuf`  |           |          | `TryFrom` | -           |
+//! | `&IriRefBuf` | `TryFrom` | `From`   | `TryFrom` | `From`      |
+//!
+//! The owned types `IriBuf` and `IriRefBuf` can also be parsed from a `&str` with `TryFrom`,
+//! and converted into a `String` or `Vec<u8>` with `From`, handing over their internal buffer
+//! without copying it.
+//!
 //! Given a base IRI, references can be resolved into a regular IRI using the
 //! [Reference Resolution Algorithm](https://tools.ietf.org/html/rfc3986#section-5)
 //! defined in [RFC 3986](https://tools.ietf.org/html/rfc3986).
@@ -196,14 +216,45 @@
 //! Thanks to the [`pct-str` crate](https://crates.io/crates/pct-str),
 //! percent encoded characters are correctly handled.
 //! The two IRIs `http://example.org` and `http://exa%6dple.org` **are** equivalent.
+//!
+//! #### The `normalized-eq` feature
+//!
+//! Enabling the `normalized-eq` feature changes the `PartialEq`, `Eq`, `Ord` and `Hash`
+//! implementations of `Iri`, `IriBuf`, `IriRef` and `IriRefBuf` to use the normalized form
+//! given by [`IriRef::sort_key`]:
+//! the scheme and host are compared case-insensitively,
+//! and the port is normalized (leading zeros and default ports of well-known schemes are
+//! ignored).
+//! This way a `HashSet<IriBuf>` will deduplicate equivalent IRIs such as
+//! `HTTP://Example.org:80/` and `http://example.org/`.
+//!
+//! **Be careful**: this feature is not protocol agnostic anymore, and since Cargo
+//! features are additive, enabling it in one crate changes the comparison of IRIs
+//! for every crate of the dependency graph.
+//! Comparing and hashing is also more expensive since it allocates the normalized form.
+//! Only the IRI types are affected: components such as `Authority` or `Path` are still
+//! compared as described above.
+//!
+//! #### The `idna` feature
+//!
+//! Enabling the `idna` feature makes [`Host::canonical`] (and the `canonical_host` methods
+//! built on it) convert internationalized domain names to their ASCII form,
+//! so that `例え.jp` and `xn--r8jz45g.jp` have the same canonical host.
 #![allow(clippy::tabs_in_doc_comments)]
 
 mod iri;
 pub mod parsing;
+mod pattern;
 mod reference;
+mod str_key;
+mod template;
+pub mod whatwg;
 
 pub use crate::iri::*;
+pub use crate::pattern::*;
 pub use crate::reference::*;
+pub use crate::str_key::*;
+pub use crate::template::*;
 use std::ops::Range;
 
 /// Replacement function in IRI-reference buffers.
@@ -1,8 +1,30 @@
 mod utf8;
 
 use super::Error;
+use std::ops::Range;
+
+/// Returns the `offset..(offset + len)` byte range of a component in a buffer of
+/// `data_len` bytes.
+///
+/// The lengths and offsets computed by [`ParsedIriRef`] saturate instead of overflowing,
+/// so inconsistent parsing data always ends up here.
+///
+/// # Panics
+///
+/// Panics if the range is out of the buffer bounds, meaning that the parsing data does not
+/// describe the buffer (which can only happen if it was built with `from_raw`).
+#[inline]
+pub(crate) fn component_range(offset: usize, len: usize, data_len: usize) -> Range<usize> {
+	match offset.checked_add(len) {
+		Some(end) if end <= data_len => offset..end,
+		_ => panic!(
+			"inconsistent IRI parsing data: component at {} of length {} is out of bounds (IRI length is {})",
+			offset, len, data_len
+		),
+	}
+}
 
-#[derive(Default, Clone, Copy)]
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
 pub struct ParsedAuthority {
 	pub userinfo_len: Option<usize>,
 	pub host_len: usize,
@@ -17,16 +39,16 @@ impl ParsedAuthority {
 
 	#[inline]
 	pub fn len(&self) -> usize {
-		let mut len = 0;
+		let mut len: usize = 0;
 
 		if let Some(l) = self.userinfo_len {
-			len += l + 1;
+			len = len.saturating_add(l).saturating_add(1);
 		}
 
-		len += self.host_len;
+		len = len.saturating_add(self.host_len);
 
 		if let Some(l) = self.port_len {
-			len += 1 + l;
+			len = len.saturating_add(1).saturating_add(l);
 		}
 
 		len
@@ -34,10 +56,10 @@ impl ParsedAuthority {
 
 	#[inline]
 	pub fn host_offset(&self) -> usize {
-		let mut offset = 0;
+		let mut offset: usize = 0;
 
 		if let Some(l) = self.userinfo_len {
-			offset += l + 1;
+			offset = offset.saturating_add(l).saturating_add(1);
 		}
 
 		offset
@@ -45,23 +67,40 @@ impl ParsedAuthority {
 
 	#[inline]
 	pub fn port_offset(&self) -> usize {
-		let mut offset = 0;
+		let mut offset: usize = 0;
 
 		if let Some(l) = self.userinfo_len {
-			offset += l + 1;
+			offset = offset.saturating_add(l).saturating_add(1);
 		}
 
-		offset += self.host_len;
+		offset = offset.saturating_add(self.host_len);
 
 		if self.port_len.is_some() {
-			offset += 1;
+			offset = offset.saturating_add(1);
 		}
 
 		offset
 	}
 }
 
-#[derive(Default, Clone, Copy)]
+/// Parsing options.
+///
+/// Limits put on the input when parsing untrusted IRIs or IRI references.
+/// By default, no limit is set.
+#[derive(Default, Clone, Copy, Debug)]
+pub struct ParseOptions {
+	/// Maximum length of the input, in bytes.
+	pub max_len: Option<usize>,
+
+	/// Maximum number of segments in the path, as given by
+	/// [`Path::segments`](crate::Path::segments).
+	///
+	/// This bounds the work done to remove dot segments when the IRI reference is normalized
+	/// or resolved.
+	pub max_segments: Option<usize>,
+}
+
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
 pub struct ParsedIriRef {
 	pub scheme_len: Option<usize>,
 	pub authority: Option<ParsedAuthority>,
@@ -155,26 +194,75 @@ impl ParsedIriRef {
 		}
 	}
 
+	/// Checks that this parsing data correctly describes the given buffer.
+	///
+	/// The buffer must have the length described by the parsing data and parse into the
+	/// exact same components.
+	/// Returns [`Error::InvalidParsingData`] if the parsing data does not match the buffer,
+	/// or the parsing error if the buffer is not a valid IRI reference.
+	#[inline]
+	pub fn check_against(&self, data: &[u8]) -> Result<(), Error> {
+		if self.len() != data.len() {
+			return Err(Error::InvalidParsingData);
+		}
+
+		if ParsedIriRef::new(data)? == *self {
+			Ok(())
+		} else {
+			Err(Error::InvalidParsingData)
+		}
+	}
+
+	/// Parse the input buffer, with the given parsing options.
+	#[inline]
+	pub fn new_with_options<S: AsRef<[u8]> + ?Sized>(
+		buffer: &S,
+		options: &ParseOptions,
+	) -> Result<ParsedIriRef, Error> {
+		let buffer = buffer.as_ref();
+
+		if let Some(max_len) = options.max_len {
+			if buffer.len() > max_len {
+				return Err(Error::TooLong);
+			}
+		}
+
+		let p = ParsedIriRef::new(buffer)?;
+
+		if let Some(max_segments) = options.max_segments {
+			let offset = p.path_offset();
+			let path = crate::Path {
+				data: &buffer[offset..(offset + p.path_len)],
+			};
+			let segment_count = path.segments().count();
+			if segment_count > max_segments {
+				return Err(Error::TooManySegments);
+			}
+		}
+
+		Ok(p)
+	}
+
 	#[inline]
 	pub fn len(&self) -> usize {
-		let mut offset = 0;
+		let mut offset: usize = 0;
 
 		if let Some(len) = self.scheme_len {
-			offset += len + 1;
+			offset = offset.saturating_add(len).saturating_add(1);
 		}
 
 		if let Some(authority) = self.authority.as_ref() {
-			offset += 2 + authority.len();
+			offset = offset.saturating_add(2).saturating_add(authority.len());
 		}
 
-		offset += self.path_len;
+		offset = offset.saturating_add(self.path_len);
 
 		if let Some(len) = self.query_len {
-			offset += 1 + len;
+			offset = offset.saturating_add(1).saturating_add(len);
 		}
 
 		if let Some(len) = self.fragment_len {
-			offset += 1 + len;
+			offset = offset.saturating_add(1).saturating_add(len);
 		}
 
 		offset
@@ -191,14 +279,14 @@ impl ParsedIriRef {
 
 	#[inline]
 	pub fn authority_offset(&self) -> usize {
-		let mut offset = 0;
+		let mut offset: usize = 0;
 
 		if let Some(len) = self.scheme_len {
-			offset += len + 1;
+			offset = offset.saturating_add(len).saturating_add(1);
 		}
 
 		if self.authority.is_some() {
-			offset += 2;
+			offset = offset.saturating_add(2);
 		}
 
 		offset
@@ -206,14 +294,14 @@ impl ParsedIriRef {
 
 	#[inline]
 	pub fn path_offset(&self) -> usize {
-		let mut offset = 0;
+		let mut offset: usize = 0;
 
 		if let Some(len) = self.scheme_len {
-			offset += len + 1;
+			offset = offset.saturating_add(len).saturating_add(1);
 		}
 
 		if let Some(authority) = self.authority.as_ref() {
-			offset += 2 + authority.len();
+			offset = offset.saturating_add(2).saturating_add(authority.len());
 		}
 
 		offset
@@ -221,10 +309,10 @@ impl ParsedIriRef {
 
 	#[inline]
 	pub fn query_offset(&self) -> usize {
-		let mut offset = self.path_offset() + self.path_len;
+		let mut offset = self.path_offset().saturating_add(self.path_len);
 
 		if self.query_len.is_some() {
-			offset += 1;
+			offset = offset.saturating_add(1);
 		}
 
 		offset
@@ -232,14 +320,14 @@ impl ParsedIriRef {
 
 	#[inline]
 	pub fn fragment_offset(&self) -> usize {
-		let mut offset = self.path_offset() + self.path_len;
+		let mut offset = self.path_offset().saturating_add(self.path_len);
 
 		if let Some(len) = self.query_len {
-			offset += 1 + len;
+			offset = offset.saturating_add(1).saturating_add(len);
 		}
 
 		if self.fragment_len.is_some() {
-			offset += 1;
+			offset = offset.saturating_add(1);
 		}
 
 		offset
@@ -309,18 +397,32 @@ fn is_ucschar(c: char) -> bool {
 		|| (0xE1000..=0xEFFFD).contains(&c)
 }
 
-fn is_private(c: char) -> bool {
+pub(crate) fn is_private(c: char) -> bool {
 	let c = c as u32;
 	(0xE000..=0xF8FF).contains(&c)
 		|| (0xF0000..=0xFFFFD).contains(&c)
 		|| (0x100000..=0x10FFFD).contains(&c)
 }
 
-fn is_unreserved(c: char) -> bool {
+/// Checks if the given character is a bidirectional formatting character.
+///
+/// [RFC 3987 section 4.1](https://tools.ietf.org/html/rfc3987#section-4.1) forbids
+/// LRM (U+200E), RLM (U+200F), LRE (U+202A), RLE (U+202B), PDF (U+202C), LRO (U+202D)
+/// and RLO (U+202E) in IRIs.
+/// This also includes the formatting characters introduced since then by Unicode:
+/// ALM (U+061C), LRI (U+2066), RLI (U+2067), FSI (U+2068) and PDI (U+2069).
+pub(crate) fn is_bidi_formatting(c: char) -> bool {
+	matches!(
+		c,
+		'\u{061c}' | '\u{200e}' | '\u{200f}' | '\u{202a}'..='\u{202e}' | '\u{2066}'..='\u{2069}'
+	)
+}
+
+pub(crate) fn is_unreserved(c: char) -> bool {
 	is_alphanumeric(c) || c == '-' || c == '.' || c == '_' || c == '~' || is_ucschar(c)
 }
 
-fn is_subdelim(c: char) -> bool {
+pub(crate) fn is_subdelim(c: char) -> bool {
 	matches!(
 		c,
 		'!' | '$' | '&' | '\'' | '(' | ')' | '*' | '+' | ',' | ';' | '='
@@ -347,6 +449,31 @@ fn parse_pct_encoded(buffer: &[u8], i: usize) -> Result<Option<usize>, Error> {
 	}
 }
 
+/// Returns the byte position of the first malformed percent-encoded octet in `buffer`.
+///
+/// A `%` character not followed by two ASCII hexadecimal digits is malformed.
+/// This can be used to locate the error when parsing fails with
+/// [`Error::InvalidPercentEncoding`].
+///
+/// # Example
+///
+/// ```
+/// # use iref::{Error, IriRef, parsing::find_invalid_pct_encoded};
+/// let input = "http://example.org/a%2Gb";
+/// assert_eq!(IriRef::new(input), Err(Error::InvalidPercentEncoding));
+/// assert_eq!(find_invalid_pct_encoded(input.as_bytes()), Some(20));
+/// ```
+pub fn find_invalid_pct_encoded(buffer: &[u8]) -> Option<usize> {
+	buffer.iter().enumerate().find_map(|(i, b)| {
+		let is_hex = |j: usize| matches!(buffer.get(j), Some(b) if b.is_ascii_hexdigit());
+		if *b == b'%' && !(is_hex(i + 1) && is_hex(i + 2)) {
+			Some(i)
+		} else {
+			None
+		}
+	})
+}
+
 #[inline]
 pub fn parse_userinfo(buffer: &[u8], mut i: usize) -> Result<usize, Error> {
 	let offset = i;
@@ -371,6 +498,16 @@ pub fn parse_userinfo(buffer: &[u8], mut i: usize) -> Result<usize, Error> {
 	Ok(i - offset)
 }
 
+/// Checks if the given character may appear as is (not percent-encoded) in a query.
+pub(crate) fn is_query_char(c: char) -> bool {
+	is_fragment_char(c) || is_private(c)
+}
+
+/// Checks if the given character may appear as is (not percent-encoded) in a fragment.
+pub(crate) fn is_fragment_char(c: char) -> bool {
+	c == ':' || c == '@' || c == '/' || c == '?' || is_subdelim(c) || is_unreserved(c)
+}
+
 #[inline]
 pub fn parse_query(buffer: &[u8], mut i: usize) -> Result<usize, Error> {
 	let offset = i;
@@ -384,14 +521,7 @@ pub fn parse_query(buffer: &[u8], mut i: usize) -> Result<usize, Error> {
 					break;
 				}
 			}
-			Some((c, len))
-				if c == ':'
-					|| c == '@' || c == '/'
-					|| c == '?' || is_subdelim(c)
-					|| is_unreserved(c) || is_private(c) =>
-			{
-				i += len
-			}
+			Some((c, len)) if is_query_char(c) => i += len,
 			_ => break,
 		}
 	}
@@ -412,14 +542,7 @@ pub fn parse_fragment(buffer: &[u8], mut i: usize) -> Result<usize, Error> {
 					break;
 				}
 			}
-			Some((c, len))
-				if c == ':'
-					|| c == '@' || c == '/'
-					|| c == '?' || is_subdelim(c)
-					|| is_unreserved(c) =>
-			{
-				i += len
-			}
+			Some((c, len)) if is_fragment_char(c) => i += len,
 			_ => break,
 		}
 	}
@@ -503,7 +626,11 @@ fn parse_h16(buffer: &[u8], i: usize) -> Result<Option<(u16, usize)>, Error> {
 		}
 	}
 
-	Ok(Some((h16, len)))
+	if len == 0 {
+		Ok(None)
+	} else {
+		Ok(Some((h16, len)))
+	}
 }
 
 /// Parse an IPv6 literal.
@@ -527,7 +654,8 @@ fn parse_ipv6_literal(buffer: &[u8], mut i: usize) -> Result<Option<(u128, usize
 			if let Some((':', 1)) = get_char(buffer, i)? {
 				i += 1;
 
-				if lhs_count == 0 {
+				if lit_count == 0 {
+					// Leading `::`.
 					if let Some((':', 1)) = get_char(buffer, i)? {
 						i += 1;
 					} else {
@@ -542,6 +670,12 @@ fn parse_ipv6_literal(buffer: &[u8], mut i: usize) -> Result<Option<(u128, usize
 
 				lit = 0;
 				lit_count = 1;
+
+				if let Some((']', 1)) = get_char(buffer, i)? {
+					// Trailing `::`.
+					break;
+				}
+
 				continue;
 			}
 		}
@@ -549,7 +683,7 @@ fn parse_ipv6_literal(buffer: &[u8], mut i: usize) -> Result<Option<(u128, usize
 		if lhs_count + lit_count <= 6 {
 			if let Some((ipv4, len)) = parse_ipv4_literal(buffer, i)? {
 				lit = (lit << 32) | ipv4 as u128;
-				// lit_count += 2;
+				lit_count += 2;
 				i += len;
 				break;
 			}
@@ -572,6 +706,10 @@ fn parse_ipv6_literal(buffer: &[u8], mut i: usize) -> Result<Option<(u128, usize
 		}
 	}
 
+	if is_lhs && lit_count != 8 {
+		return Ok(None); // Invalid IPv6 (not enough pieces)
+	}
+
 	if lhs_count > 0 {
 		lit |= lhs << (16 * (8 - lhs_count));
 	}
@@ -6,22 +6,32 @@ pub fn expect_byte(buffer: &[u8], i: usize) -> Result<u32, ()> {
 	get_byte(buffer, i).ok_or(())
 }
 
+/// Expect a continuation byte (`10xxxxxx`).
+pub fn expect_continuation_byte(buffer: &[u8], i: usize) -> Result<u32, ()> {
+	let b = expect_byte(buffer, i)?;
+	if b & 0xc0 == 0x80 {
+		Ok(b)
+	} else {
+		Err(())
+	}
+}
+
 /// Return a char and the size of its UTF-8 encoding.
 pub fn get_codepoint(buffer: &[u8], i: usize) -> Result<Option<(u32, u8)>, ()> {
 	if let Some(a) = get_byte(buffer, i) {
 		let r = if a & 0x80 == 0x00 {
 			(a, 1)
 		} else if a & 0xe0 == 0xc0 {
-			let b = expect_byte(buffer, i + 1)?;
+			let b = expect_continuation_byte(buffer, i + 1)?;
 			((a & 0x1f) << 6 | (b & 0x3f), 2)
 		} else if a & 0xf0 == 0xe0 {
-			let b = expect_byte(buffer, i + 1)?;
-			let c = expect_byte(buffer, i + 2)?;
+			let b = expect_continuation_byte(buffer, i + 1)?;
+			let c = expect_continuation_byte(buffer, i + 2)?;
 			((a & 0x0f) << 12 | (b & 0x3f) << 6 | (c & 0x3f), 3)
 		} else if a & 0xf8 == 0xf0 {
-			let b = expect_byte(buffer, i + 1)?;
-			let c = expect_byte(buffer, i + 2)?;
-			let d = expect_byte(buffer, i + 3)?;
+			let b = expect_continuation_byte(buffer, i + 1)?;
+			let c = expect_continuation_byte(buffer, i + 2)?;
+			let d = expect_continuation_byte(buffer, i + 3)?;
 			(
 				(a & 0x07) << 18 | (b & 0x3f) << 12 | (c & 0x3f) << 6 | (d & 0x3f),
 				4,
@@ -30,6 +40,18 @@ pub fn get_codepoint(buffer: &[u8], i: usize) -> Result<Option<(u32, u8)>, ()> {
 			return Err(());
 		};
 
+		// Overlong encodings are not valid UTF-8.
+		let min = match r.1 {
+			1 => 0,
+			2 => 0x80,
+			3 => 0x800,
+			_ => 0x10000,
+		};
+
+		if r.0 < min {
+			return Err(());
+		}
+
 		Ok(Some(r))
 	} else {
 		Ok(None)
@@ -66,4 +88,28 @@ pub mod tests {
 
 		assert_eq!(decoded, string)
 	}
+
+	#[test]
+	fn reject_invalid() {
+		let invalid: [&[u8]; 7] = [
+			b"\xc0\xaf",         // overlong `/`
+			b"\xe0\x80\xaf",     // overlong `/`
+			b"\xf0\x80\x80\xaf", // overlong `/`
+			b"\xc3A",            // invalid continuation byte
+			b"\xed\xa0\x80",     // surrogate U+D800
+			b"\xf4\x90\x80\x80", // U+110000
+			b"\x80",             // lone continuation byte
+		];
+
+		for bytes in invalid.iter() {
+			assert!(get_char(bytes, 0).is_err(), "{:?}", bytes);
+			assert!(std::str::from_utf8(bytes).is_err());
+		}
+
+		assert_eq!(get_char(b"\xc2\x80", 0), Ok(Some(('\u{80}', 2))));
+		assert_eq!(
+			get_char(b"\xf4\x8f\xbf\xbf", 0),
+			Ok(Some(('\u{10ffff}', 4)))
+		);
+	}
 }
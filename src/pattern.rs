@@ -0,0 +1,314 @@
+use std::{fmt, str::FromStr};
+
+use crate::{pct_eq, AsIriRef, Error, Fragment, IriRef, Query, Segment};
+
+/// Pattern matching a scheme or an authority.
+#[derive(Clone, Debug)]
+enum ComponentPattern {
+	/// `*`, matching any value.
+	Any,
+
+	/// Literal value.
+	Exact(String),
+}
+
+/// Pattern matching path segments.
+#[derive(Clone, Debug)]
+enum SegmentPattern {
+	/// `*`, matching exactly one segment.
+	One,
+
+	/// `**`, matching any number of segments (including none).
+	Many,
+
+	/// Literal segment.
+	Exact(String),
+}
+
+/// Glob-like IRI pattern.
+///
+/// A pattern is an IRI-reference in which the scheme, the authority and each path segment
+/// can be replaced by a wildcard:
+///
+///   - `*` as scheme (`*://...`) matches any scheme;
+///   - `*` as authority (`http://*/...`) matches any authority;
+///   - `*` as path segment matches exactly one segment;
+///   - `**` as path segment matches any number of segments, including none.
+///
+/// Other components are matched literally:
+/// the scheme is compared case-insensitively,
+/// the authority and segments are compared after percent-decoding.
+/// An IRI-reference without scheme (or authority) in the pattern only matches
+/// IRI-references without scheme (or authority).
+/// The path of the matched IRI-reference is normalized before matching
+/// (see [`Path::normalized_segments`](crate::Path::normalized_segments)),
+/// and trailing slashes are not significant.
+/// The query and fragment are compared only if they appear in the pattern,
+/// and are ignored otherwise.
+///
+/// # Example
+///
+/// ```
+/// # use iref::{IriPattern, IriRef};
+/// let pattern = IriPattern::new("http://x/api/*/users").unwrap();
+/// assert!(pattern.matches(IriRef::new("http://x/api/v2/users").unwrap()));
+/// assert!(!pattern.matches(IriRef::new("http://x/api/v2/admin/users").unwrap()));
+///
+/// let pattern = IriPattern::new("*://*/static/**").unwrap();
+/// assert!(pattern.matches(IriRef::new("https://example.org/static/css/main.css").unwrap()));
+/// assert!(!pattern.matches(IriRef::new("https://example.org/api").unwrap()));
+/// ```
+#[derive(Clone)]
+pub struct IriPattern {
+	source: String,
+	scheme: Option<ComponentPattern>,
+	authority: Option<ComponentPattern>,
+	absolute: bool,
+	segments: Vec<SegmentPattern>,
+	query: Option<String>,
+	fragment: Option<String>,
+}
+
+impl IriPattern {
+	/// Compiles the given pattern.
+	///
+	/// Fails if the pattern, once its wildcards are taken into account,
+	/// is not a valid IRI-reference.
+	pub fn new(pattern: &str) -> Result<IriPattern, Error> {
+		// `*` is not a valid scheme, so it is replaced by a placeholder before parsing.
+		let (any_scheme, iri_ref) = match pattern.strip_prefix("*:") {
+			Some(rest) => (true, IriRef::new(&format!("x:{}", rest))?.to_owned()),
+			None => (false, IriRef::new(pattern)?.to_owned()),
+		};
+
+		let scheme = iri_ref.scheme().map(|scheme| {
+			if any_scheme {
+				ComponentPattern::Any
+			} else {
+				ComponentPattern::Exact(scheme.as_str().to_string())
+			}
+		});
+
+		let authority = iri_ref.authority().map(|authority| {
+			if authority.as_str() == "*" {
+				ComponentPattern::Any
+			} else {
+				ComponentPattern::Exact(authority.as_str().to_string())
+			}
+		});
+
+		let path = iri_ref.path();
+		let segments = path
+			.segments()
+			.map(|segment| match segment.as_str() {
+				"*" => SegmentPattern::One,
+				"**" => SegmentPattern::Many,
+				s => SegmentPattern::Exact(s.to_string()),
+			})
+			.collect();
+
+		Ok(IriPattern {
+			source: pattern.to_string(),
+			scheme,
+			authority,
+			absolute: path.is_absolute(),
+			segments,
+			query: iri_ref.query().map(|query| query.as_str().to_string()),
+			fragment: iri_ref
+				.fragment()
+				.map(|fragment| fragment.as_str().to_string()),
+		})
+	}
+
+	/// Returns the source of the pattern.
+	#[inline]
+	pub fn as_str(&self) -> &str {
+		&self.source
+	}
+
+	/// Checks if the given IRI-reference matches this pattern.
+	pub fn matches<I: AsIriRef>(&self, iri_ref: I) -> bool {
+		let iri_ref = iri_ref.as_iri_ref();
+
+		let scheme_matches = match (&self.scheme, iri_ref.scheme()) {
+			(None, None) => true,
+			(Some(ComponentPattern::Any), Some(_)) => true,
+			(Some(ComponentPattern::Exact(expected)), Some(scheme)) => {
+				scheme.as_str().eq_ignore_ascii_case(expected)
+			}
+			_ => false,
+		};
+
+		let authority_matches = match (&self.authority, iri_ref.authority()) {
+			(None, None) => true,
+			(Some(ComponentPattern::Any), Some(_)) => true,
+			(Some(ComponentPattern::Exact(expected)), Some(authority)) => {
+				pct_eq(authority.as_bytes(), expected.as_bytes())
+			}
+			_ => false,
+		};
+
+		let query = iri_ref.query();
+		let query_matches = optional_matches(&self.query, query.as_ref().map(Query::as_bytes));
+
+		let fragment = iri_ref.fragment();
+		let fragment_matches =
+			optional_matches(&self.fragment, fragment.as_ref().map(Fragment::as_bytes));
+
+		let path = iri_ref.path();
+		let segments: Vec<_> = path.normalized_segments().collect();
+
+		scheme_matches
+			&& authority_matches
+			&& query_matches
+			&& fragment_matches
+			&& path.is_absolute() == self.absolute
+			&& segments_match(&self.segments, &segments)
+	}
+}
+
+/// Matches an optional component, ignored if absent from the pattern.
+#[inline]
+fn optional_matches(expected: &Option<String>, value: Option<&[u8]>) -> bool {
+	match (expected, value) {
+		(Some(expected), Some(value)) => pct_eq(expected.as_bytes(), value),
+		(Some(_), None) => false,
+		(None, _) => true,
+	}
+}
+
+/// Matches the given segments against the segment patterns.
+///
+/// When a segment does not match, the matching resumes after the last `**` seen,
+/// letting it absorb one more segment.
+/// Only the last `**` needs to be retried since the ones before it can only absorb
+/// segments that it could absorb itself, so this takes quadratic time at worst.
+fn segments_match(patterns: &[SegmentPattern], segments: &[Segment]) -> bool {
+	let mut p = 0;
+	let mut s = 0;
+	let mut last_many: Option<(usize, usize)> = None;
+
+	while s < segments.len() {
+		let matches = match patterns.get(p) {
+			Some(SegmentPattern::Many) => {
+				last_many = Some((p, s));
+				p += 1;
+				continue;
+			}
+			Some(SegmentPattern::One) => true,
+			Some(SegmentPattern::Exact(expected)) => {
+				pct_eq(segments[s].as_bytes(), expected.as_bytes())
+			}
+			None => false,
+		};
+
+		if matches {
+			p += 1;
+			s += 1;
+		} else {
+			match last_many {
+				Some((many_p, many_s)) => {
+					last_many = Some((many_p, many_s + 1));
+					p = many_p + 1;
+					s = many_s + 1;
+				}
+				None => return false,
+			}
+		}
+	}
+
+	patterns[p..]
+		.iter()
+		.all(|pattern| matches!(pattern, SegmentPattern::Many))
+}
+
+impl FromStr for IriPattern {
+	type Err = Error;
+
+	#[inline]
+	fn from_str(s: &str) -> Result<IriPattern, Error> {
+		IriPattern::new(s)
+	}
+}
+
+impl fmt::Display for IriPattern {
+	#[inline]
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		self.source.fmt(f)
+	}
+}
+
+impl fmt::Debug for IriPattern {
+	#[inline]
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		self.source.fmt(f)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn matches(pattern: &str, iri_ref: &str) -> bool {
+		IriPattern::new(pattern)
+			.unwrap()
+			.matches(IriRef::new(iri_ref).unwrap())
+	}
+
+	#[test]
+	fn single_segment_wildcard() {
+		assert!(matches("http://x/api/*/users", "http://x/api/v2/users"));
+		assert!(matches("http://x/api/*/users", "HTTP://x/api/v%32/users/"));
+		assert!(!matches("http://x/api/*/users", "http://x/api/users"));
+		assert!(!matches("http://x/api/*/users", "http://y/api/v2/users"));
+		assert!(!matches("http://x/api/*/users", "https://x/api/v2/users"));
+	}
+
+	#[test]
+	fn many_segments_wildcard() {
+		assert!(matches("http://x/a/**", "http://x/a"));
+		assert!(matches("http://x/a/**/z", "http://x/a/b/c/z"));
+		assert!(matches("http://x/a/**/z", "http://x/a/z"));
+		assert!(!matches("http://x/a/**/z", "http://x/a/b/c"));
+		assert!(!matches("http://x/a/**", "http://x/a/../b"));
+		assert!(matches("/**/a/**/b/**", "/x/a/y/a/b"));
+		assert!(matches("/**/a/*/**", "/a/a"));
+		assert!(!matches("/**/a/*/**", "/b/a"));
+		assert!(matches("/**/**/a", "/a"));
+	}
+
+	#[test]
+	fn many_wildcards_are_not_exponential() {
+		let pattern = IriPattern::new(&format!("/{}b", "**/a/".repeat(20))).unwrap();
+		let iri_ref = IriRef::new(&format!("/{}", "a/".repeat(60)))
+			.unwrap()
+			.to_owned();
+		assert!(!pattern.matches(&iri_ref));
+	}
+
+	#[test]
+	fn invalid_utf8() {
+		assert!(matches("/a/%FF/*", "/a/%ff/b"));
+		assert!(!matches("/a/%FF", "/a/%FE"));
+		assert!(!matches("/a/%FF", "/a/%C3%BF"));
+		assert!(matches("http://%FF/a", "http://%ff/a"));
+		assert!(matches("/a?%FF#%FF", "/a?%ff#%ff"));
+		assert!(!matches("/a?%FF", "/a?%FE"));
+	}
+
+	#[test]
+	fn scheme_and_authority_wildcards() {
+		assert!(matches("*://*/a", "ftp://user@host:21/a"));
+		assert!(!matches("*://*/a", "/a"));
+		assert!(matches("/a/*", "/a/b"));
+		assert!(!matches("/a/*", "http://x/a/b"));
+	}
+
+	#[test]
+	fn query_and_fragment() {
+		assert!(matches("http://x/a", "http://x/a?q#f"));
+		assert!(matches("http://x/a?q", "http://x/a?q#f"));
+		assert!(!matches("http://x/a?q", "http://x/a?r"));
+		assert!(!matches("http://x/a#f", "http://x/a"));
+	}
+}
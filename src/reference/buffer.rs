@@ -1,19 +1,26 @@
 use std::{
+	borrow::Cow,
 	cmp::{Ord, Ordering, PartialOrd},
 	convert::{TryFrom, TryInto},
 	fmt,
 	hash::{Hash, Hasher},
-	ops::Range,
+	ops::{AddAssign, Range},
 	str::FromStr,
 };
 
 use pct_str::PctStr;
 
 use crate::{
-	parsing::ParsedIriRef, AsIriRef, Authority, AuthorityMut, Error, Fragment, Iri, IriBuf, IriRef,
-	Path, PathBuf, PathMut, Query, Scheme,
+	parsing::{self, ParseOptions, ParsedIriRef},
+	AsIriRef, Authority, AuthorityMut, ComponentMask, Components, Decomposed,
+	DefaultPctNormalizationPolicy, EncodingPolicy, Error, Fragment, Host, Iri, IriBuf, IriRef,
+	NormalizeOptions, Normalizer, Path, PathBuf, PathMut, PctNormalizationPolicy, PercentEscapes,
+	Port, Query, QueryBuf, Scheme, Segment, UserInfo,
 };
 
+use super::encoding::encode_component;
+use super::normalization;
+
 /// Owned IRI-reference.
 ///
 /// Holds a mutable buffer representing an IRI-reference.
@@ -56,6 +63,196 @@ impl IriRefBuf {
 		})
 	}
 
+	/// Creates a new IRI reference by parsing and copying the input buffer, with the given
+	/// parsing options.
+	///
+	/// See [`IriRef::new_with_options`] for more details.
+	#[inline]
+	pub fn new_with_options<S: AsRef<[u8]> + ?Sized>(
+		buffer: &S,
+		options: &ParseOptions,
+	) -> Result<IriRefBuf, Error> {
+		Ok(IriRefBuf {
+			p: ParsedIriRef::new_with_options(buffer, options)?,
+			data: Vec::from(buffer.as_ref()),
+		})
+	}
+
+	/// Creates a new IRI reference by parsing and copying the input buffer,
+	/// rejecting bidirectional formatting characters.
+	///
+	/// Those characters are forbidden by
+	/// [RFC 3987 section 4.1](https://tools.ietf.org/html/rfc3987#section-4.1)
+	/// since they can be used to spoof the displayed IRI.
+	/// See [`IriRef::has_bidi_spoofing_risk`] for the list of rejected code points.
+	/// Returns [`Error::BidiFormattingCharacter`] if one is found.
+	#[inline]
+	pub fn new_checked<S: AsRef<[u8]> + ?Sized>(buffer: &S) -> Result<IriRefBuf, Error> {
+		let iri_ref = IriRefBuf::new(buffer)?;
+
+		if iri_ref.as_str().chars().any(parsing::is_bidi_formatting) {
+			Err(Error::BidiFormattingCharacter)
+		} else {
+			Ok(iri_ref)
+		}
+	}
+
+	/// Creates a new same-document reference `#fragment` from the given fragment.
+	///
+	/// Resolving it against a base IRI only changes the fragment of the base.
+	/// Returns [`Error::InvalidFragment`] if `fragment` is not a valid fragment.
+	///
+	/// # Example
+	///
+	/// ```
+	/// # use iref::IriRefBuf;
+	/// let iri_ref = IriRefBuf::from_fragment("section-2").unwrap();
+	/// assert_eq!(iri_ref, "#section-2");
+	/// assert!(IriRefBuf::from_fragment("a#b").is_err());
+	/// ```
+	#[inline]
+	pub fn from_fragment(fragment: &str) -> Result<IriRefBuf, Error> {
+		let mut iri_ref = IriRefBuf::default();
+		iri_ref.set_fragment(Some(fragment.try_into()?));
+		Ok(iri_ref)
+	}
+
+	/// Creates a new reference `?query` from the given query.
+	///
+	/// Resolving it against a base IRI only changes the query of the base
+	/// (and removes its fragment).
+	/// Returns [`Error::InvalidQuery`] if `query` is not a valid query.
+	///
+	/// # Example
+	///
+	/// ```
+	/// # use iref::IriRefBuf;
+	/// let iri_ref = IriRefBuf::from_query("page=2").unwrap();
+	/// assert_eq!(iri_ref, "?page=2");
+	/// assert!(IriRefBuf::from_query("a#b").is_err());
+	/// ```
+	#[inline]
+	pub fn from_query(query: &str) -> Result<IriRefBuf, Error> {
+		let mut iri_ref = IriRefBuf::default();
+		iri_ref.set_query(Some(query.try_into()?));
+		Ok(iri_ref)
+	}
+
+	/// Assembles a new IRI reference from its components.
+	///
+	/// Each component is already valid on its own, so only the way they fit together is
+	/// checked: with an authority the path must be empty or absolute, without authority the
+	/// path must not start with `//`, and without scheme nor authority the first segment of
+	/// the path must not contain `:`.
+	/// Returns [`Error::InvalidPath`] if it is not the case.
+	///
+	/// The parsing data is computed directly from the components, without parsing the
+	/// resulting IRI reference again.
+	///
+	/// # Example
+	///
+	/// ```
+	/// # use std::convert::TryInto;
+	/// # use iref::IriRefBuf;
+	/// # fn main() -> Result<(), iref::Error> {
+	/// let iri_ref = IriRefBuf::from_components(
+	/// 	Some("https".try_into()?),
+	/// 	Some("example.org:8080".try_into()?),
+	/// 	"/a/b".try_into()?,
+	/// 	Some("q".try_into()?),
+	/// 	None,
+	/// )?;
+	///
+	/// assert_eq!(iri_ref, "https://example.org:8080/a/b?q");
+	/// assert_eq!(iri_ref.authority().unwrap().port().unwrap(), "8080");
+	///
+	/// let rootless = IriRefBuf::from_components(None, Some("h".try_into()?), "a".try_into()?, None, None);
+	/// assert_eq!(rootless, Err(iref::Error::InvalidPath));
+	/// # Ok(())
+	/// # }
+	/// ```
+	pub fn from_components(
+		scheme: Option<Scheme>,
+		authority: Option<Authority>,
+		path: Path,
+		query: Option<Query>,
+		fragment: Option<Fragment>,
+	) -> Result<IriRefBuf, Error> {
+		let path_bytes = path.as_bytes();
+
+		if authority.is_some() {
+			if !path_bytes.is_empty() && path_bytes[0] != b'/' {
+				return Err(Error::InvalidPath);
+			}
+		} else {
+			if path_bytes.starts_with(b"//") {
+				return Err(Error::InvalidPath);
+			}
+
+			if scheme.is_none() {
+				let first_segment = path_bytes.split(|c| *c == b'/').next().unwrap();
+				if first_segment.contains(&b':') {
+					return Err(Error::InvalidPath);
+				}
+			}
+		}
+
+		Ok(unsafe {
+			IriRefBuf::from_components_unchecked(scheme, authority, path, query, fragment)
+		})
+	}
+
+	/// Assembles a new IRI reference from its components, without checking that they fit
+	/// together.
+	///
+	/// This is the unchecked version of [`IriRefBuf::from_components`].
+	///
+	/// # Safety
+	///
+	/// The components must form a valid IRI reference, as checked by
+	/// [`IriRefBuf::from_components`]. Otherwise the parsing data will not describe the
+	/// resulting buffer.
+	pub unsafe fn from_components_unchecked(
+		scheme: Option<Scheme>,
+		authority: Option<Authority>,
+		path: Path,
+		query: Option<Query>,
+		fragment: Option<Fragment>,
+	) -> IriRefBuf {
+		let mut data = Vec::new();
+		let mut p = ParsedIriRef::default();
+
+		if let Some(scheme) = scheme {
+			data.extend_from_slice(scheme.as_bytes());
+			data.push(b':');
+			p.scheme_len = Some(scheme.as_bytes().len());
+		}
+
+		if let Some(authority) = authority {
+			data.extend_from_slice(b"//");
+			data.extend_from_slice(authority.as_str().as_bytes());
+			p.authority = Some(authority.p);
+		}
+
+		data.extend_from_slice(path.as_bytes());
+		p.path_len = path.as_bytes().len();
+
+		if let Some(query) = query {
+			data.push(b'?');
+			data.extend_from_slice(query.as_bytes());
+			p.query_len = Some(query.as_bytes().len());
+		}
+
+		if let Some(fragment) = fragment {
+			data.push(b'#');
+			data.extend_from_slice(fragment.as_bytes());
+			p.fragment_len = Some(fragment.as_bytes().len());
+		}
+
+		debug_assert_eq!(p.check_against(&data), Ok(()));
+		IriRefBuf { data, p }
+	}
+
 	/// Creates a new IRI reference by parsing and the input buffer.
 	#[inline]
 	pub fn from_vec(buffer: Vec<u8>) -> Result<IriRefBuf, (Error, Vec<u8>)> {
@@ -77,6 +274,43 @@ impl IriRefBuf {
 		})
 	}
 
+	/// Append the given string at the end of the IRI reference buffer, without parsing it.
+	///
+	/// This is useful to assemble an IRI reference from many pieces and validate it only once
+	/// at the end with [`IriRefBuf::revalidate`].
+	///
+	/// **The buffer is in an unvalidated state** between the call to `push_str` and the call to
+	/// `revalidate`: the whole buffer is accessible with [`IriRefBuf::as_bytes`], but the
+	/// components accessors and modifiers still refer to the IRI reference as it was before
+	/// the first `push_str` call.
+	///
+	/// # Example
+	///
+	/// ```
+	/// # use iref::IriRefBuf;
+	/// let mut iri_ref = IriRefBuf::new("http://example.org").unwrap();
+	/// iri_ref.push_str("/foo");
+	/// iri_ref.push_str("?bar");
+	/// iri_ref.revalidate().unwrap();
+	///
+	/// assert_eq!(iri_ref.path(), "/foo");
+	/// assert_eq!(iri_ref.query().unwrap(), "bar");
+	/// ```
+	#[inline]
+	pub fn push_str(&mut self, s: &str) {
+		self.data.extend_from_slice(s.as_bytes())
+	}
+
+	/// Parse the whole buffer again, after some calls to [`IriRefBuf::push_str`].
+	///
+	/// Returns an error if the buffer is not a valid IRI reference.
+	/// In that case the buffer remains in an unvalidated state.
+	#[inline]
+	pub fn revalidate(&mut self) -> Result<(), Error> {
+		self.p = ParsedIriRef::new(&self.data)?;
+		Ok(())
+	}
+
 	/// Consume the IRI reference and return its constituting parts:
 	/// the internal buffer and parsing data.
 	#[inline]
@@ -92,9 +326,26 @@ impl IriRefBuf {
 	/// The parsed data must match the given `buffer`.
 	#[inline]
 	pub unsafe fn from_raw_parts(buffer: Vec<u8>, p: ParsedIriRef) -> Self {
+		debug_assert_eq!(p.check_against(&buffer), Ok(()));
 		Self { data: buffer, p }
 	}
 
+	/// Creates a new IRI reference using `buffer` and the parsing information `p`,
+	/// checking that they match.
+	///
+	/// This is the safe version of [`IriRefBuf::from_raw_parts`].
+	/// See [`ParsedIriRef::check_against`] for the performed checks.
+	#[inline]
+	pub fn try_from_raw_parts(
+		buffer: Vec<u8>,
+		p: ParsedIriRef,
+	) -> Result<IriRefBuf, (Error, Vec<u8>)> {
+		match p.check_against(&buffer) {
+			Ok(()) => Ok(IriRefBuf { data: buffer, p }),
+			Err(e) => Err((e, buffer)),
+		}
+	}
+
 	/// Consume the IRI reference and return its internal buffer.
 	#[inline]
 	pub fn into_bytes(self) -> Vec<u8> {
@@ -135,6 +386,14 @@ impl IriRefBuf {
 		unsafe { std::str::from_utf8_unchecked(&self.data[0..self.len()]) }
 	}
 
+	/// Get the IRI-reference as a string slice, checking that it is valid UTF-8.
+	///
+	/// See [`IriRef::as_str_checked`].
+	#[inline]
+	pub fn as_str_checked(&self) -> Result<&str, std::str::Utf8Error> {
+		std::str::from_utf8(&self.data[0..self.len()])
+	}
+
 	#[inline]
 	pub fn as_pct_str(&self) -> &PctStr {
 		unsafe { PctStr::new_unchecked(self.as_str()) }
@@ -152,6 +411,119 @@ impl IriRefBuf {
 		})
 	}
 
+	/// Returns the byte offset of the end of the scheme, if any.
+	///
+	/// See [`IriRef::scheme_end`].
+	#[inline]
+	pub fn scheme_end(&self) -> Option<usize> {
+		self.as_iri_ref().scheme_end()
+	}
+
+	/// Returns the byte offset of the start of the authority, if any.
+	///
+	/// See [`IriRef::authority_start`].
+	#[inline]
+	pub fn authority_start(&self) -> Option<usize> {
+		self.as_iri_ref().authority_start()
+	}
+
+	/// Returns the byte offset of the start of the path.
+	///
+	/// See [`IriRef::path_start`].
+	#[inline]
+	pub fn path_start(&self) -> usize {
+		self.as_iri_ref().path_start()
+	}
+
+	/// Returns the byte offset of the start of the query, if any.
+	///
+	/// See [`IriRef::query_start`].
+	#[inline]
+	pub fn query_start(&self) -> Option<usize> {
+		self.as_iri_ref().query_start()
+	}
+
+	/// Returns the byte offset of the start of the fragment, if any.
+	///
+	/// See [`IriRef::fragment_start`].
+	#[inline]
+	pub fn fragment_start(&self) -> Option<usize> {
+		self.as_iri_ref().fragment_start()
+	}
+
+	/// Checks if the scheme is the given one, ignoring the case.
+	///
+	/// See [`IriRef::scheme_eq`].
+	#[inline]
+	pub fn scheme_eq(&self, scheme: &str) -> bool {
+		self.as_iri_ref().scheme_eq(scheme)
+	}
+
+	/// Checks if the scheme is one of the given schemes, ignoring the case.
+	///
+	/// See [`IriRef::scheme_is_one_of`].
+	#[inline]
+	pub fn scheme_is_one_of(&self, schemes: &[&str]) -> bool {
+		self.as_iri_ref().scheme_is_one_of(schemes)
+	}
+
+	/// Checks if the IRI-reference has an authority or an absolute path.
+	///
+	/// See [`IriRef::is_hierarchical`].
+	#[inline]
+	pub fn is_hierarchical(&self) -> bool {
+		self.as_iri_ref().is_hierarchical()
+	}
+
+	/// Checks if the IRI-reference has an authority (even empty).
+	#[inline]
+	pub fn has_authority(&self) -> bool {
+		self.as_iri_ref().has_authority()
+	}
+
+	/// Checks if the IRI-reference has a non-empty path.
+	#[inline]
+	pub fn has_path(&self) -> bool {
+		self.as_iri_ref().has_path()
+	}
+
+	/// Checks if the IRI-reference has a query (even empty).
+	#[inline]
+	pub fn has_query(&self) -> bool {
+		self.as_iri_ref().has_query()
+	}
+
+	/// Checks if the IRI-reference has a fragment (even empty).
+	#[inline]
+	pub fn has_fragment(&self) -> bool {
+		self.as_iri_ref().has_fragment()
+	}
+
+	/// Checks if the IRI-reference has a user info (even empty) in its authority.
+	///
+	/// See [`IriRef::has_userinfo`].
+	#[inline]
+	pub fn has_userinfo(&self) -> bool {
+		self.as_iri_ref().has_userinfo()
+	}
+
+	/// Checks if the user info of the IRI-reference looks like a host name.
+	///
+	/// See [`IriRef::looks_deceptive`] for the heuristic and its limits.
+	#[inline]
+	pub fn looks_deceptive(&self) -> bool {
+		self.as_iri_ref().looks_deceptive()
+	}
+
+	/// Returns the smallest string greater than every string starting with this
+	/// IRI-reference.
+	///
+	/// See [`IriRef::successor`].
+	#[inline]
+	pub fn successor(&self) -> Option<String> {
+		self.as_iri_ref().successor()
+	}
+
 	/// Set the scheme of the IRI.
 	#[inline]
 	pub fn set_scheme(&mut self, scheme: Option<Scheme>) {
@@ -179,7 +551,8 @@ impl IriRefBuf {
 		if let Some(authority) = self.p.authority {
 			let offset = self.p.authority_offset();
 			Some(Authority {
-				data: &self.data[offset..(offset + authority.len())],
+				data: &self.data
+					[parsing::component_range(offset, authority.len(), self.data.len())],
 				p: authority,
 			})
 		} else {
@@ -228,11 +601,102 @@ impl IriRefBuf {
 		}
 	}
 
+	/// Set the host of the IRI-reference, creating the authority if needed.
+	///
+	/// If the IRI-reference has no authority, a `//host` authority is inserted
+	/// before the path. Further calls to [`set_port`](Self::set_port) and
+	/// [`set_userinfo`](Self::set_userinfo) then complete this authority.
+	///
+	/// Returns [`Error::InvalidHost`] if the host is not valid, or [`Error::InvalidPath`]
+	/// if an authority must be created but the path is rootless (such as `a/b`),
+	/// since it would then be merged into the host.
+	/// The IRI-reference is unchanged if an error is returned.
+	///
+	/// # Example
+	///
+	/// ```
+	/// # use iref::IriRefBuf;
+	/// let mut iri_ref = IriRefBuf::new("/path?q").unwrap();
+	/// iri_ref.set_host("example.org").unwrap();
+	/// iri_ref.set_port(Some("8080")).unwrap();
+	/// iri_ref.set_userinfo(Some("user:pw")).unwrap();
+	/// assert_eq!(iri_ref, "//user:pw@example.org:8080/path?q");
+	/// ```
+	pub fn set_host(&mut self, host: &str) -> Result<(), Error> {
+		let host = Host::try_from(host)?;
+		self.authority_mut_or_insert()?.set_host(host);
+		Ok(())
+	}
+
+	/// Set the port of the IRI-reference, creating the authority if needed.
+	///
+	/// If the IRI-reference has no authority and a port is given, an authority
+	/// with an empty host is inserted. Removing the port (with `None`) never
+	/// creates an authority.
+	///
+	/// Returns [`Error::InvalidPort`] if the port is not valid, or [`Error::InvalidPath`]
+	/// if an authority must be created but the path is rootless.
+	/// The IRI-reference is unchanged if an error is returned.
+	pub fn set_port(&mut self, port: Option<&str>) -> Result<(), Error> {
+		match port {
+			Some(port) => {
+				let port = Port::try_from(port)?;
+				self.authority_mut_or_insert()?.set_port(Some(port))
+			}
+			None => {
+				if let Some(mut authority) = self.authority_mut() {
+					authority.set_port(None)
+				}
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Set the user info of the IRI-reference, creating the authority if needed.
+	///
+	/// If the IRI-reference has no authority and a user info is given, an authority
+	/// with an empty host is inserted. Removing the user info (with `None`) never
+	/// creates an authority.
+	///
+	/// Returns [`Error::InvalidUserInfo`] if the user info is not valid, or
+	/// [`Error::InvalidPath`] if an authority must be created but the path is rootless.
+	/// The IRI-reference is unchanged if an error is returned.
+	pub fn set_userinfo(&mut self, userinfo: Option<&str>) -> Result<(), Error> {
+		match userinfo {
+			Some(userinfo) => {
+				let userinfo = UserInfo::try_from(userinfo)?;
+				self.authority_mut_or_insert()?.set_userinfo(Some(userinfo))
+			}
+			None => {
+				if let Some(mut authority) = self.authority_mut() {
+					authority.set_userinfo(None)
+				}
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Returns the authority, inserting an empty one first if there is none.
+	fn authority_mut_or_insert(&mut self) -> Result<AuthorityMut<'_>, Error> {
+		if self.p.authority.is_none() {
+			let path = self.path();
+			if !path.is_empty() && path.is_relative() {
+				return Err(Error::InvalidPath);
+			}
+
+			self.set_authority(Some(Authority::try_from("")?));
+		}
+
+		Ok(self.authority_mut().unwrap())
+	}
+
 	#[inline]
 	pub fn path(&self) -> Path {
 		let offset = self.p.path_offset();
 		Path {
-			data: &self.data[offset..(offset + self.p.path_len)],
+			data: &self.data[parsing::component_range(offset, self.p.path_len, self.data.len())],
 		}
 	}
 
@@ -248,18 +712,190 @@ impl IriRefBuf {
 		self.p.path_len = path.as_ref().len()
 	}
 
+	/// Insert the given prefix at the start of the path.
+	///
+	/// The prefix and the current path are joined with exactly one `/`,
+	/// so that `http://x/a/b` with prefix `/v2` (or `/v2/`) becomes `http://x/v2/a/b`.
+	/// If the IRI-reference has an authority, the resulting path is made absolute.
+	///
+	/// Returns an error if the prefix is not a valid path, or if the resulting path
+	/// would make the IRI-reference ambiguous (e.g. a path starting with `//` without authority).
+	///
+	/// # Example
+	///
+	/// ```
+	/// # use iref::IriRefBuf;
+	/// let mut iri_ref = IriRefBuf::new("http://x/a/b?q").unwrap();
+	/// iri_ref.prepend_path("/v2").unwrap();
+	/// assert_eq!(iri_ref, "http://x/v2/a/b?q");
+	/// ```
+	pub fn prepend_path(&mut self, prefix: &str) -> Result<(), Error> {
+		let prefix = Path::try_from(prefix)?;
+		let prefix = prefix.as_bytes();
+		let path = self.path();
+		let path = path.as_bytes();
+
+		let trimmed_len = if path.is_empty() {
+			prefix.len()
+		} else {
+			prefix
+				.iter()
+				.rposition(|c| *c != b'/')
+				.map(|i| i + 1)
+				.unwrap_or(0)
+		};
+
+		let mut new_path = Vec::with_capacity(prefix.len() + path.len() + 2);
+		if trimmed_len > 0 && self.p.authority.is_some() && prefix[0] != b'/' {
+			new_path.push(b'/');
+		}
+		new_path.extend_from_slice(&prefix[..trimmed_len]);
+
+		if new_path.is_empty() {
+			if prefix.starts_with(b"/") && !path.starts_with(b"/") {
+				new_path.push(b'/');
+			}
+			new_path.extend_from_slice(path);
+		} else if !path.is_empty() {
+			new_path.push(b'/');
+			new_path.extend_from_slice(path.strip_prefix(b"/").unwrap_or(path));
+		}
+
+		if self.p.authority.is_none() {
+			if new_path.starts_with(b"//") {
+				return Err(Error::InvalidPath);
+			}
+
+			if self.p.scheme_len.is_none() {
+				let first_segment = new_path.split(|c| *c == b'/').next().unwrap();
+				if first_segment.contains(&b':') {
+					return Err(Error::InvalidPath);
+				}
+			}
+		}
+
+		self.set_path(Path { data: &new_path });
+		Ok(())
+	}
+
+	/// Truncate the IRI reference to its base, removing the last path segment (the part after
+	/// the right most `/`), the query and the fragment.
+	///
+	/// This is the in-place counterpart of [`IriRef::base`]: the resulting IRI reference is
+	/// the base used to merge relative paths in
+	/// [RFC 3986 section 5.2.3](https://tools.ietf.org/html/rfc3986#section-5.2.3),
+	/// so resolving a relative path reference against it gives the same result as resolving
+	/// it against the original IRI reference.
+	/// The buffer is only truncated, the preceding components are left untouched.
+	///
+	/// # Example
+	///
+	/// ```
+	/// # use iref::IriRefBuf;
+	/// let mut iri_ref = IriRefBuf::new("https://crates.io/crates/iref?query#fragment").unwrap();
+	/// iri_ref.truncate_to_base();
+	/// assert_eq!(iri_ref, "https://crates.io/crates/");
+	/// ```
+	#[inline]
+	pub fn truncate_to_base(&mut self) {
+		self.p.path_len = self.path().directory().len();
+		self.p.query_len = None;
+		self.p.fragment_len = None;
+		let len = self.p.len();
+		self.data.truncate(len)
+	}
+
+	/// Append the given suffix at the end of the path.
+	///
+	/// The current path and the suffix are joined with exactly one `/`,
+	/// so that `http://x/a/b` with suffix `c/d` (or `/c/d`) becomes `http://x/a/b/c/d`.
+	/// The query and fragment, if any, are preserved after the new path.
+	/// Appending an empty suffix leaves the IRI-reference unchanged.
+	///
+	/// This is not the same as resolving the suffix against the IRI-reference:
+	/// resolution would replace the last segment `b` and give `http://x/a/c/d`.
+	///
+	/// Returns an error if the suffix is not a valid path, or if the resulting path
+	/// would make the IRI-reference ambiguous (e.g. a path starting with `//` without authority).
+	///
+	/// # Example
+	///
+	/// ```
+	/// # use iref::IriRefBuf;
+	/// let mut iri_ref = IriRefBuf::new("http://x/a/b?q").unwrap();
+	/// iri_ref.append_path("c/d").unwrap();
+	/// assert_eq!(iri_ref, "http://x/a/b/c/d?q");
+	/// ```
+	pub fn append_path(&mut self, suffix: &str) -> Result<(), Error> {
+		let suffix = Path::try_from(suffix)?;
+		let suffix = suffix.as_bytes();
+
+		if suffix.is_empty() {
+			return Ok(());
+		}
+
+		let path = self.path();
+		let path = path.as_bytes();
+
+		let mut new_path = Vec::with_capacity(path.len() + suffix.len() + 1);
+		if path.is_empty() {
+			if self.p.authority.is_some() && suffix[0] != b'/' {
+				new_path.push(b'/');
+			}
+			new_path.extend_from_slice(suffix);
+		} else {
+			let trimmed_len = path
+				.iter()
+				.rposition(|c| *c != b'/')
+				.map(|i| i + 1)
+				.unwrap_or(0);
+			new_path.extend_from_slice(&path[..trimmed_len]);
+			new_path.push(b'/');
+
+			let start = suffix
+				.iter()
+				.position(|c| *c != b'/')
+				.unwrap_or(suffix.len());
+			new_path.extend_from_slice(&suffix[start..]);
+		}
+
+		if self.p.authority.is_none() {
+			if new_path.starts_with(b"//") {
+				return Err(Error::InvalidPath);
+			}
+
+			if self.p.scheme_len.is_none() {
+				let first_segment = new_path.split(|c| *c == b'/').next().unwrap();
+				if first_segment.contains(&b':') {
+					return Err(Error::InvalidPath);
+				}
+			}
+		}
+
+		self.set_path(Path { data: &new_path });
+		Ok(())
+	}
+
 	#[inline]
 	pub fn query(&self) -> Option<Query> {
 		if let Some(len) = self.p.query_len {
 			let offset = self.p.query_offset();
 			Some(Query {
-				data: &self.data[offset..(offset + len)],
+				data: &self.data[parsing::component_range(offset, len, self.data.len())],
 			})
 		} else {
 			None
 		}
 	}
 
+	/// Get the path and query of the IRI-reference, without the fragment.
+	///
+	/// See [`IriRef::path_and_query`].
+	#[inline]
+	pub fn path_and_query(&self) -> &str {
+		self.as_iri_ref().path_and_query()
+	}
+
 	#[inline]
 	pub fn set_query(&mut self, query: Option<Query>) {
 		let offset = self.p.query_offset();
@@ -282,17 +918,65 @@ impl IriRefBuf {
 		}
 	}
 
+	/// Removes the query from the IRI reference and returns it, if any.
+	///
+	/// The `?` delimiter is removed along with the query.
+	///
+	/// # Example
+	///
+	/// ```
+	/// # use iref::IriRefBuf;
+	/// let mut iri_ref = IriRefBuf::new("/search?q=iri&page=2#results").unwrap();
+	/// let query = iri_ref.take_query().unwrap();
+	///
+	/// assert_eq!(query, "q=iri&page=2");
+	/// assert_eq!(query.params().count(), 2);
+	/// assert_eq!(iri_ref, "/search#results");
+	/// assert!(iri_ref.take_query().is_none());
+	/// ```
+	#[inline]
+	pub fn take_query(&mut self) -> Option<QueryBuf> {
+		let query = self.query().map(Query::to_owned);
+		self.set_query(None);
+		query
+	}
+
 	#[inline]
 	pub fn fragment(&self) -> Option<Fragment> {
 		if let Some(len) = self.p.fragment_len {
 			let offset = self.p.fragment_offset();
 			Some(Fragment {
-				data: &self.data[offset..(offset + len)],
+				data: &self.data[parsing::component_range(offset, len, self.data.len())],
 			})
 		} else {
 			None
 		}
 	}
+
+	/// Get the percent-decoded path of the IRI-reference.
+	///
+	/// See [`IriRef::path_decoded`].
+	#[inline]
+	pub fn path_decoded(&self) -> Cow<'_, str> {
+		self.as_iri_ref().path_decoded()
+	}
+
+	/// Get the percent-decoded query of the IRI-reference, if any.
+	///
+	/// See [`IriRef::query_decoded`].
+	#[inline]
+	pub fn query_decoded(&self) -> Option<Cow<'_, str>> {
+		self.as_iri_ref().query_decoded()
+	}
+
+	/// Get the percent-decoded fragment of the IRI-reference, if any.
+	///
+	/// See [`IriRef::fragment_decoded`].
+	#[inline]
+	pub fn fragment_decoded(&self) -> Option<Cow<'_, str>> {
+		self.as_iri_ref().fragment_decoded()
+	}
+
 	#[inline]
 	pub fn set_fragment(&mut self, fragment: Option<Fragment>) {
 		let offset = self.p.fragment_offset();
@@ -315,37 +999,371 @@ impl IriRefBuf {
 		}
 	}
 
-	/// Resolve the IRI reference.
+	/// Set the query of the IRI reference from a string.
 	///
-	/// ## Abnormal use of dot segments.
+	/// The string must be a valid query, percent-encoded where needed
+	/// (see [`EncodingPolicy::Strict`]).
+	/// Use [`IriRefBuf::set_query_str_with`] to percent-encode it automatically.
 	///
-	/// See <https://www.rfc-editor.org/errata/eid4547>
-	pub fn resolve<'b, Base: Into<Iri<'b>>>(&mut self, base_iri: Base) {
-		let base_iri: Iri<'b> = base_iri.into();
+	/// Returns [`Error::InvalidQuery`] (or [`Error::InvalidPercentEncoding`]) if the query is
+	/// not valid, in which case the IRI reference is left unchanged.
+	#[inline]
+	pub fn set_query_str(&mut self, query: Option<&str>) -> Result<(), Error> {
+		self.set_query_str_with(query, EncodingPolicy::Strict)
+	}
 
-		if self.scheme().is_some() {
-			self.path_mut().normalize();
-		} else {
-			self.set_scheme(Some(base_iri.scheme()));
-			if self.authority().is_some() {
-				self.path_mut().normalize();
-			} else {
-				if self.path().is_relative() && self.path().is_empty() {
-					self.set_path(base_iri.path());
-					if self.query().is_none() {
-						self.set_query(base_iri.query());
-					}
-				} else if self.path().is_absolute() {
-					self.path_mut().normalize();
-				} else {
-					let mut path_buffer = IriBuf::from_scheme(base_iri.scheme()); // we set the scheme to avoid path disambiguation.
-					path_buffer.set_authority(base_iri.authority()); // we set the authority to avoid path disambiguation.
-					if base_iri.authority().is_some() && base_iri.path().is_empty() {
-						path_buffer.set_path("/".try_into().unwrap());
-					} else {
-						path_buffer.set_path(base_iri.path().directory());
-						path_buffer.path_mut().normalize();
+	/// Set the query of the IRI reference from a string, using the given percent-encoding
+	/// policy.
+	///
+	/// # Example
+	///
+	/// ```
+	/// # use iref::{EncodingPolicy, IriRefBuf};
+	/// let mut iri_ref = IriRefBuf::new("http://example.org/search").unwrap();
+	/// iri_ref.set_query_str_with(Some("q=rust iri#1"), EncodingPolicy::Encode).unwrap();
+	/// assert_eq!(iri_ref, "http://example.org/search?q=rust%20iri%231");
+	/// ```
+	pub fn set_query_str_with(
+		&mut self,
+		query: Option<&str>,
+		policy: EncodingPolicy,
+	) -> Result<(), Error> {
+		match query {
+			Some(query) => match policy {
+				EncodingPolicy::Strict => self.set_query(Some(Query::try_from(query)?)),
+				EncodingPolicy::Encode => {
+					let query = encode_component(query, parsing::is_query_char);
+					self.set_query(Some(Query {
+						data: query.as_bytes(),
+					}))
+				}
+			},
+			None => self.set_query(None),
+		}
+
+		Ok(())
+	}
+
+	/// Set the fragment of the IRI reference from a string.
+	///
+	/// The string must be a valid fragment, percent-encoded where needed
+	/// (see [`EncodingPolicy::Strict`]).
+	/// Use [`IriRefBuf::set_fragment_str_with`] to percent-encode it automatically.
+	///
+	/// Returns [`Error::InvalidFragment`] (or [`Error::InvalidPercentEncoding`]) if the
+	/// fragment is not valid, in which case the IRI reference is left unchanged.
+	#[inline]
+	pub fn set_fragment_str(&mut self, fragment: Option<&str>) -> Result<(), Error> {
+		self.set_fragment_str_with(fragment, EncodingPolicy::Strict)
+	}
+
+	/// Set the fragment of the IRI reference from a string, using the given
+	/// percent-encoding policy.
+	///
+	/// See [`EncodingPolicy`].
+	pub fn set_fragment_str_with(
+		&mut self,
+		fragment: Option<&str>,
+		policy: EncodingPolicy,
+	) -> Result<(), Error> {
+		match fragment {
+			Some(fragment) => match policy {
+				EncodingPolicy::Strict => self.set_fragment(Some(Fragment::try_from(fragment)?)),
+				EncodingPolicy::Encode => {
+					let fragment = encode_component(fragment, parsing::is_fragment_char);
+					self.set_fragment(Some(Fragment {
+						data: fragment.as_bytes(),
+					}))
+				}
+			},
+			None => self.set_fragment(None),
+		}
+
+		Ok(())
+	}
+
+	/// Iterate over the components present in the IRI reference.
+	///
+	/// See [`IriRef::components`] for more details.
+	#[inline]
+	pub fn components(&self) -> Components<'_> {
+		self.as_iri_ref().components()
+	}
+
+	/// Iterate over the percent-encoded octets of the IRI reference.
+	///
+	/// See [`IriRef::percent_escapes`] for more details.
+	#[inline]
+	pub fn percent_escapes(&self) -> PercentEscapes<'_> {
+		self.as_iri_ref().percent_escapes()
+	}
+
+	/// Get the canonical form of the host, if the IRI reference has an authority.
+	///
+	/// See [`IriRef::canonical_host`] for more details.
+	#[inline]
+	pub fn canonical_host(&self) -> Option<Cow<'_, str>> {
+		self.as_iri_ref().canonical_host()
+	}
+
+	/// Get all the components of the IRI reference at once.
+	///
+	/// See [`IriRef::decompose`] for more details.
+	#[inline]
+	pub fn decompose(&self) -> Decomposed<'_> {
+		self.as_iri_ref().decompose()
+	}
+
+	/// Compares this IRI reference with another on the components selected by the given mask.
+	///
+	/// See [`IriRef::eq_masked`] for more details.
+	#[inline]
+	pub fn eq_masked<I: AsIriRef>(&self, other: I, mask: ComponentMask) -> bool {
+		self.as_iri_ref().eq_masked(other, mask)
+	}
+
+	/// Compares this IRI reference with another, ignoring their fragments.
+	///
+	/// See [`IriRef::eq_ignoring_fragment`] for more details.
+	#[inline]
+	pub fn eq_ignoring_fragment<I: AsIriRef>(&self, other: I) -> bool {
+		self.as_iri_ref().eq_ignoring_fragment(other)
+	}
+
+	/// Feeds this IRI reference, without its fragment, into the given hasher.
+	///
+	/// See [`IriRef::hash_ignoring_fragment`] for more details.
+	#[inline]
+	pub fn hash_ignoring_fragment<H: Hasher>(&self, hasher: &mut H) {
+		self.as_iri_ref().hash_ignoring_fragment(hasher)
+	}
+
+	/// Normalizes the IRI reference.
+	///
+	/// Performs the syntax-based normalization of
+	/// [RFC 3986 section 6.2.2](https://tools.ietf.org/html/rfc3986#section-6.2.2):
+	/// the scheme and host are lowercased, the percent-encoding is normalized
+	/// (see [`normalize_percent_encoding`](Self::normalize_percent_encoding)) and the dot
+	/// segments are removed from the path.
+	#[inline]
+	pub fn normalize(&mut self) {
+		self.normalize_with(NormalizeOptions::default())
+	}
+
+	/// Removes the dot segments of the path, leaving the other components untouched.
+	///
+	/// This is only the `remove_dot_segments` step of the normalization
+	/// (see [`Normalizer::remove_dot_segments`]): the scheme, authority, query and fragment,
+	/// as well as the percent-encoding and case of the path, are preserved.
+	/// The leading `..` segments of a relative path are kept.
+	///
+	/// # Example
+	///
+	/// ```
+	/// # use iref::IriRefBuf;
+	/// let mut iri_ref = IriRefBuf::new("HTTP://Example.org/a/./b/../%7Ec?x=.#..").unwrap();
+	/// iri_ref.normalize_path();
+	/// assert_eq!(iri_ref, "HTTP://Example.org/a/%7Ec?x=.#..");
+	///
+	/// let mut iri_ref = IriRefBuf::new("./a/../../b").unwrap();
+	/// iri_ref.normalize_path();
+	/// assert_eq!(iri_ref, "../b");
+	/// ```
+	#[inline]
+	pub fn normalize_path(&mut self) {
+		self.path_mut().normalize()
+	}
+
+	/// Normalizes the IRI reference, unless its path has dot segments that cannot be removed.
+	///
+	/// The dot segment removal algorithm of
+	/// [RFC 3986 section 5.2.4](https://tools.ietf.org/html/rfc3986#section-5.2.4) clamps
+	/// `..` segments going above the root: `/a/../../b` is normalized into `/b`.
+	/// This is the expected behavior for IRIs, which are absolute, and
+	/// [`normalize`](Self::normalize) does the same for every IRI reference (keeping the
+	/// leading `..` of relative paths instead, following
+	/// [Errata 4547](https://www.rfc-editor.org/errata/eid4547)).
+	/// However for a reference without scheme, such a `..` segment is meaningful: once the
+	/// reference is resolved it may remove a segment of the base IRI.
+	///
+	/// This method returns [`Error::PathEscapesRoot`] instead of normalizing a reference
+	/// without scheme whose path escapes its root, leaving it unchanged.
+	/// IRI references with a scheme are always normalized.
+	///
+	/// # Example
+	///
+	/// ```
+	/// # use iref::{Error, IriRefBuf};
+	/// let mut iri_ref = IriRefBuf::new("a/../../b").unwrap();
+	/// assert_eq!(iri_ref.try_normalize(), Err(Error::PathEscapesRoot));
+	/// assert_eq!(iri_ref, "a/../../b");
+	///
+	/// let mut iri_ref = IriRefBuf::new("a/./b/../c").unwrap();
+	/// iri_ref.try_normalize().unwrap();
+	/// assert_eq!(iri_ref, "a/c");
+	///
+	/// let mut iri_ref = IriRefBuf::new("http:/a/../../b").unwrap();
+	/// iri_ref.try_normalize().unwrap();
+	/// assert_eq!(iri_ref, "http:/b");
+	/// ```
+	pub fn try_normalize(&mut self) -> Result<(), Error> {
+		if self.p.scheme_len.is_none() && self.path().escapes_root() {
+			return Err(Error::PathEscapesRoot);
+		}
+
+		self.normalize();
+		Ok(())
+	}
+
+	/// Normalizes the IRI reference with the given options.
+	///
+	/// See [`normalize`](Self::normalize) and [`NormalizeOptions`].
+	///
+	/// # Example
+	///
+	/// ```
+	/// # use iref::{IriRefBuf, NormalizeOptions};
+	/// let mut iri_ref = IriRefBuf::new("HTTP://Example.org/a/../b?y=1&x=2").unwrap();
+	/// iri_ref.normalize_with(NormalizeOptions::new().sort_query());
+	/// assert_eq!(iri_ref, "http://example.org/b?x=2&y=1");
+	/// ```
+	pub fn normalize_with(&mut self, options: NormalizeOptions) {
+		let mut normalizer = Normalizer::default();
+		if options.sort_query {
+			normalizer = normalizer.sort_query()
+		}
+
+		normalizer.apply(self)
+	}
+
+	/// Normalizes the percent-encoding of the IRI reference using the
+	/// [`DefaultPctNormalizationPolicy`].
+	///
+	/// Percent-encoded unreserved characters are decoded, and the hexadecimal digits of the
+	/// remaining percent-encoded octets are uppercased.
+	/// See [`PctNormalizationPolicy`] for more details.
+	#[inline]
+	pub fn normalize_percent_encoding(&mut self) {
+		self.normalize_percent_encoding_with(&DefaultPctNormalizationPolicy)
+	}
+
+	/// Normalizes the percent-encoding of the IRI reference, preserving the characters
+	/// declared active by the given policy.
+	pub fn normalize_percent_encoding_with<P: PctNormalizationPolicy + ?Sized>(
+		&mut self,
+		policy: &P,
+	) {
+		let normalized = normalization::normalize_percent_encoding(self.as_iri_ref(), policy);
+		// Decoding unreserved characters does not change the structure of the IRI reference.
+		*self = IriRefBuf::from_string(normalized).unwrap()
+	}
+
+	/// Percent-encodes, in place, every non-ASCII character of the user info, path,
+	/// query and fragment.
+	///
+	/// This is the in-place counterpart of [`Iri::to_uri`], except for the host,
+	/// which is left untouched: non-ASCII hosts are meant to be converted with IDNA
+	/// (see [`Host::canonical`]).
+	/// The result is an URI reference if the host is ASCII.
+	///
+	/// # Example
+	///
+	/// ```
+	/// # use iref::IriRefBuf;
+	/// let mut iri_ref = IriRefBuf::new("//ü@例え.jp/résumé?q=ü#é").unwrap();
+	/// iri_ref.encode_non_ascii();
+	/// assert_eq!(
+	///   iri_ref.as_str(),
+	///   "//%C3%BC@例え.jp/r%C3%A9sum%C3%A9?q=%C3%BC#%C3%A9"
+	/// );
+	/// assert_eq!(iri_ref.path(), "/r%C3%A9sum%C3%A9");
+	/// ```
+	pub fn encode_non_ascii(&mut self) {
+		if self.as_bytes().is_ascii() {
+			return;
+		}
+
+		// Components are encoded from the end so that the offsets of the
+		// remaining ones stay valid.
+		if let Some(len) = self.p.fragment_len {
+			let offset = self.p.fragment_offset();
+			self.p.fragment_len = Some(self.encode_non_ascii_in(offset..(offset + len)));
+		}
+
+		if let Some(len) = self.p.query_len {
+			let offset = self.p.query_offset();
+			self.p.query_len = Some(self.encode_non_ascii_in(offset..(offset + len)));
+		}
+
+		let offset = self.p.path_offset();
+		self.p.path_len = self.encode_non_ascii_in(offset..(offset + self.p.path_len));
+
+		if let Some(authority) = self.p.authority {
+			if let Some(len) = authority.userinfo_len {
+				let offset = self.p.authority_offset();
+				let new_len = self.encode_non_ascii_in(offset..(offset + len));
+				self.p.authority.as_mut().unwrap().userinfo_len = Some(new_len);
+			}
+		}
+	}
+
+	/// Percent-encodes every non-ASCII character in the given range of the buffer,
+	/// and returns the new length of the range.
+	fn encode_non_ascii_in(&mut self, range: Range<usize>) -> usize {
+		let input = unsafe { std::str::from_utf8_unchecked(&self.data[range.clone()]) };
+		if input.is_ascii() {
+			return range.len();
+		}
+
+		let mut encoded = String::with_capacity(input.len() * 3);
+		crate::encode_non_ascii(input, &mut encoded);
+		self.replace(range, encoded.as_bytes());
+		encoded.len()
+	}
+
+	/// Resolve the IRI reference.
+	///
+	/// Returns `true` if the reference was relative, meaning that its scheme has been taken from
+	/// the base IRI, or `false` if it was already an IRI, in which case only its path is
+	/// normalized.
+	///
+	/// ## Abnormal use of dot segments.
+	///
+	/// See <https://www.rfc-editor.org/errata/eid4547>
+	pub fn resolve<'b, Base: Into<Iri<'b>>>(&mut self, base_iri: Base) -> bool {
+		self.resolve_with(base_iri.into(), &mut None)
+	}
+
+	/// Resolve the IRI reference against the given base IRI, reusing the given
+	/// base directory if any.
+	///
+	/// The base directory is the base IRI with only its scheme, authority and normalized
+	/// directory path. It is computed and stored in `base_directory` the first time it is
+	/// needed, so it can be shared across resolutions against the same base IRI.
+	pub(crate) fn resolve_with(
+		&mut self,
+		base_iri: Iri,
+		base_directory: &mut Option<IriBuf>,
+	) -> bool {
+		if self.scheme().is_some() {
+			self.path_mut().normalize();
+			false
+		} else {
+			self.set_scheme(Some(base_iri.scheme()));
+			if self.authority().is_some() {
+				self.path_mut().normalize();
+			} else {
+				if self.path().is_relative() && self.path().is_empty() {
+					self.set_path(base_iri.path());
+					if self.query().is_none() {
+						self.set_query(base_iri.query());
 					}
+				} else if self.path().is_absolute() {
+					self.path_mut().normalize();
+				} else {
+					let mut path_buffer = base_directory
+						.get_or_insert_with(|| base_directory_of(base_iri))
+						.clone();
 					path_buffer.path_mut().symbolic_append(self.path());
 					if self.path().is_open() {
 						path_buffer.path_mut().open();
@@ -354,6 +1372,8 @@ impl IriRefBuf {
 				}
 				self.set_authority(base_iri.authority());
 			}
+
+			true
 		}
 	}
 
@@ -361,6 +1381,43 @@ impl IriRefBuf {
 	pub fn resolved<'b, Base: Into<Iri<'b>>>(&self, base_iri: Base) -> IriBuf {
 		self.as_iri_ref().resolved(base_iri)
 	}
+
+	/// Resolve the IRI reference against the given base IRI string.
+	///
+	/// See [`IriRef::resolved_str`] for more details.
+	#[inline]
+	pub fn resolved_str(&self, base_iri: &str) -> Result<IriBuf, Error> {
+		self.as_iri_ref().resolved_str(base_iri)
+	}
+
+	/// Resolve the IRI reference against the given base, which may itself be an
+	/// IRI-reference.
+	///
+	/// See [`IriRef::try_resolved`] for more details.
+	#[inline]
+	pub fn try_resolved<'b, Base: Into<IriRef<'b>>>(&self, base: Base) -> Result<IriBuf, Error> {
+		self.as_iri_ref().try_resolved(base)
+	}
+
+	/// Consumes and resolves the IRI reference against the given base IRI.
+	///
+	/// The result always has a scheme (taken from the base IRI if the reference is relative),
+	/// hence it is returned as an [`IriBuf`].
+	/// The buffer is reused, contrarily to [`IriRefBuf::resolved`].
+	///
+	/// # Example
+	///
+	/// ```
+	/// # use iref::{Iri, IriRefBuf};
+	/// let base = Iri::new("http://example.org/a/b").unwrap();
+	/// let iri = IriRefBuf::new("../c?q").unwrap().make_absolute(base);
+	/// assert_eq!(iri, "http://example.org/c?q");
+	/// ```
+	#[inline]
+	pub fn make_absolute<'b, Base: Into<Iri<'b>>>(mut self, base_iri: Base) -> IriBuf {
+		self.resolve(base_iri);
+		IriBuf(self)
+	}
 }
 
 impl AsRef<[u8]> for IriRefBuf {
@@ -370,6 +1427,13 @@ impl AsRef<[u8]> for IriRefBuf {
 	}
 }
 
+impl AsRef<str> for IriRefBuf {
+	#[inline]
+	fn as_ref(&self) -> &str {
+		self.as_str()
+	}
+}
+
 impl FromStr for IriRefBuf {
 	type Err = Error;
 
@@ -378,6 +1442,55 @@ impl FromStr for IriRefBuf {
 	}
 }
 
+/// Base IRI with only its scheme, authority and normalized directory path,
+/// onto which relative paths are appended during resolution.
+fn base_directory_of(base_iri: Iri) -> IriBuf {
+	let mut path_buffer = IriBuf::from_scheme(base_iri.scheme()); // we set the scheme to avoid path disambiguation.
+	path_buffer.set_authority(base_iri.authority()); // we set the authority to avoid path disambiguation.
+	if base_iri.authority().is_some() && base_iri.path().is_empty() {
+		path_buffer.set_path("/".try_into().unwrap());
+	} else {
+		path_buffer.set_path(base_iri.path().directory());
+		path_buffer.path_mut().normalize();
+	}
+
+	path_buffer
+}
+
+/// Append a path segment.
+///
+/// `iri_ref += segment` is the same as `iri_ref.path_mut().push(segment)`:
+/// it adds `segment` as a new segment at the end of the path, after a `/` separator.
+/// This is not a raw string concatenation, and the query and fragment are left untouched.
+/// If `segment` ends with a `/`, the path is left open.
+///
+/// # Example
+///
+/// ```
+/// # use iref::IriRefBuf;
+/// let mut iri_ref = IriRefBuf::new("http://example.org/foo?q").unwrap();
+/// iri_ref += "bar/";
+/// iri_ref += "baz";
+/// assert_eq!(iri_ref, "http://example.org/foo/bar/baz?q");
+/// ```
+///
+/// # Panics
+///
+/// Panics if `segment` is not a valid path segment (for instance if it contains a `/` that
+/// is not at the end, or a `?` or `#`).
+impl<'a> AddAssign<&'a str> for IriRefBuf {
+	#[inline]
+	fn add_assign(&mut self, segment: &'a str) {
+		let segment = Segment::try_from(segment).expect("invalid path segment");
+
+		if self.p.authority.is_some() && self.p.path_len == 0 {
+			self.set_path(Path { data: b"/" })
+		}
+
+		self.path_mut().push(segment)
+	}
+}
+
 impl AsIriRef for IriRefBuf {
 	#[inline]
 	fn as_iri_ref(&self) -> IriRef {
@@ -443,6 +1556,76 @@ impl<'a> PartialEq<&'a str> for IriRefBuf {
 	}
 }
 
+impl PartialEq<[u8]> for IriRefBuf {
+	#[inline]
+	fn eq(&self, other: &[u8]) -> bool {
+		self.as_iri_ref() == *other
+	}
+}
+
+impl<'a> PartialEq<&'a [u8]> for IriRefBuf {
+	#[inline]
+	fn eq(&self, other: &&'a [u8]) -> bool {
+		self.as_iri_ref() == *other
+	}
+}
+
+impl<'a, 'b> PartialEq<IriRef<'b>> for Cow<'a, IriRefBuf> {
+	#[inline]
+	fn eq(&self, other: &IriRef<'b>) -> bool {
+		self.as_iri_ref() == *other
+	}
+}
+
+impl<'a, 'b> PartialEq<Iri<'b>> for Cow<'a, IriRefBuf> {
+	#[inline]
+	fn eq(&self, other: &Iri<'b>) -> bool {
+		self.as_iri_ref() == other.as_iri_ref()
+	}
+}
+
+impl<'a> PartialEq<IriRefBuf> for Cow<'a, IriRefBuf> {
+	#[inline]
+	fn eq(&self, other: &IriRefBuf) -> bool {
+		self.as_iri_ref() == other.as_iri_ref()
+	}
+}
+
+impl<'a> PartialEq<IriBuf> for Cow<'a, IriRefBuf> {
+	#[inline]
+	fn eq(&self, other: &IriBuf) -> bool {
+		self.as_iri_ref() == other.as_iri_ref()
+	}
+}
+
+impl<'a, 'b> PartialEq<Cow<'a, IriRefBuf>> for IriRef<'b> {
+	#[inline]
+	fn eq(&self, other: &Cow<'a, IriRefBuf>) -> bool {
+		*self == other.as_iri_ref()
+	}
+}
+
+impl<'a, 'b> PartialEq<Cow<'a, IriRefBuf>> for Iri<'b> {
+	#[inline]
+	fn eq(&self, other: &Cow<'a, IriRefBuf>) -> bool {
+		self.as_iri_ref() == other.as_iri_ref()
+	}
+}
+
+impl<'a> PartialEq<Cow<'a, IriRefBuf>> for IriRefBuf {
+	#[inline]
+	fn eq(&self, other: &Cow<'a, IriRefBuf>) -> bool {
+		self.as_iri_ref() == other.as_iri_ref()
+	}
+}
+
+impl<'a> PartialEq<Cow<'a, IriRefBuf>> for IriBuf {
+	#[inline]
+	fn eq(&self, other: &Cow<'a, IriRefBuf>) -> bool {
+		self.as_iri_ref() == other.as_iri_ref()
+	}
+}
+
 impl PartialOrd for IriRefBuf {
 	#[inline]
 	fn partial_cmp(&self, other: &IriRefBuf) -> Option<Ordering> {
@@ -494,14 +1677,34 @@ impl TryFrom<String> for IriRefBuf {
 	}
 }
 
+impl<'a> TryFrom<&'a str> for IriRefBuf {
+	type Error = Error;
+
+	#[inline]
+	fn try_from(s: &'a str) -> Result<Self, Error> {
+		Self::new(s)
+	}
+}
+
+impl From<IriRefBuf> for Vec<u8> {
+	#[inline]
+	fn from(iri_ref: IriRefBuf) -> Vec<u8> {
+		iri_ref.into_bytes()
+	}
+}
+
+impl From<IriRefBuf> for String {
+	#[inline]
+	fn from(iri_ref: IriRefBuf) -> String {
+		unsafe { String::from_utf8_unchecked(iri_ref.into_bytes()) }
+	}
+}
+
 impl<'a> From<IriRef<'a>> for IriRefBuf {
 	#[inline]
 	fn from(iri_ref: IriRef<'a>) -> IriRefBuf {
-		let mut data = Vec::new();
-		data.resize(iri_ref.as_ref().len(), 0);
-		data.copy_from_slice(iri_ref.as_ref());
-
-		IriRefBuf { p: iri_ref.p, data }
+		// the parsing data is reused as is, no need to parse the input again.
+		iri_ref.to_owned()
 	}
 }
 
@@ -533,6 +1736,20 @@ impl From<IriBuf> for IriRefBuf {
 	}
 }
 
+impl<'a> From<&'a IriBuf> for IriRefBuf {
+	#[inline]
+	fn from(iri: &'a IriBuf) -> IriRefBuf {
+		iri.0.clone()
+	}
+}
+
+impl<'a> From<&'a IriRefBuf> for IriRefBuf {
+	#[inline]
+	fn from(iri_ref: &'a IriRefBuf) -> IriRefBuf {
+		iri_ref.clone()
+	}
+}
+
 impl<'a> From<Path<'a>> for IriRefBuf {
 	#[inline]
 	fn from(path: Path<'a>) -> IriRefBuf {
@@ -556,7 +1773,7 @@ impl Hash for IriRefBuf {
 
 #[cfg(test)]
 mod tests {
-	use crate::{Iri, IriRef, IriRefBuf};
+	use crate::{Error, Iri, IriBuf, IriRef, IriRefBuf};
 
 	#[test]
 	fn disambiguate1() {
@@ -572,6 +1789,249 @@ mod tests {
 		assert_eq!(iri_ref.as_str(), "/.//path")
 	}
 
+	#[test]
+	fn resolve_reports_relative() {
+		let base_iri = Iri::new("http://a/b/c/d;p?q").unwrap();
+
+		let mut relative = IriRefBuf::new("../g").unwrap();
+		assert!(relative.resolve(base_iri));
+		assert_eq!(relative, "http://a/b/g");
+
+		let mut absolute = IriRefBuf::new("g:h/./i").unwrap();
+		assert!(!absolute.resolve(base_iri));
+		assert_eq!(absolute, "g:h/i");
+	}
+
+	#[test]
+	fn prepend_path() {
+		let challenges = [
+			("http://x/a/b", "/v2", "http://x/v2/a/b"),
+			("http://x/a/b", "/v2/", "http://x/v2/a/b"),
+			("http://x/a/b", "v2", "http://x/v2/a/b"),
+			("http://x/a/", "/v2", "http://x/v2/a/"),
+			("http://x/", "/v2", "http://x/v2/"),
+			("http://x", "/v2", "http://x/v2"),
+			("http://x/a", "/", "http://x/a"),
+			("a/b", "v2", "v2/a/b"),
+			("a/b", "/v2", "/v2/a/b"),
+			("/a/b", "", "/a/b"),
+			("foo:a", "/v2", "foo:/v2/a"),
+		];
+
+		for (iri_ref, prefix, expected) in &challenges {
+			let mut iri_ref = IriRefBuf::new(iri_ref).unwrap();
+			iri_ref.prepend_path(prefix).unwrap();
+			assert_eq!(iri_ref.as_str(), *expected)
+		}
+
+		let mut iri_ref = IriRefBuf::new("a/b").unwrap();
+		assert_eq!(iri_ref.prepend_path("//v2"), Err(Error::InvalidPath));
+		assert_eq!(iri_ref.prepend_path("v:2"), Err(Error::InvalidPath));
+		assert_eq!(iri_ref.prepend_path("v?2"), Err(Error::InvalidPath));
+		assert_eq!(iri_ref.as_str(), "a/b")
+	}
+
+	#[test]
+	fn encode_non_ascii() {
+		let inputs = [
+			"http://ü:é@example.org:80/résumé/ü?q=ü&r=€#é",
+			"http://example.org/already%20ascii?q",
+			"urn:例え:ü",
+			"résumé/ü#é",
+			"//é@host",
+			"",
+		];
+
+		for input in inputs.iter() {
+			let mut iri_ref = IriRefBuf::new(input).unwrap();
+			iri_ref.encode_non_ascii();
+
+			assert!(iri_ref.as_str().is_ascii());
+			let reparsed = IriRefBuf::new(iri_ref.as_str()).unwrap();
+			assert_eq!(iri_ref.p, reparsed.p);
+
+			if let Ok(iri) = Iri::new(input) {
+				assert_eq!(iri_ref.as_str(), iri.to_uri().as_str())
+			}
+		}
+
+		let mut iri = IriBuf::new("http://例え.jp/ü").unwrap();
+		iri.encode_non_ascii();
+		assert_eq!(iri, "http://例え.jp/%C3%BC");
+	}
+
+	#[test]
+	fn structural_predicates() {
+		let challenges = [
+			// (input, hierarchical, authority, path, query, fragment)
+			("http://a/b?c#d", true, true, true, true, true),
+			("http://a", true, true, false, false, false),
+			("//", true, true, false, false, false),
+			("file:/etc", true, false, true, false, false),
+			("mailto:a@b?", false, false, true, true, false),
+			("urn:", false, false, false, false, false),
+			("a/b#", false, false, true, false, true),
+			("", false, false, false, false, false),
+		];
+
+		for (input, hierarchical, authority, path, query, fragment) in &challenges {
+			let iri_ref = IriRefBuf::new(input).unwrap();
+			assert_eq!(iri_ref.is_hierarchical(), *hierarchical, "{}", input);
+			assert_eq!(iri_ref.has_authority(), *authority, "{}", input);
+			assert_eq!(iri_ref.has_path(), *path, "{}", input);
+			assert_eq!(iri_ref.has_query(), *query, "{}", input);
+			assert_eq!(iri_ref.has_fragment(), *fragment, "{}", input);
+		}
+	}
+
+	#[test]
+	fn truncate_to_base() {
+		let challenges = [
+			("http://a/b/c/d;p?q#f", "http://a/b/c/"),
+			("http://a/b/c/", "http://a/b/c/"),
+			("http://a", "http://a"),
+			("http://a?q", "http://a"),
+			("foo:a:b/c", "foo:a:b/"),
+			("foo:abc#f", "foo:"),
+			("../a/./b", "../a/./"),
+			("", ""),
+		];
+
+		let references = [
+			"g", "./g", "g/", "../g", "../../g", ".", "", "?y", "#s", "g;x",
+		];
+
+		for (input, expected) in &challenges {
+			let original = IriRefBuf::new(input).unwrap();
+			let mut base = original.clone();
+			base.truncate_to_base();
+			assert_eq!(base.as_str(), *expected);
+			let original_ref = original.as_iri_ref();
+			assert_eq!(base.as_bytes(), original_ref.base().as_bytes());
+
+			if let Ok(original) = original.as_iri_ref().into_iri() {
+				let base = base.as_iri_ref().into_iri().unwrap();
+				for r in &references {
+					let r = IriRef::new(r).unwrap();
+					if !r.path().is_empty() {
+						assert_eq!(r.resolved(base), r.resolved(original), "{} {}", input, r);
+					}
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn append_path() {
+		let challenges = [
+			("http://x/a/b", "c/d", "http://x/a/b/c/d"),
+			("http://x/a/b", "/c/d", "http://x/a/b/c/d"),
+			("http://x/a/b/", "c", "http://x/a/b/c"),
+			("http://x/a/b", "c/", "http://x/a/b/c/"),
+			("http://x/a/b", "/", "http://x/a/b/"),
+			("http://x/a/b", "", "http://x/a/b"),
+			("http://x/", "c", "http://x/c"),
+			("http://x", "c", "http://x/c"),
+			("http://x/a?q#f", "c", "http://x/a/c?q#f"),
+			("a", "b", "a/b"),
+			("", "b", "b"),
+			("", "/b", "/b"),
+			("foo:", "b:c", "foo:b:c"),
+		];
+
+		for (iri_ref, suffix, expected) in &challenges {
+			let mut iri_ref = IriRefBuf::new(iri_ref).unwrap();
+			iri_ref.append_path(suffix).unwrap();
+			assert_eq!(iri_ref.as_str(), *expected)
+		}
+
+		let mut iri_ref = IriRefBuf::new("").unwrap();
+		assert_eq!(iri_ref.append_path("//b"), Err(Error::InvalidPath));
+		assert_eq!(iri_ref.append_path("b:c"), Err(Error::InvalidPath));
+		assert_eq!(iri_ref.append_path("b?c"), Err(Error::InvalidPath));
+		assert_eq!(iri_ref.as_str(), "")
+	}
+
+	#[test]
+	fn check_raw_parts() {
+		let (buffer, p) = IriRefBuf::new("http://a/b?c#d").unwrap().into_raw_parts();
+		assert!(IriRefBuf::try_from_raw_parts(buffer.clone(), p).is_ok());
+
+		let mut wrong_p = p;
+		wrong_p.path_len -= 1;
+		wrong_p.query_len = Some(2);
+		assert_eq!(
+			IriRefBuf::try_from_raw_parts(buffer.clone(), wrong_p).err(),
+			Some((Error::InvalidParsingData, buffer.clone()))
+		);
+
+		let (other_buffer, _) = IriRefBuf::new("http://a/b?c#e").unwrap().into_raw_parts();
+		assert!(IriRefBuf::try_from_raw_parts(other_buffer, p).is_ok());
+		assert_eq!(
+			IriRefBuf::try_from_raw_parts(b"http://a/b#c?d".to_vec(), p).err(),
+			Some((Error::InvalidParsingData, b"http://a/b#c?d".to_vec()))
+		);
+		assert_eq!(
+			IriRef::try_from_raw(b"http://a/b?c", p).err(),
+			Some(Error::InvalidParsingData)
+		);
+	}
+
+	#[test]
+	fn add_assign_segment() {
+		let mut iri_ref = IriRefBuf::new("http://example.org").unwrap();
+		iri_ref += "a";
+		assert_eq!(iri_ref.as_str(), "http://example.org/a");
+		iri_ref += "b/";
+		assert_eq!(iri_ref.as_str(), "http://example.org/a/b/");
+		iri_ref += "c";
+		assert_eq!(iri_ref.as_str(), "http://example.org/a/b/c");
+
+		let mut iri_ref = IriRefBuf::new("").unwrap();
+		iri_ref += "a:b";
+		assert_eq!(iri_ref.as_str(), "./a:b");
+
+		let mut iri = IriBuf::new("http://example.org/a/#f").unwrap();
+		iri += "b";
+		assert_eq!(iri.as_str(), "http://example.org/a/b#f");
+	}
+
+	#[test]
+	#[should_panic]
+	fn add_assign_invalid_segment() {
+		let mut iri_ref = IriRefBuf::new("a").unwrap();
+		iri_ref += "b/c";
+	}
+
+	#[test]
+	fn push_str_revalidate() {
+		let mut iri_ref = IriRefBuf::new("foo").unwrap();
+		iri_ref.push_str("/bar");
+		iri_ref.push_str("#baz");
+		assert_eq!(iri_ref.path(), "foo");
+		iri_ref.revalidate().unwrap();
+		assert_eq!(iri_ref.path(), "foo/bar");
+		assert_eq!(iri_ref.fragment().unwrap(), "baz");
+
+		iri_ref.push_str("#");
+		assert_eq!(iri_ref.revalidate(), Err(Error::InvalidFragment));
+		assert_eq!(iri_ref.as_bytes(), b"foo/bar#baz#")
+	}
+
+	#[test]
+	fn recompose_owned_components() {
+		let iri_ref = IriRefBuf::new("foo?bar#baz").unwrap();
+		let query = iri_ref.query().unwrap().to_owned();
+		let fragment = iri_ref.fragment().unwrap().to_owned();
+
+		let mut other = IriRefBuf::new("qux").unwrap();
+		other.set_query(Some(query.as_query()));
+		other.set_fragment(Some((&fragment).into()));
+
+		assert_eq!(query, "bar");
+		assert_eq!(other.as_str(), "qux?bar#baz");
+	}
+
 	#[test]
 	fn unambiguous_resolution() {
 		let base_iri = Iri::new("http:/a/b").unwrap();
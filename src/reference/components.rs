@@ -0,0 +1,357 @@
+use std::{fmt, ops};
+
+use crate::parsing::{self, ParsedIriRef};
+use crate::{Authority, Fragment, Path, Query, Scheme};
+
+/// IRI-reference component kind.
+///
+/// Identifies each top-level component of an IRI-reference,
+/// in the order in which they appear.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum ComponentKind {
+	Scheme,
+	Authority,
+	Path,
+	Query,
+	Fragment,
+}
+
+impl ComponentKind {
+	/// Name of the component, as used in RFC 3986.
+	#[inline]
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			ComponentKind::Scheme => "scheme",
+			ComponentKind::Authority => "authority",
+			ComponentKind::Path => "path",
+			ComponentKind::Query => "query",
+			ComponentKind::Fragment => "fragment",
+		}
+	}
+
+	/// Component following this one, if any.
+	#[inline]
+	fn next(&self) -> Option<ComponentKind> {
+		match self {
+			ComponentKind::Scheme => Some(ComponentKind::Authority),
+			ComponentKind::Authority => Some(ComponentKind::Path),
+			ComponentKind::Path => Some(ComponentKind::Query),
+			ComponentKind::Query => Some(ComponentKind::Fragment),
+			ComponentKind::Fragment => None,
+		}
+	}
+}
+
+impl fmt::Display for ComponentKind {
+	#[inline]
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		self.as_str().fmt(f)
+	}
+}
+
+/// Set of IRI-reference components.
+///
+/// Selects the components compared by [`IriRef::eq_masked`](crate::IriRef::eq_masked).
+/// Masks are combined with the `|` operator, intersected with `&` and complemented with `!`.
+///
+/// # Example
+///
+/// ```
+/// # use iref::{ComponentKind, ComponentMask};
+/// let mask = ComponentMask::SCHEME | ComponentMask::AUTHORITY;
+/// assert!(mask.contains(ComponentKind::Authority));
+/// assert!(!mask.contains(ComponentKind::Path));
+/// assert_eq!(!ComponentMask::FRAGMENT, ComponentMask::ALL.without(ComponentKind::Fragment));
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default, Debug)]
+pub struct ComponentMask(u8);
+
+impl ComponentMask {
+	/// Empty mask.
+	pub const NONE: ComponentMask = ComponentMask(0);
+	pub const SCHEME: ComponentMask = ComponentMask(1);
+	pub const AUTHORITY: ComponentMask = ComponentMask(1 << 1);
+	pub const PATH: ComponentMask = ComponentMask(1 << 2);
+	pub const QUERY: ComponentMask = ComponentMask(1 << 3);
+	pub const FRAGMENT: ComponentMask = ComponentMask(1 << 4);
+	/// Mask of every component.
+	pub const ALL: ComponentMask = ComponentMask(0b11111);
+
+	/// Checks if the mask includes the given component.
+	#[inline]
+	pub fn contains(&self, kind: ComponentKind) -> bool {
+		self.0 & ComponentMask::from(kind).0 != 0
+	}
+
+	/// Checks if the mask is empty.
+	#[inline]
+	pub fn is_empty(&self) -> bool {
+		self.0 == 0
+	}
+
+	/// Returns this mask with the given component.
+	#[inline]
+	pub fn with(self, kind: ComponentKind) -> ComponentMask {
+		self | kind.into()
+	}
+
+	/// Returns this mask without the given component.
+	#[inline]
+	pub fn without(self, kind: ComponentKind) -> ComponentMask {
+		self & !ComponentMask::from(kind)
+	}
+}
+
+impl From<ComponentKind> for ComponentMask {
+	#[inline]
+	fn from(kind: ComponentKind) -> ComponentMask {
+		match kind {
+			ComponentKind::Scheme => ComponentMask::SCHEME,
+			ComponentKind::Authority => ComponentMask::AUTHORITY,
+			ComponentKind::Path => ComponentMask::PATH,
+			ComponentKind::Query => ComponentMask::QUERY,
+			ComponentKind::Fragment => ComponentMask::FRAGMENT,
+		}
+	}
+}
+
+impl ops::BitOr for ComponentMask {
+	type Output = ComponentMask;
+
+	#[inline]
+	fn bitor(self, other: ComponentMask) -> ComponentMask {
+		ComponentMask(self.0 | other.0)
+	}
+}
+
+impl ops::BitAnd for ComponentMask {
+	type Output = ComponentMask;
+
+	#[inline]
+	fn bitand(self, other: ComponentMask) -> ComponentMask {
+		ComponentMask(self.0 & other.0)
+	}
+}
+
+impl ops::Not for ComponentMask {
+	type Output = ComponentMask;
+
+	#[inline]
+	fn not(self) -> ComponentMask {
+		ComponentMask(!self.0 & ComponentMask::ALL.0)
+	}
+}
+
+/// Iterator over the components of an IRI-reference.
+///
+/// Produced by the [`IriRef::components`](crate::IriRef::components) method.
+#[derive(Clone)]
+pub struct Components<'a> {
+	data: &'a [u8],
+	p: ParsedIriRef,
+	next: Option<ComponentKind>,
+}
+
+impl<'a> Components<'a> {
+	#[inline]
+	pub(crate) fn new(data: &'a [u8], p: ParsedIriRef) -> Components<'a> {
+		Components {
+			data,
+			p,
+			next: Some(ComponentKind::Scheme),
+		}
+	}
+
+	#[inline]
+	fn slice(&self, offset: usize, len: usize) -> &'a str {
+		unsafe { std::str::from_utf8_unchecked(&self.data[offset..(offset + len)]) }
+	}
+
+	/// Returns the given component, if present.
+	#[inline]
+	fn get(&self, kind: ComponentKind) -> Option<&'a str> {
+		match kind {
+			ComponentKind::Scheme => self.p.scheme_len.map(|len| self.slice(0, len)),
+			ComponentKind::Authority => self
+				.p
+				.authority
+				.map(|authority| self.slice(self.p.authority_offset(), authority.len())),
+			ComponentKind::Path => Some(self.slice(self.p.path_offset(), self.p.path_len)),
+			ComponentKind::Query => self
+				.p
+				.query_len
+				.map(|len| self.slice(self.p.query_offset(), len)),
+			ComponentKind::Fragment => self
+				.p
+				.fragment_len
+				.map(|len| self.slice(self.p.fragment_offset(), len)),
+		}
+	}
+}
+
+impl<'a> Iterator for Components<'a> {
+	type Item = (ComponentKind, &'a str);
+
+	#[inline]
+	fn next(&mut self) -> Option<(ComponentKind, &'a str)> {
+		while let Some(kind) = self.next {
+			self.next = kind.next();
+			if let Some(value) = self.get(kind) {
+				return Some((kind, value));
+			}
+		}
+
+		None
+	}
+}
+
+impl<'a> std::iter::FusedIterator for Components<'a> {}
+
+/// All the components of an IRI-reference.
+///
+/// Produced by the [`IriRef::decompose`](crate::IriRef::decompose) method, which computes
+/// every component in a single pass over the parsing data.
+/// Absent components are `None`, except for the path which is always defined.
+#[derive(PartialEq, Debug)]
+pub struct Decomposed<'a> {
+	pub scheme: Option<Scheme<'a>>,
+	pub authority: Option<Authority<'a>>,
+	pub path: Path<'a>,
+	pub query: Option<Query<'a>>,
+	pub fragment: Option<Fragment<'a>>,
+}
+
+impl<'a> Decomposed<'a> {
+	#[inline]
+	pub(crate) fn new(data: &'a [u8], p: &ParsedIriRef) -> Decomposed<'a> {
+		let slice =
+			|offset: usize, len: usize| &data[parsing::component_range(offset, len, data.len())];
+
+		let mut offset = 0;
+
+		let scheme = p.scheme_len.map(|len| {
+			offset = len.saturating_add(1);
+			Scheme {
+				data: slice(0, len),
+			}
+		});
+
+		let authority = p.authority.map(|authority| {
+			let start = offset.saturating_add(2);
+			offset = start.saturating_add(authority.len());
+			Authority {
+				data: slice(start, authority.len()),
+				p: authority,
+			}
+		});
+
+		let path = Path {
+			data: slice(offset, p.path_len),
+		};
+		offset = offset.saturating_add(p.path_len);
+
+		let query = p.query_len.map(|len| {
+			let start = offset.saturating_add(1);
+			offset = start.saturating_add(len);
+			Query {
+				data: slice(start, len),
+			}
+		});
+
+		let fragment = p.fragment_len.map(|len| Fragment {
+			data: slice(offset.saturating_add(1), len),
+		});
+
+		Decomposed {
+			scheme,
+			authority,
+			path,
+			query,
+			fragment,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{ComponentKind, ComponentMask, Fragment, IriRef, IriRefBuf, Query};
+
+	#[test]
+	fn eq_masked() {
+		let a = IriRef::new("http://u@example.org/a/./b?q=%41#f").unwrap();
+		let b = IriRefBuf::new("foo://u@example.org/a/b?q=A").unwrap();
+
+		assert!(a.eq_masked(
+			&b,
+			ComponentMask::AUTHORITY | ComponentMask::PATH | ComponentMask::QUERY
+		));
+		assert!(!a.eq_masked(&b, ComponentMask::SCHEME));
+		assert!(!a.eq_masked(&b, ComponentMask::FRAGMENT));
+		assert!(!a.eq_masked(&b, ComponentMask::ALL));
+		assert!(a.eq_masked(
+			&b,
+			ComponentMask::ALL
+				.without(ComponentKind::Scheme)
+				.without(ComponentKind::Fragment)
+		));
+		assert!(b.eq_masked(a, ComponentMask::NONE));
+		assert!(a.eq_masked(a, ComponentMask::ALL));
+
+		let c = IriRef::new("//example.org/a/b").unwrap();
+		assert!(!c.eq_masked(&b, ComponentMask::AUTHORITY));
+		assert!(!c.eq_masked(&b, ComponentMask::QUERY));
+		assert!(c.eq_masked(&b, ComponentMask::PATH));
+	}
+
+	#[test]
+	fn component_mask() {
+		let mut mask = ComponentMask::NONE;
+		assert!(mask.is_empty());
+
+		for kind in IriRef::new("a://b/c?d#e")
+			.unwrap()
+			.components()
+			.map(|(kind, _)| kind)
+		{
+			assert!(!mask.contains(kind));
+			mask = mask.with(kind);
+			assert!(mask.contains(kind));
+		}
+
+		assert_eq!(mask, ComponentMask::ALL);
+		assert_eq!(!mask, ComponentMask::NONE);
+		assert_eq!(mask & ComponentMask::PATH, ComponentMask::PATH);
+		assert_eq!(ComponentMask::default(), ComponentMask::NONE);
+	}
+
+	#[test]
+	fn decompose() {
+		let inputs = [
+			"",
+			"a",
+			"foo:",
+			"//",
+			"?#",
+			"foo://user@host:8080/a/b?q=1#frag",
+			"foo:a/b#f",
+			"//host?q",
+			"../a?#",
+		];
+
+		for input in inputs.iter() {
+			let iri_ref = IriRef::new(input).unwrap();
+			let parts = iri_ref.decompose();
+			assert_eq!(parts.scheme, iri_ref.scheme());
+			assert_eq!(parts.authority, iri_ref.authority());
+			assert_eq!(parts.path.as_str(), iri_ref.path().as_str());
+			assert_eq!(
+				parts.query.as_ref().map(Query::as_str),
+				iri_ref.query().as_ref().map(Query::as_str)
+			);
+			assert_eq!(
+				parts.fragment.as_ref().map(Fragment::as_str),
+				iri_ref.fragment().as_ref().map(Fragment::as_str)
+			);
+		}
+	}
+}
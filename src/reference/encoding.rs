@@ -0,0 +1,128 @@
+use std::fmt::Write;
+
+/// Percent-encoding policy used by the string-accepting component setters,
+/// such as [`IriRefBuf::set_query_str_with`](crate::IriRefBuf::set_query_str_with).
+///
+/// # Example
+///
+/// ```
+/// # use iref::{EncodingPolicy, Error, IriRefBuf};
+/// let mut iri_ref = IriRefBuf::new("http://example.org/").unwrap();
+///
+/// assert_eq!(
+/// 	iri_ref.set_fragment_str_with(Some("a b"), EncodingPolicy::Strict),
+/// 	Err(Error::InvalidFragment)
+/// );
+///
+/// iri_ref.set_fragment_str_with(Some("a b"), EncodingPolicy::Encode).unwrap();
+/// assert_eq!(iri_ref, "http://example.org/#a%20b");
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum EncodingPolicy {
+	/// Reject any character that is not allowed in the component.
+	///
+	/// The input must already be a valid (percent-encoded) component.
+	Strict,
+
+	/// Percent-encode every character that is not allowed in the component.
+	///
+	/// Valid percent-encoded sequences (`%` followed by two hexadecimal digits) are kept
+	/// as they are, while any other `%` is encoded as `%25`.
+	/// Any input accepted by [`EncodingPolicy::Strict`] is then left unchanged.
+	Encode,
+}
+
+impl Default for EncodingPolicy {
+	#[inline]
+	fn default() -> EncodingPolicy {
+		EncodingPolicy::Strict
+	}
+}
+
+/// Percent-encode every character of `input` not satisfying `allowed`,
+/// keeping valid percent-encoded sequences.
+pub(crate) fn encode_component<F: Fn(char) -> bool>(input: &str, allowed: F) -> String {
+	let bytes = input.as_bytes();
+	let mut output = String::with_capacity(input.len());
+
+	for (i, c) in input.char_indices() {
+		let is_pct_encoded = c == '%'
+			&& matches!(bytes.get(i + 1), Some(b) if b.is_ascii_hexdigit())
+			&& matches!(bytes.get(i + 2), Some(b) if b.is_ascii_hexdigit());
+
+		if is_pct_encoded || (c != '%' && allowed(c)) {
+			output.push(c)
+		} else {
+			let mut buffer = [0; 4];
+			for byte in c.encode_utf8(&mut buffer).bytes() {
+				write!(output, "%{:02X}", byte).unwrap();
+			}
+		}
+	}
+
+	output
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{EncodingPolicy, Error, IriBuf, IriRefBuf};
+
+	#[test]
+	fn set_query_str() {
+		let mut iri_ref = IriRefBuf::new("http://a/b#f").unwrap();
+
+		iri_ref.set_query_str(Some("x=1&y=%20")).unwrap();
+		assert_eq!(iri_ref, "http://a/b?x=1&y=%20#f");
+
+		assert_eq!(iri_ref.set_query_str(Some("x y")), Err(Error::InvalidQuery));
+		assert_eq!(iri_ref.set_query_str(Some("x#y")), Err(Error::InvalidQuery));
+		assert_eq!(
+			iri_ref.set_query_str(Some("x%zz")),
+			Err(Error::InvalidPercentEncoding)
+		);
+		assert_eq!(iri_ref, "http://a/b?x=1&y=%20#f");
+
+		iri_ref
+			.set_query_str_with(Some("q=a b#c%20%zz\u{e000}"), EncodingPolicy::Encode)
+			.unwrap();
+		assert_eq!(iri_ref, "http://a/b?q=a%20b%23c%20%25zz\u{e000}#f");
+
+		iri_ref.set_query_str(None).unwrap();
+		assert_eq!(iri_ref, "http://a/b#f");
+	}
+
+	#[test]
+	fn set_fragment_str() {
+		let mut iri = IriBuf::new("http://a/b?q").unwrap();
+
+		iri.set_fragment_str(Some("section-2")).unwrap();
+		assert_eq!(iri, "http://a/b?q#section-2");
+		assert_eq!(
+			iri.set_fragment_str(Some("a#b")),
+			Err(Error::InvalidFragment)
+		);
+
+		iri.set_fragment_str_with(Some("a#b é\u{e000}"), EncodingPolicy::Encode)
+			.unwrap();
+		assert_eq!(iri, "http://a/b?q#a%23b%20é%EE%80%80");
+
+		iri.set_fragment_str_with(None, EncodingPolicy::Encode)
+			.unwrap();
+		assert_eq!(iri, "http://a/b?q");
+	}
+
+	#[test]
+	fn encode_keeps_valid_input() {
+		let valid = ["", "a=b&c", "%41%2F/?:@!$'()*+,;=-._~", "é"];
+
+		for input in valid.iter() {
+			let mut strict = IriRefBuf::default();
+			strict.set_query_str(Some(input)).unwrap();
+			let mut encoded = IriRefBuf::default();
+			encoded
+				.set_query_str_with(Some(input), EncodingPolicy::Encode)
+				.unwrap();
+			assert_eq!(strict.as_str(), encoded.as_str());
+		}
+	}
+}
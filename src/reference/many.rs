@@ -0,0 +1,121 @@
+use std::iter::FusedIterator;
+use std::str::SplitWhitespace;
+
+use crate::{Error, IriRef, IriRefBuf};
+
+/// Parse a list of whitespace-separated IRI-references.
+///
+/// The input is split on whitespace (including line breaks), and each token is parsed
+/// as an [`IriRef`] borrowing from the input.
+/// Blank lines and repeated whitespace are skipped.
+/// Since IRI-references cannot contain whitespace, this is suitable to read lists
+/// with one IRI per line, such as allow-lists.
+///
+/// Invalid tokens are reported as errors without stopping the iteration.
+///
+/// # Example
+///
+/// ```
+/// let input = "http://example.org/a\n\n  http://example.org/b ../c\n";
+/// let iris: Vec<_> = iref::parse_many(input).collect::<Result<_, _>>().unwrap();
+/// assert_eq!(iris, ["http://example.org/a", "http://example.org/b", "../c"]);
+///
+/// let mut iris = iref::parse_many("http://example.org/a a%ZZ");
+/// assert!(iris.next().unwrap().is_ok());
+/// assert!(iris.next().unwrap().is_err());
+/// assert!(iris.next().is_none());
+/// ```
+#[inline]
+pub fn parse_many(input: &str) -> ParseMany<'_> {
+	ParseMany {
+		tokens: input.split_whitespace(),
+	}
+}
+
+/// Parse a list of whitespace-separated IRI-references into owned buffers.
+///
+/// This is the same as [`parse_many`], but each IRI-reference is copied into an
+/// [`IriRefBuf`] so that it can outlive the input.
+#[inline]
+pub fn parse_many_buf(input: &str) -> ParseManyBuf<'_> {
+	ParseManyBuf {
+		inner: parse_many(input),
+	}
+}
+
+/// Iterator over the IRI-references of a whitespace-separated list.
+///
+/// See [`parse_many`].
+#[derive(Clone)]
+pub struct ParseMany<'a> {
+	tokens: SplitWhitespace<'a>,
+}
+
+impl<'a> Iterator for ParseMany<'a> {
+	type Item = Result<IriRef<'a>, Error>;
+
+	#[inline]
+	fn next(&mut self) -> Option<Result<IriRef<'a>, Error>> {
+		self.tokens.next().map(IriRef::new)
+	}
+}
+
+impl<'a> DoubleEndedIterator for ParseMany<'a> {
+	#[inline]
+	fn next_back(&mut self) -> Option<Result<IriRef<'a>, Error>> {
+		self.tokens.next_back().map(IriRef::new)
+	}
+}
+
+impl<'a> FusedIterator for ParseMany<'a> {}
+
+/// Iterator over the owned IRI-references of a whitespace-separated list.
+///
+/// See [`parse_many_buf`].
+#[derive(Clone)]
+pub struct ParseManyBuf<'a> {
+	inner: ParseMany<'a>,
+}
+
+impl<'a> Iterator for ParseManyBuf<'a> {
+	type Item = Result<IriRefBuf, Error>;
+
+	#[inline]
+	fn next(&mut self) -> Option<Result<IriRefBuf, Error>> {
+		self.inner.next().map(|r| r.map(IriRef::to_owned))
+	}
+}
+
+impl<'a> DoubleEndedIterator for ParseManyBuf<'a> {
+	#[inline]
+	fn next_back(&mut self) -> Option<Result<IriRefBuf, Error>> {
+		self.inner.next_back().map(|r| r.map(IriRef::to_owned))
+	}
+}
+
+impl<'a> FusedIterator for ParseManyBuf<'a> {}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parse_many_lines() {
+		let input = "\n  http://a/b\r\n\r\n\tc/d?q  #f\n";
+		let items: Vec<_> = parse_many(input).collect::<Result<_, _>>().unwrap();
+		assert_eq!(items, ["http://a/b", "c/d?q", "#f"]);
+		assert_eq!(parse_many(input).next_back().unwrap().unwrap(), "#f");
+
+		assert_eq!(parse_many("").count(), 0);
+		assert_eq!(parse_many(" \n\t\n").count(), 0);
+	}
+
+	#[test]
+	fn parse_many_errors() {
+		let items: Vec<_> = parse_many_buf("a b%G c").collect();
+		assert_eq!(items.len(), 3);
+		assert_eq!(items[0].as_ref().unwrap(), "a");
+		assert!(items[1].is_err());
+		assert_eq!(items[2].as_ref().unwrap(), "c");
+	}
+}
@@ -0,0 +1,673 @@
+use crate::{parsing, ComponentKind, IriBuf, IriRef, IriRefBuf, PercentDecode, Query, Scheme};
+use std::collections::{hash_map::RandomState, HashSet};
+use std::hash::BuildHasher;
+
+/// Percent-encoding normalization policy.
+///
+/// Percent-encoding normalization
+/// (see [RFC 3986 section 6.2.2.2](https://tools.ietf.org/html/rfc3986#section-6.2.2.2))
+/// decodes percent-encoded unreserved characters and uppercases the hexadecimal digits of the
+/// remaining percent-encoded octets.
+/// A policy selects the characters that are *active* in a given component for a given scheme:
+/// a percent-encoded active character is never decoded and is left exactly as it is,
+/// hexadecimal digits included.
+///
+/// Any `Fn(Option<Scheme>, ComponentKind, char) -> bool` function can be used as a policy.
+///
+/// # Example
+///
+/// ```
+/// # use iref::{ComponentKind, IriRefBuf, Scheme};
+/// // `~` is meaningful in the query of `http` IRIs.
+/// fn policy(scheme: Option<Scheme>, component: ComponentKind, c: char) -> bool {
+/// 	scheme.map(|s| s == "http").unwrap_or(false) && component == ComponentKind::Query && c == '~'
+/// }
+///
+/// let mut iri_ref = IriRefBuf::new("http://example.org/%7euser?q=%7e").unwrap();
+/// iri_ref.normalize_percent_encoding_with(&policy);
+/// assert_eq!(iri_ref, "http://example.org/~user?q=%7e");
+/// ```
+pub trait PctNormalizationPolicy {
+	/// Checks if the given character is active in the given component,
+	/// meaning that its percent-encoded form must be preserved.
+	fn is_active(&self, scheme: Option<Scheme>, component: ComponentKind, c: char) -> bool;
+}
+
+impl<F: Fn(Option<Scheme>, ComponentKind, char) -> bool> PctNormalizationPolicy for F {
+	#[inline]
+	fn is_active(&self, scheme: Option<Scheme>, component: ComponentKind, c: char) -> bool {
+		self(scheme, component, c)
+	}
+}
+
+/// Default percent-encoding normalization policy.
+///
+/// No character is active.
+/// Only percent-encoded unreserved characters (and private characters in the query) are
+/// decoded by the normalization, so reserved characters with a special meaning for some schemes
+/// are always preserved.
+/// For instance form-encoded queries (`application/x-www-form-urlencoded`) survive the
+/// normalization unchanged: `+`, `&` and `=` and their percent-encoded form `%2B`, `%26` and
+/// `%3D` are left untouched (up to the case of the hexadecimal digits).
+///
+/// Use a custom [`PctNormalizationPolicy`] to preserve additional characters.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct DefaultPctNormalizationPolicy;
+
+impl PctNormalizationPolicy for DefaultPctNormalizationPolicy {
+	#[inline]
+	fn is_active(&self, _scheme: Option<Scheme>, _component: ComponentKind, _c: char) -> bool {
+		false
+	}
+}
+
+/// Normalization options.
+///
+/// Options of the [`IriRefBuf::normalize_with`](crate::IriRefBuf::normalize_with) method.
+/// By default, only the syntax-based normalization of
+/// [RFC 3986 section 6.2.2](https://tools.ietf.org/html/rfc3986#section-6.2.2) is performed.
+///
+/// More options may be added in the future, so this type can only be built with
+/// [`NormalizeOptions::new`] (or [`Default`]) and the builder methods.
+///
+/// # Example
+///
+/// ```
+/// # use iref::NormalizeOptions;
+/// let options = NormalizeOptions::new().sort_query();
+/// assert!(options.sort_query);
+/// ```
+#[derive(Default, Clone, Copy, Debug)]
+#[non_exhaustive]
+pub struct NormalizeOptions {
+	/// Sort the query parameters by their percent-decoded key.
+	///
+	/// The sort is stable: parameters with the same key keep their relative order.
+	/// This is not a syntax-based normalization since the order of the parameters is
+	/// sometimes significant, hence it is disabled by default.
+	pub sort_query: bool,
+}
+
+impl NormalizeOptions {
+	/// Default options, performing only the syntax-based normalization.
+	#[inline]
+	pub fn new() -> NormalizeOptions {
+		NormalizeOptions::default()
+	}
+
+	/// Enables the sorting of the query parameters.
+	///
+	/// See [`NormalizeOptions::sort_query`](#structfield.sort_query).
+	#[inline]
+	pub fn sort_query(mut self) -> NormalizeOptions {
+		self.sort_query = true;
+		self
+	}
+}
+
+/// Composable normalization.
+///
+/// Each normalization step is enabled with the corresponding builder method, and the
+/// enabled steps are then applied to an IRI (reference) with [`Normalizer::apply`]
+/// or [`Normalizer::apply_iri`].
+/// A new normalizer ([`Normalizer::new`]) has no step enabled, while the default
+/// normalizer ([`Normalizer::default`]) performs the syntax-based normalization of
+/// [RFC 3986 section 6.2.2](https://tools.ietf.org/html/rfc3986#section-6.2.2),
+/// the same as [`IriRefBuf::normalize`].
+///
+/// # Example
+///
+/// ```
+/// # use iref::{IriBuf, Normalizer};
+/// let mut iri = IriBuf::new("HTTP://Example.ORG:80/a/./b/../%7Ec").unwrap();
+///
+/// Normalizer::new()
+/// 	.case_fold_scheme()
+/// 	.case_fold_host()
+/// 	.drop_default_port()
+/// 	.apply_iri(&mut iri);
+///
+/// assert_eq!(iri, "http://example.org/a/./b/../%7Ec");
+///
+/// Normalizer::default().apply_iri(&mut iri);
+/// assert_eq!(iri, "http://example.org/a/~c");
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Normalizer {
+	case_fold_scheme: bool,
+	case_fold_host: bool,
+	decode_unreserved: bool,
+	remove_dot_segments: bool,
+	drop_default_port: bool,
+	sort_query: bool,
+}
+
+impl Normalizer {
+	/// Creates a new normalizer with no normalization step enabled.
+	#[inline]
+	pub fn new() -> Normalizer {
+		Normalizer {
+			case_fold_scheme: false,
+			case_fold_host: false,
+			decode_unreserved: false,
+			remove_dot_segments: false,
+			drop_default_port: false,
+			sort_query: false,
+		}
+	}
+
+	/// Lowercases the scheme.
+	#[inline]
+	pub fn case_fold_scheme(mut self) -> Normalizer {
+		self.case_fold_scheme = true;
+		self
+	}
+
+	/// Lowercases the host, except for the hexadecimal digits of its percent-encoded octets.
+	#[inline]
+	pub fn case_fold_host(mut self) -> Normalizer {
+		self.case_fold_host = true;
+		self
+	}
+
+	/// Normalizes the percent-encoding using the [`DefaultPctNormalizationPolicy`].
+	///
+	/// See [`IriRefBuf::normalize_percent_encoding`].
+	#[inline]
+	pub fn decode_unreserved(mut self) -> Normalizer {
+		self.decode_unreserved = true;
+		self
+	}
+
+	/// Removes the dot segments (`.` and `..`) from the path.
+	#[inline]
+	pub fn remove_dot_segments(mut self) -> Normalizer {
+		self.remove_dot_segments = true;
+		self
+	}
+
+	/// Removes the port if it is empty or equal to the default port of the scheme
+	/// (see [`Scheme::default_port`]).
+	///
+	/// This is a scheme-based normalization
+	/// (see [RFC 3986 section 6.2.3](https://tools.ietf.org/html/rfc3986#section-6.2.3)),
+	/// not enabled by default.
+	#[inline]
+	pub fn drop_default_port(mut self) -> Normalizer {
+		self.drop_default_port = true;
+		self
+	}
+
+	/// Sorts the query parameters by their percent-decoded key.
+	///
+	/// See [`NormalizeOptions::sort_query`]. Not enabled by default.
+	#[inline]
+	pub fn sort_query(mut self) -> Normalizer {
+		self.sort_query = true;
+		self
+	}
+
+	/// Applies the enabled normalization steps to the given IRI reference.
+	pub fn apply(&self, iri_ref: &mut IriRefBuf) {
+		if self.decode_unreserved {
+			iri_ref.normalize_percent_encoding()
+		}
+
+		if self.case_fold_scheme {
+			if let Some(scheme_len) = iri_ref.p.scheme_len {
+				iri_ref.data[..scheme_len].make_ascii_lowercase()
+			}
+		}
+
+		if let Some(authority) = iri_ref.p.authority {
+			if self.case_fold_host {
+				let offset = iri_ref.p.authority_offset() + authority.host_offset();
+				lowercase_except_pct(&mut iri_ref.data[offset..(offset + authority.host_len)])
+			}
+
+			if self.drop_default_port && authority.port_len.is_some() {
+				let is_default = iri_ref
+					.authority()
+					.unwrap()
+					.normalized_port(iri_ref.scheme())
+					.is_none();
+				if is_default {
+					iri_ref.authority_mut().unwrap().set_port(None)
+				}
+			}
+		}
+
+		if self.remove_dot_segments {
+			iri_ref.path_mut().normalize()
+		}
+
+		if self.sort_query {
+			if let Some(query) = iri_ref.query() {
+				let sorted = sort_query(query);
+				iri_ref.set_query(Some(Query { data: &sorted }))
+			}
+		}
+	}
+
+	/// Applies the enabled normalization steps to the given IRI.
+	#[inline]
+	pub fn apply_iri(&self, iri: &mut IriBuf) {
+		// No normalization step removes the scheme.
+		self.apply(&mut iri.0)
+	}
+}
+
+impl Default for Normalizer {
+	/// Creates a normalizer performing the syntax-based normalization of RFC 3986:
+	/// case folding of the scheme and host, percent-encoding normalization and dot
+	/// segments removal.
+	#[inline]
+	fn default() -> Normalizer {
+		Normalizer::new()
+			.case_fold_scheme()
+			.case_fold_host()
+			.decode_unreserved()
+			.remove_dot_segments()
+	}
+}
+
+/// Removes the duplicate IRIs of a collection, treating equivalent IRIs as duplicates.
+///
+/// Two IRIs are equivalent if they have the same normalized form, given by
+/// [`IriRef::sort_key`]: they only differ by the case of their scheme and host,
+/// their percent-encoding, their dot segments or an explicit default port.
+/// This is the equality used when the `normalized-eq` feature is enabled.
+///
+/// The first IRI of each equivalence class is kept, and the order of the collection is
+/// preserved.
+///
+/// # Example
+///
+/// ```
+/// # use iref::IriBuf;
+/// let iris = vec![
+/// 	IriBuf::new("http://example.org/a").unwrap(),
+/// 	IriBuf::new("https://example.org/").unwrap(),
+/// 	IriBuf::new("HTTP://Example.org:80/b/../%61").unwrap(),
+/// ];
+///
+/// assert_eq!(
+/// 	iref::dedup_normalized(iris),
+/// 	["http://example.org/a", "https://example.org/"]
+/// );
+/// ```
+#[inline]
+pub fn dedup_normalized<I: IntoIterator<Item = IriBuf>>(iris: I) -> Vec<IriBuf> {
+	dedup_normalized_with_hasher(iris, RandomState::new())
+}
+
+/// Removes the duplicate IRIs of a collection, treating equivalent IRIs as duplicates,
+/// using the given hasher builder for the set of normalized IRIs already seen.
+///
+/// See [`dedup_normalized`].
+pub fn dedup_normalized_with_hasher<I, S>(iris: I, hash_builder: S) -> Vec<IriBuf>
+where
+	I: IntoIterator<Item = IriBuf>,
+	S: BuildHasher,
+{
+	let mut seen = HashSet::with_hasher(hash_builder);
+	iris.into_iter()
+		.filter(|iri| seen.insert(iri.as_iri_ref().sort_key()))
+		.collect()
+}
+
+/// Checks if the given percent-encoded character can be decoded in the given component.
+#[inline]
+fn is_decodable(component: ComponentKind, c: char) -> bool {
+	parsing::is_unreserved(c) || (component == ComponentKind::Query && parsing::is_private(c))
+}
+
+#[inline]
+fn hex_value(b: u8) -> u8 {
+	match b {
+		b'0'..=b'9' => b - b'0',
+		b'a'..=b'f' => b - b'a' + 10,
+		_ => b - b'A' + 10,
+	}
+}
+
+/// Decodes the UTF-8 character encoded by the percent-encoded octets starting at `bytes[0]`.
+///
+/// Returns the character and the number of consumed bytes.
+fn decode_char(bytes: &[u8]) -> Option<(char, usize)> {
+	let octet = |i: usize| -> Option<u8> {
+		let j = i * 3;
+		if bytes.len() >= j + 3 && bytes[j] == b'%' {
+			Some(hex_value(bytes[j + 1]) << 4 | hex_value(bytes[j + 2]))
+		} else {
+			None
+		}
+	};
+
+	let first = octet(0)?;
+	let len = match first {
+		0x00..=0x7f => 1,
+		0xc0..=0xdf => 2,
+		0xe0..=0xef => 3,
+		0xf0..=0xf7 => 4,
+		_ => return None,
+	};
+
+	let mut buffer = [0; 4];
+	for (i, b) in buffer.iter_mut().enumerate().take(len) {
+		*b = octet(i)?;
+	}
+
+	let c = std::str::from_utf8(&buffer[..len]).ok()?.chars().next()?;
+	Some((c, len * 3))
+}
+
+/// Normalizes the percent-encoding of the given component, appending the result to `output`.
+fn normalize_component<P: PctNormalizationPolicy + ?Sized>(
+	policy: &P,
+	scheme: Option<Scheme>,
+	component: ComponentKind,
+	input: &str,
+	output: &mut String,
+) {
+	let bytes = input.as_bytes();
+	let mut i = 0;
+
+	while i < bytes.len() {
+		if bytes[i] == b'%' {
+			match decode_char(&bytes[i..]) {
+				Some((c, len)) if policy.is_active(scheme, component, c) => {
+					output.push_str(&input[i..(i + len)]);
+					i += len
+				}
+				Some((c, len)) if is_decodable(component, c) => {
+					output.push(c);
+					i += len
+				}
+				_ => {
+					output.push('%');
+					output.push(bytes[i + 1].to_ascii_uppercase() as char);
+					output.push(bytes[i + 2].to_ascii_uppercase() as char);
+					i += 3
+				}
+			}
+		} else {
+			let c = input[i..].chars().next().unwrap();
+			output.push(c);
+			i += c.len_utf8()
+		}
+	}
+}
+
+/// Normalizes the percent-encoding of the given IRI-reference.
+pub(crate) fn normalize_percent_encoding<P: PctNormalizationPolicy + ?Sized>(
+	iri_ref: IriRef,
+	policy: &P,
+) -> String {
+	let scheme = iri_ref.scheme();
+	let mut output = String::with_capacity(iri_ref.len());
+
+	for (component, value) in iri_ref.components() {
+		match component {
+			ComponentKind::Scheme => {
+				output.push_str(value);
+				output.push(':');
+				continue;
+			}
+			ComponentKind::Authority => output.push_str("//"),
+			ComponentKind::Path => (),
+			ComponentKind::Query => output.push('?'),
+			ComponentKind::Fragment => output.push('#'),
+		}
+
+		normalize_component(policy, scheme, component, value, &mut output)
+	}
+
+	output
+}
+
+/// Normalizes the percent-encoding of the given query and sorts its parameters.
+pub(crate) fn canonical_query(query: Query) -> Vec<u8> {
+	let mut normalized = String::with_capacity(query.as_bytes().len());
+	normalize_component(
+		&DefaultPctNormalizationPolicy,
+		None,
+		ComponentKind::Query,
+		query.as_str(),
+		&mut normalized,
+	);
+
+	sort_query(Query {
+		data: normalized.as_bytes(),
+	})
+}
+
+/// Lowercases the ASCII letters of the given bytes, except for the hexadecimal digits of
+/// percent-encoded octets, which must stay uppercase once normalized.
+pub(crate) fn lowercase_except_pct(bytes: &mut [u8]) {
+	let mut i = 0;
+	while i < bytes.len() {
+		if bytes[i] == b'%' {
+			i += 3
+		} else {
+			bytes[i].make_ascii_lowercase();
+			i += 1
+		}
+	}
+}
+
+/// Sorts the `&` separated parameters of the given query by their percent-decoded key.
+///
+/// Keys are compared as decoded bytes, since they may not be valid UTF-8 (as in `%FF`).
+pub(crate) fn sort_query(query: Query) -> Vec<u8> {
+	let mut params: Vec<_> = query
+		.as_bytes()
+		.split(|c| *c == b'&')
+		.map(|param| {
+			let key = param.split(|c| *c == b'=').next().unwrap();
+			(PercentDecode::new(key).into_bytes(), param)
+		})
+		.collect();
+
+	params.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+	let mut output = Vec::with_capacity(query.as_bytes().len());
+	for (i, (_, param)) in params.into_iter().enumerate() {
+		if i > 0 {
+			output.push(b'&')
+		}
+
+		output.extend_from_slice(param)
+	}
+
+	output
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{NormalizeOptions, Normalizer};
+	use crate::{ComponentKind, IriBuf, IriRefBuf, Scheme};
+
+	#[test]
+	fn dedup_normalized() {
+		use std::collections::hash_map::DefaultHasher;
+		use std::hash::BuildHasherDefault;
+
+		let iris: Vec<_> = [
+			"http://a/b",
+			"http://a/c",
+			"HTTP://A/b",
+			"http://a:80/./b",
+			"http://a/%62",
+			"https://a/b",
+			"http://a/b#f",
+			"http://a/c",
+			"http://a:8080/b",
+			"http://a/%FF",
+			"http://a/%ff",
+		]
+		.iter()
+		.map(|iri| IriBuf::new(iri).unwrap())
+		.collect();
+
+		let expected = [
+			"http://a/b",
+			"http://a/c",
+			"https://a/b",
+			"http://a/b#f",
+			"http://a:8080/b",
+			"http://a/%FF",
+		];
+
+		let deduped = super::dedup_normalized(iris.clone());
+		assert_eq!(
+			deduped.iter().map(|iri| iri.as_str()).collect::<Vec<_>>(),
+			expected
+		);
+
+		let deduped = super::dedup_normalized_with_hasher(
+			iris,
+			BuildHasherDefault::<DefaultHasher>::default(),
+		);
+		assert_eq!(
+			deduped.iter().map(|iri| iri.as_str()).collect::<Vec<_>>(),
+			expected
+		);
+		assert!(super::dedup_normalized(Vec::new()).is_empty())
+	}
+
+	#[test]
+	fn normalize_path() {
+		let challenges = [
+			("http://a/a/./b/../c", "http://a/a/c"),
+			(
+				"HTTP://A/a/./%2E/b/../c?./..#../.",
+				"HTTP://A/a/%2E/c?./..#../.",
+			),
+			("http://a/../a/./", "http://a/a/"),
+			("http://a/a/..", "http://a/"),
+			("s:/a/..//b", "s:/.//b"),
+			("./a/b", "a/b"),
+			("../a/./b", "../a/b"),
+			("./../a", "../a"),
+			("a/../..", ".."),
+			("/../a", "/a"),
+			("", ""),
+		];
+
+		for (input, expected) in challenges.iter() {
+			let mut iri_ref = IriRefBuf::new(input).unwrap();
+			iri_ref.normalize_path();
+			assert_eq!(iri_ref.as_str(), *expected, "{}", input)
+		}
+
+		let mut iri = IriBuf::new("http://a/a/./b/../c").unwrap();
+		iri.normalize_path();
+		assert_eq!(iri, "http://a/a/c")
+	}
+
+	#[test]
+	fn normalize_default() {
+		let mut iri_ref =
+			IriRefBuf::new("http://%7Eu@ex%61mple.org/%7e%2fa/%c3%a9?a%2Bb=%3d+#%e2%82%ac%2a")
+				.unwrap();
+		iri_ref.normalize_percent_encoding();
+		assert_eq!(
+			iri_ref.as_str(),
+			"http://~u@example.org/~%2Fa/é?a%2Bb=%3D+#€%2A"
+		);
+	}
+
+	#[test]
+	fn normalize() {
+		let mut iri_ref =
+			IriRefBuf::new("HTTP://Ex%61mple.%c3%a9.ORG/a/./b/../c?b=1&%61=2#F").unwrap();
+		iri_ref.normalize();
+		assert_eq!(iri_ref.as_str(), "http://example.é.org/a/c?b=1&a=2#F");
+
+		let mut iri_ref = IriRefBuf::new("FOO://A%2fB").unwrap();
+		iri_ref.normalize();
+		assert_eq!(iri_ref.as_str(), "foo://a%2Fb");
+	}
+
+	#[test]
+	fn normalize_sort_query() {
+		let options = NormalizeOptions::new().sort_query();
+
+		let mut iri_ref = IriRefBuf::new("http://a/?b=1&%61=2&c&b=0&a%20b=3&=4").unwrap();
+		iri_ref.normalize_with(options);
+		assert_eq!(iri_ref.as_str(), "http://a/?=4&a=2&a%20b=3&b=1&b=0&c");
+
+		let mut iri_ref = IriRefBuf::new("?b&a#f").unwrap();
+		iri_ref.normalize_with(options);
+		assert_eq!(iri_ref.as_str(), "?a&b#f");
+
+		let mut iri_ref = IriRefBuf::new("?%ff=1&%C3%A9=2&a=3").unwrap();
+		iri_ref.normalize_with(options);
+		assert_eq!(iri_ref.as_str(), "?a=3&é=2&%FF=1");
+	}
+
+	#[test]
+	fn normalizer_steps() {
+		let input = "HTTPS://U@Ex%61mple.ORG:443/a/../%7e?b&a";
+		let challenges = [
+			(Normalizer::new(), input),
+			(
+				Normalizer::new().case_fold_scheme(),
+				"https://U@Ex%61mple.ORG:443/a/../%7e?b&a",
+			),
+			(
+				Normalizer::new().case_fold_host(),
+				"HTTPS://U@ex%61mple.org:443/a/../%7e?b&a",
+			),
+			(
+				Normalizer::new().decode_unreserved(),
+				"HTTPS://U@Example.ORG:443/a/../~?b&a",
+			),
+			(
+				Normalizer::new().remove_dot_segments(),
+				"HTTPS://U@Ex%61mple.ORG:443/%7e?b&a",
+			),
+			(
+				Normalizer::new().drop_default_port(),
+				"HTTPS://U@Ex%61mple.ORG/a/../%7e?b&a",
+			),
+			(
+				Normalizer::new().sort_query(),
+				"HTTPS://U@Ex%61mple.ORG:443/a/../%7e?a&b",
+			),
+			(Normalizer::default(), "https://U@example.org:443/~?b&a"),
+		];
+
+		for (normalizer, expected) in challenges.iter() {
+			let mut iri_ref = IriRefBuf::new(input).unwrap();
+			normalizer.apply(&mut iri_ref);
+			assert_eq!(iri_ref.as_str(), *expected);
+		}
+
+		let mut iri_ref = IriRefBuf::new("http://a:/b").unwrap();
+		Normalizer::new().drop_default_port().apply(&mut iri_ref);
+		assert_eq!(iri_ref.as_str(), "http://a/b");
+
+		let mut iri_ref = IriRefBuf::new("http://a:8080/b").unwrap();
+		Normalizer::new().drop_default_port().apply(&mut iri_ref);
+		assert_eq!(iri_ref.as_str(), "http://a:8080/b");
+	}
+
+	#[test]
+	fn normalize_with_policy() {
+		fn policy(scheme: Option<Scheme>, component: ComponentKind, c: char) -> bool {
+			scheme.map(|s| s == "foo").unwrap_or(false)
+				&& component == ComponentKind::Query
+				&& c == '~'
+		}
+
+		let mut iri_ref = IriRefBuf::new("foo:%41%7e?%41%7e").unwrap();
+		iri_ref.normalize_percent_encoding_with(&policy);
+		assert_eq!(iri_ref.as_str(), "foo:A~?A%7e");
+
+		let mut iri_ref = IriRefBuf::new("bar:%41%7e?%41%7e").unwrap();
+		iri_ref.normalize_percent_encoding_with(&policy);
+		assert_eq!(iri_ref.as_str(), "bar:A~?A~");
+	}
+}
@@ -0,0 +1,164 @@
+use std::{
+	borrow::Borrow,
+	cmp::Ordering,
+	fmt,
+	hash::{Hash, Hasher},
+	ops::Deref,
+};
+
+/// IRI (or IRI-reference) wrapper compared and hashed as a plain string.
+///
+/// The IRI types do not implement `Borrow<str>`, because their [`PartialEq`] and [`Hash`]
+/// implementations work component by component (ignoring the percent-encoding for instance,
+/// or normalizing the whole IRI with the `normalized-eq` feature).
+/// Two IRIs may then be equal while their string representations are not, which breaks
+/// the `Borrow` contract: `x.borrow() == y.borrow()` must be equivalent to `x == y`, and
+/// `hash(x.borrow()) == hash(x)`.
+///
+/// This wrapper uses the exact string representation of the IRI instead, and implements
+/// `Borrow<str>`. It can be used as a `HashMap` or `BTreeMap` key to look values up with a
+/// `&str`.
+///
+/// # Example
+///
+/// ```
+/// # use std::collections::HashMap;
+/// # use iref::{IriBuf, StrKey};
+/// let mut map = HashMap::new();
+/// map.insert(StrKey::new(IriBuf::new("https://example.org/a").unwrap()), 1);
+///
+/// assert_eq!(map.get("https://example.org/a"), Some(&1));
+/// assert_eq!(map.get("https://example.org/%61"), None);
+/// ```
+#[derive(Clone, Copy, Default)]
+pub struct StrKey<T>(T);
+
+impl<T: AsRef<str>> StrKey<T> {
+	/// Wraps the given IRI.
+	#[inline]
+	pub fn new(iri: T) -> StrKey<T> {
+		StrKey(iri)
+	}
+
+	/// Returns the string representation used for comparison and hashing.
+	#[inline]
+	pub fn as_str(&self) -> &str {
+		self.0.as_ref()
+	}
+
+	/// Returns a reference to the wrapped IRI.
+	#[inline]
+	pub fn as_inner(&self) -> &T {
+		&self.0
+	}
+
+	/// Consumes the wrapper and returns the wrapped IRI.
+	#[inline]
+	pub fn into_inner(self) -> T {
+		self.0
+	}
+}
+
+impl<T: AsRef<str>> From<T> for StrKey<T> {
+	#[inline]
+	fn from(iri: T) -> StrKey<T> {
+		StrKey(iri)
+	}
+}
+
+impl<T> Deref for StrKey<T> {
+	type Target = T;
+
+	#[inline]
+	fn deref(&self) -> &T {
+		&self.0
+	}
+}
+
+impl<T: AsRef<str>> Borrow<str> for StrKey<T> {
+	#[inline]
+	fn borrow(&self) -> &str {
+		self.as_str()
+	}
+}
+
+impl<T: AsRef<str>> AsRef<str> for StrKey<T> {
+	#[inline]
+	fn as_ref(&self) -> &str {
+		self.as_str()
+	}
+}
+
+impl<T: AsRef<str>> fmt::Display for StrKey<T> {
+	#[inline]
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		self.as_str().fmt(f)
+	}
+}
+
+impl<T: AsRef<str>> fmt::Debug for StrKey<T> {
+	#[inline]
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		self.as_str().fmt(f)
+	}
+}
+
+impl<T: AsRef<str>, U: AsRef<str>> PartialEq<StrKey<U>> for StrKey<T> {
+	#[inline]
+	fn eq(&self, other: &StrKey<U>) -> bool {
+		self.as_str() == other.as_str()
+	}
+}
+
+impl<T: AsRef<str>> Eq for StrKey<T> {}
+
+impl<'a, T: AsRef<str>> PartialEq<&'a str> for StrKey<T> {
+	#[inline]
+	fn eq(&self, other: &&'a str) -> bool {
+		self.as_str() == *other
+	}
+}
+
+impl<T: AsRef<str>, U: AsRef<str>> PartialOrd<StrKey<U>> for StrKey<T> {
+	#[inline]
+	fn partial_cmp(&self, other: &StrKey<U>) -> Option<Ordering> {
+		Some(self.as_str().cmp(other.as_str()))
+	}
+}
+
+impl<T: AsRef<str>> Ord for StrKey<T> {
+	#[inline]
+	fn cmp(&self, other: &StrKey<T>) -> Ordering {
+		self.as_str().cmp(other.as_str())
+	}
+}
+
+impl<T: AsRef<str>> Hash for StrKey<T> {
+	#[inline]
+	fn hash<H: Hasher>(&self, hasher: &mut H) {
+		self.as_str().hash(hasher)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{Iri, IriRefBuf};
+	use std::collections::{BTreeMap, HashMap};
+
+	#[test]
+	fn str_lookup() {
+		let mut map = HashMap::new();
+		map.insert(StrKey::new(IriRefBuf::new("../a?b").unwrap()), 1);
+		map.insert(StrKey::new(IriRefBuf::new("../%61?b").unwrap()), 2);
+		assert_eq!(map.len(), 2);
+		assert_eq!(map.get("../a?b"), Some(&1));
+		assert_eq!(map.get("../%61?b"), Some(&2));
+
+		let mut map = BTreeMap::new();
+		map.insert(StrKey::new(Iri::new("http://b").unwrap()), 1);
+		map.insert(StrKey::new(Iri::new("http://a").unwrap()), 2);
+		assert_eq!(map.get("http://a"), Some(&2));
+		assert_eq!(map.keys().next().unwrap(), &"http://a");
+	}
+}
@@ -0,0 +1,686 @@
+use std::{collections::HashMap, fmt, fmt::Write, str::FromStr};
+
+use crate::{parsing, Error, IriBuf, IriRefBuf};
+
+/// Value of a template variable.
+///
+/// See [`IriTemplate::expand`].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum TemplateValue {
+	/// String value.
+	String(String),
+
+	/// List of string values.
+	List(Vec<String>),
+
+	/// Associative array of string values, with the order of its entries.
+	Map(Vec<(String, String)>),
+}
+
+impl TemplateValue {
+	/// Checks if the value is defined, in the sense of
+	/// [RFC 6570 section 2.3](https://tools.ietf.org/html/rfc6570#section-2.3).
+	///
+	/// Empty lists and empty associative arrays are undefined.
+	#[inline]
+	pub fn is_defined(&self) -> bool {
+		match self {
+			TemplateValue::String(_) => true,
+			TemplateValue::List(items) => !items.is_empty(),
+			TemplateValue::Map(entries) => !entries.is_empty(),
+		}
+	}
+}
+
+impl<'a> From<&'a str> for TemplateValue {
+	#[inline]
+	fn from(value: &'a str) -> TemplateValue {
+		TemplateValue::String(value.to_string())
+	}
+}
+
+impl From<String> for TemplateValue {
+	#[inline]
+	fn from(value: String) -> TemplateValue {
+		TemplateValue::String(value)
+	}
+}
+
+impl From<Vec<String>> for TemplateValue {
+	#[inline]
+	fn from(items: Vec<String>) -> TemplateValue {
+		TemplateValue::List(items)
+	}
+}
+
+impl From<Vec<(String, String)>> for TemplateValue {
+	#[inline]
+	fn from(entries: Vec<(String, String)>) -> TemplateValue {
+		TemplateValue::Map(entries)
+	}
+}
+
+/// Expression operator.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Operator {
+	/// Simple string expansion `{var}`.
+	Simple,
+
+	/// Reserved expansion `{+var}`.
+	Reserved,
+
+	/// Fragment expansion `{#var}`.
+	Fragment,
+
+	/// Label expansion `{.var}`.
+	Label,
+
+	/// Path segment expansion `{/var}`.
+	PathSegment,
+
+	/// Path-style parameter expansion `{;var}`.
+	PathParameter,
+
+	/// Form-style query expansion `{?var}`.
+	Query,
+
+	/// Form-style query continuation `{&var}`.
+	QueryContinuation,
+}
+
+impl Operator {
+	/// String inserted before the first defined variable.
+	fn first(&self) -> &'static str {
+		match self {
+			Operator::Simple | Operator::Reserved => "",
+			Operator::Fragment => "#",
+			Operator::Label => ".",
+			Operator::PathSegment => "/",
+			Operator::PathParameter => ";",
+			Operator::Query => "?",
+			Operator::QueryContinuation => "&",
+		}
+	}
+
+	/// Separator between variables.
+	fn separator(&self) -> char {
+		match self {
+			Operator::Simple | Operator::Reserved | Operator::Fragment => ',',
+			Operator::Label => '.',
+			Operator::PathSegment => '/',
+			Operator::PathParameter => ';',
+			Operator::Query | Operator::QueryContinuation => '&',
+		}
+	}
+
+	/// Checks if the variables are expanded as `name=value` pairs.
+	fn is_named(&self) -> bool {
+		matches!(
+			self,
+			Operator::PathParameter | Operator::Query | Operator::QueryContinuation
+		)
+	}
+
+	/// String appended to the name of an empty named variable.
+	fn if_empty(&self) -> &'static str {
+		match self {
+			Operator::Query | Operator::QueryContinuation => "=",
+			_ => "",
+		}
+	}
+
+	/// Checks if reserved characters and percent-encoded triplets are left unencoded.
+	fn allows_reserved(&self) -> bool {
+		matches!(self, Operator::Reserved | Operator::Fragment)
+	}
+}
+
+/// Variable value modifier.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Modifier {
+	None,
+
+	/// Prefix modifier `:n`, keeping only the first `n` characters of the value.
+	Prefix(usize),
+
+	/// Explode modifier `*`.
+	Explode,
+}
+
+#[derive(Clone, Debug)]
+struct VarSpec {
+	name: String,
+	modifier: Modifier,
+}
+
+#[derive(Clone, Debug)]
+enum Part {
+	/// Literal, already encoded.
+	Literal(String),
+
+	/// Expression.
+	Expression(Operator, Vec<VarSpec>),
+}
+
+/// IRI template.
+///
+/// Implements the [RFC 6570](https://tools.ietf.org/html/rfc6570) URI Template syntax
+/// up to level 4: simple (`{var}`), reserved (`{+var}`), fragment (`{#var}`),
+/// label (`{.var}`), path segment (`{/var}`), path-style parameter (`{;var}`) and
+/// form-style query (`{?var}`, `{&var}`) expansions, with the prefix (`{var:3}`) and
+/// explode (`{var*}`) modifiers.
+///
+/// Since this is an IRI template, non-ASCII unreserved characters
+/// (see [RFC 3987](https://tools.ietf.org/html/rfc3987#section-2.2)) are not percent-encoded
+/// by the expansion.
+///
+/// # Example
+///
+/// ```
+/// # use std::collections::HashMap;
+/// # use iref::{IriTemplate, TemplateValue};
+/// let template = IriTemplate::new("http://x/users/{id}{?fields*}").unwrap();
+///
+/// let mut vars = HashMap::new();
+/// vars.insert("id".to_string(), TemplateValue::from("42"));
+/// vars.insert("fields".to_string(), TemplateValue::List(vec!["name".into(), "email".into()]));
+///
+/// assert_eq!(template.expand(&vars).unwrap(), "http://x/users/42?fields=name&fields=email");
+/// ```
+#[derive(Clone)]
+pub struct IriTemplate {
+	source: String,
+	parts: Vec<Part>,
+}
+
+impl IriTemplate {
+	/// Parses the given template.
+	///
+	/// Returns [`Error::InvalidTemplate`] if the template is not syntactically valid.
+	pub fn new(template: &str) -> Result<IriTemplate, Error> {
+		let mut parts = Vec::new();
+		let mut rest = template;
+
+		while !rest.is_empty() {
+			match rest.find('{') {
+				Some(0) => {
+					let end = rest.find('}').ok_or(Error::InvalidTemplate)?;
+					parts.push(parse_expression(&rest[1..end])?);
+					rest = &rest[(end + 1)..]
+				}
+				Some(start) => {
+					parts.push(parse_literal(&rest[..start])?);
+					rest = &rest[start..]
+				}
+				None => {
+					parts.push(parse_literal(rest)?);
+					rest = ""
+				}
+			}
+		}
+
+		Ok(IriTemplate {
+			source: template.to_string(),
+			parts,
+		})
+	}
+
+	/// Returns the source of the template.
+	#[inline]
+	pub fn as_str(&self) -> &str {
+		&self.source
+	}
+
+	/// Returns the names of the variables appearing in the template, in order.
+	///
+	/// A variable appearing in multiple expressions is listed multiple times.
+	pub fn variables(&self) -> impl Iterator<Item = &str> {
+		self.parts
+			.iter()
+			.flat_map(|part| match part {
+				Part::Literal(_) => [].iter(),
+				Part::Expression(_, vars) => vars.iter(),
+			})
+			.map(|var| var.name.as_str())
+	}
+
+	/// Expands the template into an IRI, using the given variables.
+	///
+	/// Variables missing from `vars` are undefined, and are ignored by the expansion.
+	/// Returns [`Error::InvalidTemplate`] if a prefix modifier is applied to a list or
+	/// associative array, or a parsing error if the result is not a valid IRI.
+	pub fn expand(&self, vars: &HashMap<String, TemplateValue>) -> Result<IriBuf, Error> {
+		let iri_ref = self.expand_ref(vars)?;
+		if iri_ref.scheme().is_some() {
+			Ok(IriBuf(iri_ref))
+		} else {
+			Err(Error::MissingScheme)
+		}
+	}
+
+	/// Expands the template into an IRI reference, using the given variables.
+	///
+	/// See [`IriTemplate::expand`].
+	pub fn expand_ref(&self, vars: &HashMap<String, TemplateValue>) -> Result<IriRefBuf, Error> {
+		let mut output = String::new();
+
+		for part in &self.parts {
+			match part {
+				Part::Literal(literal) => output.push_str(literal),
+				Part::Expression(operator, specs) => {
+					expand_expression(*operator, specs, vars, &mut output)?
+				}
+			}
+		}
+
+		IriRefBuf::from_string(output).map_err(|(e, _)| e)
+	}
+}
+
+impl FromStr for IriTemplate {
+	type Err = Error;
+
+	#[inline]
+	fn from_str(s: &str) -> Result<IriTemplate, Error> {
+		IriTemplate::new(s)
+	}
+}
+
+impl fmt::Display for IriTemplate {
+	#[inline]
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		self.source.fmt(f)
+	}
+}
+
+impl fmt::Debug for IriTemplate {
+	#[inline]
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		self.source.fmt(f)
+	}
+}
+
+/// Checks if the given character is allowed in a template literal.
+fn is_literal_char(c: char) -> bool {
+	!(c.is_control()
+		|| matches!(
+			c,
+			' ' | '"' | '\'' | '<' | '>' | '\\' | '^' | '`' | '{' | '|' | '}'
+		))
+}
+
+fn parse_literal(literal: &str) -> Result<Part, Error> {
+	if literal.chars().all(is_literal_char) {
+		let mut output = String::new();
+		encode(literal, true, &mut output);
+		Ok(Part::Literal(output))
+	} else {
+		Err(Error::InvalidTemplate)
+	}
+}
+
+fn parse_expression(expression: &str) -> Result<Part, Error> {
+	let mut chars = expression.chars();
+	let operator = match chars.next() {
+		Some('+') => Operator::Reserved,
+		Some('#') => Operator::Fragment,
+		Some('.') => Operator::Label,
+		Some('/') => Operator::PathSegment,
+		Some(';') => Operator::PathParameter,
+		Some('?') => Operator::Query,
+		Some('&') => Operator::QueryContinuation,
+		_ => Operator::Simple,
+	};
+
+	let var_list = if operator == Operator::Simple {
+		expression
+	} else {
+		chars.as_str()
+	};
+
+	let specs = var_list
+		.split(',')
+		.map(parse_varspec)
+		.collect::<Result<_, _>>()?;
+
+	Ok(Part::Expression(operator, specs))
+}
+
+fn parse_varspec(spec: &str) -> Result<VarSpec, Error> {
+	let (name, modifier) = if let Some(name) = spec.strip_suffix('*') {
+		(name, Modifier::Explode)
+	} else if let Some(i) = spec.find(':') {
+		let max_len = &spec[(i + 1)..];
+		if max_len.is_empty()
+			|| max_len.len() > 4
+			|| max_len.starts_with('0')
+			|| !max_len.bytes().all(|b| b.is_ascii_digit())
+		{
+			return Err(Error::InvalidTemplate);
+		}
+
+		(&spec[..i], Modifier::Prefix(max_len.parse().unwrap()))
+	} else {
+		(spec, Modifier::None)
+	};
+
+	if is_varname(name) {
+		Ok(VarSpec {
+			name: name.to_string(),
+			modifier,
+		})
+	} else {
+		Err(Error::InvalidTemplate)
+	}
+}
+
+/// Checks if the given string is a valid variable name.
+fn is_varname(name: &str) -> bool {
+	let bytes = name.as_bytes();
+	let mut i = 0;
+	let mut previous_dot = true;
+
+	while i < bytes.len() {
+		match bytes[i] {
+			b'.' if !previous_dot => previous_dot = true,
+			b'%' if i + 2 < bytes.len()
+				&& bytes[i + 1].is_ascii_hexdigit()
+				&& bytes[i + 2].is_ascii_hexdigit() =>
+			{
+				previous_dot = false;
+				i += 2
+			}
+			b if b.is_ascii_alphanumeric() || b == b'_' => previous_dot = false,
+			_ => return false,
+		}
+
+		i += 1
+	}
+
+	!previous_dot
+}
+
+/// Append the given string to `output`, percent-encoding the characters that are not
+/// unreserved (or reserved, if `allow_reserved` is `true`).
+///
+/// When `allow_reserved` is `true`, percent-encoded triplets are left untouched.
+fn encode(input: &str, allow_reserved: bool, output: &mut String) {
+	let bytes = input.as_bytes();
+
+	for (i, c) in input.char_indices() {
+		let allowed = parsing::is_unreserved(c)
+			|| (allow_reserved
+				&& (parsing::is_subdelim(c)
+					|| matches!(c, ':' | '/' | '?' | '#' | '[' | ']' | '@')
+					|| (c == '%'
+						&& i + 2 < bytes.len()
+						&& bytes[i + 1].is_ascii_hexdigit()
+						&& bytes[i + 2].is_ascii_hexdigit())));
+
+		if allowed {
+			output.push(c)
+		} else {
+			let mut buffer = [0; 4];
+			for b in c.encode_utf8(&mut buffer).bytes() {
+				write!(output, "%{:02X}", b).unwrap();
+			}
+		}
+	}
+}
+
+/// Append the given value to `output`, truncated to `max_len` characters if any.
+fn encode_value(value: &str, max_len: Option<usize>, allow_reserved: bool, output: &mut String) {
+	match max_len.and_then(|max_len| value.char_indices().nth(max_len)) {
+		Some((end, _)) => encode(&value[..end], allow_reserved, output),
+		None => encode(value, allow_reserved, output),
+	}
+}
+
+/// Append `name=value` (or `name` followed by the operator `ifemp` string if `value` is empty)
+/// to `output`.
+fn encode_named(
+	operator: Operator,
+	name: &str,
+	value: &str,
+	max_len: Option<usize>,
+	output: &mut String,
+) {
+	output.push_str(name);
+	if value.is_empty() {
+		output.push_str(operator.if_empty())
+	} else {
+		output.push('=');
+		encode_value(value, max_len, operator.allows_reserved(), output)
+	}
+}
+
+/// Expands a template expression.
+///
+/// See [RFC 6570 appendix A](https://tools.ietf.org/html/rfc6570#appendix-A).
+fn expand_expression(
+	operator: Operator,
+	specs: &[VarSpec],
+	vars: &HashMap<String, TemplateValue>,
+	output: &mut String,
+) -> Result<(), Error> {
+	let allow_reserved = operator.allows_reserved();
+	let separator = operator.separator();
+	let mut first = true;
+
+	for spec in specs {
+		let value = match vars.get(&spec.name) {
+			Some(value) if value.is_defined() => value,
+			_ => continue,
+		};
+
+		if first {
+			output.push_str(operator.first());
+			first = false
+		} else {
+			output.push(separator)
+		}
+
+		match (value, spec.modifier) {
+			(TemplateValue::String(value), modifier) => {
+				let max_len = match modifier {
+					Modifier::Prefix(max_len) => Some(max_len),
+					_ => None,
+				};
+
+				if operator.is_named() {
+					encode_named(operator, &spec.name, value, max_len, output)
+				} else {
+					encode_value(value, max_len, allow_reserved, output)
+				}
+			}
+			(_, Modifier::Prefix(_)) => return Err(Error::InvalidTemplate),
+			(TemplateValue::List(items), Modifier::Explode) => {
+				for (i, item) in items.iter().enumerate() {
+					if i > 0 {
+						output.push(separator)
+					}
+
+					if operator.is_named() {
+						encode_named(operator, &spec.name, item, None, output)
+					} else {
+						encode(item, allow_reserved, output)
+					}
+				}
+			}
+			(TemplateValue::Map(entries), Modifier::Explode) => {
+				for (i, (key, value)) in entries.iter().enumerate() {
+					if i > 0 {
+						output.push(separator)
+					}
+
+					if operator.is_named() {
+						// Unlike variable names, keys are arbitrary strings.
+						let mut encoded_key = String::new();
+						encode(key, allow_reserved, &mut encoded_key);
+						encode_named(operator, &encoded_key, value, None, output)
+					} else {
+						encode(key, allow_reserved, output);
+						output.push('=');
+						encode(value, allow_reserved, output)
+					}
+				}
+			}
+			(value, _) => {
+				if operator.is_named() {
+					output.push_str(&spec.name);
+					output.push('=');
+				}
+
+				let items: Vec<&str> = match value {
+					TemplateValue::List(items) => items.iter().map(String::as_str).collect(),
+					TemplateValue::Map(entries) => entries
+						.iter()
+						.flat_map(|(key, value)| vec![key.as_str(), value.as_str()])
+						.collect(),
+					TemplateValue::String(_) => unreachable!(),
+				};
+
+				for (i, item) in items.into_iter().enumerate() {
+					if i > 0 {
+						output.push(',')
+					}
+
+					encode(item, allow_reserved, output)
+				}
+			}
+		}
+	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn vars() -> HashMap<String, TemplateValue> {
+		let mut vars = HashMap::new();
+		let mut insert = |name: &str, value: TemplateValue| {
+			vars.insert(name.to_string(), value);
+		};
+
+		insert("var", "value".into());
+		insert("hello", "Hello World!".into());
+		insert("path", "/foo/bar".into());
+		insert("empty", "".into());
+		insert("x", "1024".into());
+		insert("y", "768".into());
+		insert(
+			"list",
+			vec!["red".to_string(), "green".to_string(), "blue".to_string()].into(),
+		);
+		insert(
+			"keys",
+			vec![
+				("semi".to_string(), ";".to_string()),
+				("dot".to_string(), ".".to_string()),
+				("comma".to_string(), ",".to_string()),
+			]
+			.into(),
+		);
+		insert("empty_keys", TemplateValue::Map(Vec::new()));
+		insert(
+			"unsafe_keys",
+			vec![
+				("a&b".to_string(), "c".to_string()),
+				("a b".to_string(), "".to_string()),
+			]
+			.into(),
+		);
+		vars
+	}
+
+	fn expand(template: &str) -> String {
+		IriTemplate::new(template)
+			.unwrap()
+			.expand_ref(&vars())
+			.unwrap()
+			.as_str()
+			.to_string()
+	}
+
+	#[test]
+	fn level1() {
+		assert_eq!(expand("{var}"), "value");
+		assert_eq!(expand("{hello}"), "Hello%20World%21");
+	}
+
+	#[test]
+	fn level2() {
+		assert_eq!(expand("{+var}"), "value");
+		assert_eq!(expand("{+hello}"), "Hello%20World!");
+		assert_eq!(expand("{+path}/here"), "/foo/bar/here");
+		assert_eq!(expand("here?ref={+path}"), "here?ref=/foo/bar");
+		assert_eq!(expand("X{#var}"), "X#value");
+		assert_eq!(expand("X{#hello}"), "X#Hello%20World!");
+	}
+
+	#[test]
+	fn level3() {
+		assert_eq!(expand("map?{x,y}"), "map?1024,768");
+		assert_eq!(expand("{x,hello,y}"), "1024,Hello%20World%21,768");
+		assert_eq!(expand("{+x,hello,y}"), "1024,Hello%20World!,768");
+		assert_eq!(expand("{+path,x}/here"), "/foo/bar,1024/here");
+		assert_eq!(expand("{#x,hello,y}"), "#1024,Hello%20World!,768");
+		assert_eq!(expand("X{.var}"), "X.value");
+		assert_eq!(expand("X{.x,y}"), "X.1024.768");
+		assert_eq!(expand("{/var}"), "/value");
+		assert_eq!(expand("{/var,x}/here"), "/value/1024/here");
+		assert_eq!(expand("{;x,y}"), ";x=1024;y=768");
+		assert_eq!(expand("{;x,y,empty}"), ";x=1024;y=768;empty");
+		assert_eq!(expand("{?x,y}"), "?x=1024&y=768");
+		assert_eq!(expand("{?x,y,empty}"), "?x=1024&y=768&empty=");
+		assert_eq!(expand("?fixed=yes{&x}"), "?fixed=yes&x=1024");
+		assert_eq!(expand("{&x,y,empty}"), "&x=1024&y=768&empty=");
+	}
+
+	#[test]
+	fn level4() {
+		assert_eq!(expand("{var:3}"), "val");
+		assert_eq!(expand("{var:30}"), "value");
+		assert_eq!(expand("{list}"), "red,green,blue");
+		assert_eq!(expand("{list*}"), "red,green,blue");
+		assert_eq!(expand("{keys}"), "semi,%3B,dot,.,comma,%2C");
+		assert_eq!(expand("{keys*}"), "semi=%3B,dot=.,comma=%2C");
+		assert_eq!(expand("{+path:6}/here"), "/foo/b/here");
+		assert_eq!(expand("{+keys*}"), "semi=;,dot=.,comma=,");
+		assert_eq!(expand("{/list*,path:4}"), "/red/green/blue/%2Ffoo");
+		assert_eq!(expand("{;list*}"), ";list=red;list=green;list=blue");
+		assert_eq!(expand("{;keys*}"), ";semi=%3B;dot=.;comma=%2C");
+		assert_eq!(expand("{?list}"), "?list=red,green,blue");
+		assert_eq!(expand("{?keys*}"), "?semi=%3B&dot=.&comma=%2C");
+		assert_eq!(expand("{&list*}"), "&list=red&list=green&list=blue");
+		assert_eq!(expand("{?empty_keys,undefined}"), "");
+	}
+
+	#[test]
+	fn exploded_keys_are_encoded() {
+		assert_eq!(expand("{?unsafe_keys*}"), "?a%26b=c&a%20b=");
+		assert_eq!(expand("{;unsafe_keys*}"), ";a%26b=c;a%20b");
+		assert_eq!(expand("{&unsafe_keys*}"), "&a%26b=c&a%20b=");
+		assert_eq!(expand("{unsafe_keys*}"), "a%26b=c,a%20b=");
+	}
+
+	#[test]
+	fn invalid() {
+		assert!(IriTemplate::new("{var").is_err());
+		assert!(IriTemplate::new("{var:0}").is_err());
+		assert!(IriTemplate::new("{var:10000}").is_err());
+		assert!(IriTemplate::new("{va r}").is_err());
+		assert!(IriTemplate::new("{=var}").is_err());
+		assert!(IriTemplate::new("a b").is_err());
+		assert!(IriTemplate::new("{list:3}")
+			.unwrap()
+			.expand_ref(&vars())
+			.is_err());
+		assert_eq!(
+			IriTemplate::new("/{var}").unwrap().expand(&vars()),
+			Err(Error::MissingScheme)
+		);
+	}
+}
@@ -0,0 +1,378 @@
+//! Parsing following the [WHATWG URL Standard](https://url.spec.whatwg.org/).
+//!
+//! The default parser of this crate strictly follows RFC 3986 and RFC 3987,
+//! which is not how web browsers parse URLs.
+//! This module provides a [`parse`] function that mimics the WHATWG parsing algorithm,
+//! for use in browser-like components.
+//! The input is rewritten into a valid IRI, which is then stored using the usual [`IriBuf`] type.
+//!
+//! ## Differences with the strict parser
+//!
+//! - Leading and trailing C0 control characters and spaces are removed,
+//!   and ASCII tabs and newlines are removed everywhere.
+//! - The scheme and the host of special schemes (`ftp`, `file`, `http`, `https`, `ws` and `wss`)
+//!   are lowercased.
+//! - In special schemes, `\` is treated as `/` before the query,
+//!   and any number of slashes is accepted after the scheme (`http:example.com` is
+//!   `http://example.com/`).
+//! - Characters that are not allowed in a component are percent-encoded instead of being
+//!   rejected, including a lone `%` (encoded `%25`), and every `@` but the last one in the authority.
+//! - Default ports are removed and ports are serialized without leading zeros.
+//! - Special schemes always have a non-empty path (`http://example.com` is
+//!   `http://example.com/`).
+//! - Percent-encoded dot segments (`%2e`, `.%2e`, etc.) are treated as dot segments.
+//!
+//! ## Differences with the WHATWG URL Standard
+//!
+//! Since the output is an IRI, this is only an approximation of the standard:
+//!
+//! - Non-ASCII characters allowed in IRIs are preserved instead of being percent-encoded,
+//!   and hosts are not converted using IDNA.
+//! - IPv4 hosts in non-decimal notations (such as `0x7f.1`) are not recognized.
+//! - Opaque paths (such as `blank` in `about:blank`) are checked against the IRI grammar.
+use std::convert::TryFrom;
+
+use crate::{parsing, Error, Iri, IriBuf, IriRefBuf, Path, Scheme};
+
+/// Special schemes, as defined by the WHATWG URL Standard.
+const SPECIAL_SCHEMES: [&str; 6] = ["ftp", "file", "http", "https", "ws", "wss"];
+
+#[inline]
+fn is_special(scheme: &str) -> bool {
+	SPECIAL_SCHEMES.contains(&scheme)
+}
+
+#[inline]
+fn default_port(scheme: &str) -> Option<u16> {
+	Scheme::try_from(scheme)
+		.ok()
+		.and_then(|scheme| scheme.default_port())
+}
+
+/// Splits the scheme from the rest of the input, if any.
+fn split_scheme(input: &str) -> Option<(&str, &str)> {
+	let len = parsing::parse_scheme(input.as_bytes(), 0).ok()?;
+	if len > 0 && input.as_bytes().get(len) == Some(&b':') {
+		Some((&input[0..len], &input[(len + 1)..]))
+	} else {
+		None
+	}
+}
+
+/// Push the given component to the output, percent-encoding every character not `allowed`.
+fn push_encoded<F: Fn(char) -> bool>(output: &mut String, component: &str, allowed: F) {
+	let bytes = component.as_bytes();
+
+	for (i, c) in component.char_indices() {
+		if c == '%' {
+			let is_hex = |j: usize| bytes.get(j).map(u8::is_ascii_hexdigit).unwrap_or(false);
+			if is_hex(i + 1) && is_hex(i + 2) {
+				output.push('%')
+			} else {
+				output.push_str("%25")
+			}
+		} else if allowed(c) {
+			output.push(c)
+		} else {
+			let mut buffer = [0; 4];
+			for byte in c.encode_utf8(&mut buffer).bytes() {
+				output.push_str(&format!("%{:02X}", byte))
+			}
+		}
+	}
+}
+
+#[inline]
+fn is_userinfo_char(c: char) -> bool {
+	c == ':' || parsing::is_subdelim(c) || parsing::is_unreserved(c)
+}
+
+#[inline]
+fn is_path_char(c: char) -> bool {
+	c == ':' || c == '@' || c == '/' || parsing::is_subdelim(c) || parsing::is_unreserved(c)
+}
+
+#[inline]
+fn is_query_char(c: char) -> bool {
+	is_path_char(c) || c == '?' || parsing::is_private(c)
+}
+
+#[inline]
+fn is_fragment_char(c: char) -> bool {
+	is_path_char(c) || c == '?'
+}
+
+/// Push the authority to the output, normalizing it along the way.
+fn push_authority(output: &mut String, authority: &str, scheme: &str) -> Result<(), Error> {
+	let special = is_special(scheme);
+
+	let (userinfo, host_port) = match authority.rfind('@') {
+		Some(i) => (&authority[0..i], &authority[(i + 1)..]),
+		None => ("", authority),
+	};
+
+	let (host, port) = match host_port.rfind(':') {
+		Some(i) if !host_port[i..].contains(']') => (&host_port[0..i], Some(&host_port[(i + 1)..])),
+		_ => (host_port, None),
+	};
+
+	if !userinfo.is_empty() {
+		push_encoded(output, userinfo, is_userinfo_char);
+		output.push('@');
+	}
+
+	let host = if special {
+		host.to_ascii_lowercase()
+	} else {
+		host.to_string()
+	};
+
+	if parsing::parse_host(host.as_bytes(), 0)? != host.len() {
+		return Err(Error::InvalidHost);
+	}
+
+	if scheme == "file" {
+		if host != "localhost" {
+			output.push_str(&host)
+		}
+	} else if special && host.is_empty() {
+		return Err(Error::InvalidHost);
+	} else {
+		output.push_str(&host)
+	}
+
+	if let Some(port) = port.filter(|port| !port.is_empty()) {
+		if !port.bytes().all(|c| c.is_ascii_digit()) {
+			return Err(Error::InvalidPort);
+		}
+
+		let port = match port.trim_start_matches('0') {
+			"" => 0,
+			port => port.parse::<u16>().map_err(|_| Error::InvalidPort)?,
+		};
+
+		if default_port(scheme) != Some(port) {
+			output.push(':');
+			output.push_str(&port.to_string());
+		}
+	}
+
+	Ok(())
+}
+
+/// Push the path to the output, percent-encoding it and decoding percent-encoded dot segments.
+fn push_path(output: &mut String, path: &str) {
+	let mut encoded = String::new();
+	push_encoded(&mut encoded, path, is_path_char);
+
+	for (i, segment) in encoded.split('/').enumerate() {
+		if i > 0 {
+			output.push('/')
+		}
+
+		match segment.to_ascii_lowercase().as_str() {
+			"%2e" => output.push('.'),
+			".%2e" | "%2e." | "%2e%2e" => output.push_str(".."),
+			_ => output.push_str(segment),
+		}
+	}
+}
+
+/// Parse the input according to the WHATWG URL Standard, against an optional base IRI.
+///
+/// See the [module documentation](self) for the differences with the strict parser.
+///
+/// # Example
+///
+/// ```
+/// # use iref::{Iri, whatwg};
+/// let iri = whatwg::parse(" HTTP://Example.COM:80\\foo bar ", None).unwrap();
+/// assert_eq!(iri.as_str(), "http://example.com/foo%20bar");
+///
+/// let base = Iri::new("https://example.com/a/b").unwrap();
+/// let iri = whatwg::parse("https:c", Some(base)).unwrap();
+/// assert_eq!(iri.as_str(), "https://example.com/a/c");
+/// ```
+pub fn parse(input: &str, base: Option<Iri>) -> Result<IriBuf, Error> {
+	let input: String = input
+		.trim_matches(|c: char| c <= ' ')
+		.chars()
+		.filter(|c| !matches!(c, '\t' | '\n' | '\r'))
+		.collect();
+
+	let base_scheme = base.map(|base| base.scheme().as_str().to_ascii_lowercase());
+	let (mut scheme, rest) = match split_scheme(&input) {
+		Some((scheme, rest)) => (Some(scheme.to_ascii_lowercase()), rest),
+		None => (None, input.as_str()),
+	};
+
+	let effective_scheme = match scheme.as_ref().or(base_scheme.as_ref()) {
+		Some(scheme) => scheme.clone(),
+		None => return Err(Error::MissingScheme),
+	};
+	let special = is_special(&effective_scheme);
+
+	let mut rest = if special {
+		let end = rest.find(['?', '#']).unwrap_or(rest.len());
+		rest[0..end].replace('\\', "/") + &rest[end..]
+	} else {
+		rest.to_string()
+	};
+
+	if special {
+		if effective_scheme == "file" {
+			if scheme.is_some() && !rest.starts_with("//") {
+				if !rest.starts_with('/') {
+					rest.insert(0, '/')
+				}
+				rest.insert_str(0, "//")
+			}
+		} else if scheme.is_some() && base_scheme != scheme && !rest.starts_with('/') {
+			rest.insert_str(0, "//")
+		} else if (scheme.is_some() && base_scheme != scheme) || rest.starts_with("//") {
+			// any number of slashes is allowed before the authority.
+			rest = format!("//{}", rest.trim_start_matches('/'))
+		} else if scheme.is_some() {
+			// relative reference with a scheme.
+			scheme = None
+		}
+	}
+
+	let mut reference = String::new();
+	if let Some(scheme) = scheme.as_ref() {
+		reference.push_str(scheme);
+		reference.push(':');
+	}
+
+	let mut rest = rest.as_str();
+
+	let has_authority = rest.starts_with("//");
+	if has_authority {
+		let end = rest[2..]
+			.find(['/', '?', '#'])
+			.map(|i| i + 2)
+			.unwrap_or(rest.len());
+		reference.push_str("//");
+		push_authority(&mut reference, &rest[2..end], &effective_scheme)?;
+		rest = &rest[end..];
+	}
+
+	let (rest, fragment) = match rest.find('#') {
+		Some(i) => (&rest[0..i], Some(&rest[(i + 1)..])),
+		None => (rest, None),
+	};
+
+	let (path, query) = match rest.find('?') {
+		Some(i) => (&rest[0..i], Some(&rest[(i + 1)..])),
+		None => (rest, None),
+	};
+
+	if scheme.is_none()
+		&& !has_authority
+		&& !path.starts_with('/')
+		&& path.split('/').next().unwrap().contains(':')
+	{
+		// make sure the first segment is not confused with a scheme.
+		reference.push_str("./")
+	}
+
+	push_path(&mut reference, path);
+
+	if let Some(query) = query {
+		reference.push('?');
+		push_encoded(&mut reference, query, |c| {
+			is_query_char(c) && !(special && c == '\'')
+		});
+	}
+
+	if let Some(fragment) = fragment {
+		reference.push('#');
+		push_encoded(&mut reference, fragment, is_fragment_char);
+	}
+
+	let mut iri_ref = IriRefBuf::new(&reference)?;
+	let mut iri = if iri_ref.scheme().is_some() {
+		iri_ref.path_mut().normalize();
+		IriBuf(iri_ref)
+	} else {
+		match base {
+			Some(base) => iri_ref.resolved(base),
+			None => return Err(Error::MissingScheme),
+		}
+	};
+
+	if iri.scheme().as_str() != effective_scheme {
+		iri.set_scheme(Scheme::try_from(effective_scheme.as_str()).unwrap());
+	}
+
+	if special && iri.authority().is_some() && iri.path().as_bytes().is_empty() {
+		iri.set_path(Path::try_from("/").unwrap());
+	}
+
+	Ok(iri)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::parse;
+	use crate::{Error, Iri};
+
+	#[test]
+	fn absolute() {
+		let tests = [
+			("HTTP://Example.COM:80/a/../b", "http://example.com/b"),
+			(
+				"  http://example.com\\foo\\bar?a\\b  ",
+				"http://example.com/foo/bar?a%5Cb",
+			),
+			("http:example.com", "http://example.com/"),
+			("https:///example.com:0443", "https://example.com/"),
+			(
+				"https://example.com/a b?c d'#e f",
+				"https://example.com/a%20b?c%20d%27#e%20f",
+			),
+			("http://user@name@host/", "http://user%40name@host/"),
+			("http://host/%2e%2E/a/%2e/b", "http://host/a/b"),
+			("http://host/100%", "http://host/100%25"),
+			("file:/foo", "file:///foo"),
+			("file://localhost/foo", "file:///foo"),
+			("about:blank", "about:blank"),
+			("foo://Host/a\\b", "foo://Host/a%5Cb"),
+		];
+
+		for (input, expected) in &tests {
+			assert_eq!(parse(input, None).unwrap().as_str(), *expected)
+		}
+	}
+
+	#[test]
+	fn relative() {
+		let base = Iri::new("http://a/b/c/d;p?q").unwrap();
+		let tests = [
+			("../x", "http://a/b/x"),
+			("http:g", "http://a/b/c/g"),
+			("\\\\other\\path", "http://other/path"),
+			("?y z", "http://a/b/c/d;p?y%20z"),
+			("a:b", "a:b"),
+			("../../../g", "http://a/g"),
+		];
+
+		for (input, expected) in &tests {
+			assert_eq!(parse(input, Some(base)).unwrap().as_str(), *expected)
+		}
+	}
+
+	#[test]
+	fn invalid() {
+		assert_eq!(parse("foo", None).err(), Some(Error::MissingScheme));
+		assert_eq!(
+			parse("http://ex ample.com", None).err(),
+			Some(Error::InvalidHost)
+		);
+		assert_eq!(
+			parse("http://example.com:99999", None).err(),
+			Some(Error::InvalidPort)
+		);
+		assert_eq!(parse("http://", None).err(), Some(Error::InvalidHost));
+	}
+}
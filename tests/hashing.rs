@@ -0,0 +1,149 @@
+extern crate iref;
+
+use iref::{Iri, IriBuf, IriRef, IriRefBuf, Path, Scheme};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::convert::{TryFrom, TryInto};
+use std::hash::{Hash, Hasher};
+
+fn hash<T: Hash>(value: &T) -> u64 {
+	let mut hasher = DefaultHasher::new();
+	value.hash(&mut hasher);
+	hasher.finish()
+}
+
+/// Checks that all the given IRIs are equal, hash identically and have the parsing
+/// data of their freshly parsed bytes.
+fn assert_consistent(expected: &str, iris: &[IriBuf]) {
+	let parsed = IriBuf::new(expected).unwrap();
+
+	for iri in iris {
+		assert_eq!(iri.as_str(), expected);
+		assert_eq!(
+			iri.as_iri_ref().parsing_data(),
+			parsed.as_iri_ref().parsing_data()
+		);
+		assert_eq!(*iri, parsed);
+		assert_eq!(hash(iri), hash(&parsed));
+		assert_eq!(hash(&iri.as_iri()), hash(&parsed));
+		assert_eq!(hash(&iri.as_iri_ref()), hash(&parsed));
+	}
+
+	let set: HashSet<IriBuf> = iris.iter().cloned().collect();
+	assert_eq!(set.len(), 1);
+}
+
+#[test]
+fn construction_paths() {
+	let expected = "http://user@example.org:8080/a/b?q=1#f";
+
+	let from_string = IriBuf::from_string(expected.to_string()).unwrap();
+	let from_bytes = IriBuf::new(expected.as_bytes()).unwrap();
+	let from_vec = IriBuf::from_vec(expected.as_bytes().to_vec()).unwrap();
+	let from_iri = IriBuf::from(Iri::new(expected).unwrap());
+
+	let mut from_setters = IriBuf::from_scheme(Scheme::try_from("http").unwrap());
+	from_setters.set_host("example.org").unwrap();
+	from_setters.set_port(Some("8080")).unwrap();
+	from_setters.set_userinfo(Some("user")).unwrap();
+	from_setters.set_path(Path::try_from("/a/b").unwrap());
+	from_setters.set_query_str(Some("q=1")).unwrap();
+	from_setters.set_fragment_str(Some("f")).unwrap();
+
+	let mut from_segments = IriBuf::new("http://user@example.org:8080").unwrap();
+	from_segments.path_mut().push("a".try_into().unwrap());
+	from_segments.path_mut().push("b".try_into().unwrap());
+	from_segments.set_query_str(Some("q=1")).unwrap();
+	from_segments.set_fragment_str(Some("f")).unwrap();
+
+	let mut from_reference = IriRefBuf::new("//user@example.org:8080/a/b?q=1#f").unwrap();
+	from_reference.set_scheme(Some(Scheme::try_from("http").unwrap()));
+	let from_reference = IriBuf::try_from(from_reference).unwrap();
+
+	let from_resolution = IriRef::new("b?q=1#f")
+		.unwrap()
+		.resolved(Iri::new("http://user@example.org:8080/a/c?x#y").unwrap());
+
+	let mut from_edits = IriBuf::new("ftp://other@example.com/x/y/z?r#g").unwrap();
+	from_edits.set_scheme(Scheme::try_from("http").unwrap());
+	from_edits.set_host("example.org").unwrap();
+	from_edits.set_port(Some("8080")).unwrap();
+	from_edits.set_userinfo(Some("user")).unwrap();
+	from_edits.path_mut().pop();
+	from_edits.path_mut().pop();
+	from_edits.path_mut().pop();
+	from_edits.path_mut().push("a".try_into().unwrap());
+	from_edits.path_mut().push("b".try_into().unwrap());
+	from_edits.set_query_str(Some("q=1")).unwrap();
+	from_edits.set_fragment_str(Some("f")).unwrap();
+
+	assert_consistent(
+		expected,
+		&[
+			from_string,
+			from_bytes,
+			from_vec,
+			from_iri,
+			from_setters,
+			from_segments,
+			from_reference,
+			from_resolution,
+			from_edits,
+		],
+	);
+}
+
+#[test]
+fn empty_components() {
+	let expected = "s://@:/?#";
+
+	let mut from_setters = IriBuf::from_scheme(Scheme::try_from("s").unwrap());
+	from_setters.set_userinfo(Some("")).unwrap();
+	from_setters.set_port(Some("")).unwrap();
+	from_setters.set_path(Path::try_from("/").unwrap());
+	from_setters.set_query_str(Some("")).unwrap();
+	from_setters.set_fragment_str(Some("")).unwrap();
+
+	let mut from_removal = IriBuf::new("s://u@h:1/p?q#f").unwrap();
+	from_removal.set_userinfo(Some("")).unwrap();
+	from_removal.set_host("").unwrap();
+	from_removal.set_port(Some("")).unwrap();
+	from_removal.set_path(Path::try_from("/").unwrap());
+	from_removal.set_query_str(Some("")).unwrap();
+	from_removal.set_fragment_str(Some("")).unwrap();
+
+	assert_consistent(
+		expected,
+		&[
+			IriBuf::from_string(expected.to_string()).unwrap(),
+			IriBuf::new(expected.as_bytes()).unwrap(),
+			from_setters,
+			from_removal,
+		],
+	);
+}
+
+#[test]
+fn removed_components() {
+	let expected = "s:p";
+
+	let mut from_removal = IriBuf::new("s://u@h:1/p?q#f").unwrap();
+	from_removal.set_authority(None);
+	from_removal.set_path(Path::try_from("p").unwrap());
+	from_removal.set_query(None);
+	from_removal.set_fragment(None);
+
+	let mut from_opaque =
+		IriBuf::from_scheme_and_opaque(Scheme::try_from("s").unwrap(), "p").unwrap();
+	from_opaque.set_query_str(Some("q")).unwrap();
+	from_opaque.set_query_str(None).unwrap();
+
+	assert_consistent(
+		expected,
+		&[
+			IriBuf::from_string(expected.to_string()).unwrap(),
+			from_removal,
+			from_opaque,
+		],
+	);
+}
@@ -1,6 +1,7 @@
 extern crate iref;
 
-use iref::Iri;
+use iref::parsing::ParseOptions;
+use iref::{Iri, IriBuf, IriRef, IriRefBuf};
 
 #[test]
 fn test1() {
@@ -101,3 +102,1150 @@ fn invalid_hash_in_fragment2() {
 		Err(iref::Error::InvalidFragment)
 	)
 }
+
+#[test]
+fn parse_options_max_len() {
+	let options = ParseOptions {
+		max_len: Some(10),
+		..ParseOptions::default()
+	};
+
+	assert!(IriRef::new_with_options("http:/a/b", &options).is_ok());
+	assert_eq!(
+		Iri::new_with_options("http://example.com", &options),
+		Err(iref::Error::TooLong)
+	)
+}
+
+#[test]
+fn parse_options_max_segments() {
+	let options = ParseOptions {
+		max_segments: Some(3),
+		..ParseOptions::default()
+	};
+
+	assert!(Iri::new_with_options("http://a/b/c?d/e/f", &options).is_ok());
+	assert!(IriRef::new_with_options("/a/b/c", &options).is_ok());
+	assert!(IriRef::new_with_options("a//c", &options).is_ok());
+	assert!(IriRef::new_with_options("/a/b/c/", &options).is_ok());
+	assert_eq!(
+		IriRef::new_with_options("/a/b/c/d", &options).err(),
+		Some(iref::Error::TooManySegments)
+	);
+	assert_eq!(
+		IriRefBuf::new_with_options("../../../../g", &options).err(),
+		Some(iref::Error::TooManySegments)
+	);
+
+	let options = ParseOptions {
+		max_segments: Some(0),
+		..ParseOptions::default()
+	};
+
+	assert!(IriRef::new_with_options("", &options).is_ok());
+	assert!(Iri::new_with_options("http://a", &options).is_ok());
+	assert!(Iri::new_with_options("http://a/", &options).is_ok());
+	assert!(IriRef::new_with_options("/", &options).is_ok());
+	assert_eq!(
+		IriRef::new_with_options("a", &options).err(),
+		Some(iref::Error::TooManySegments)
+	);
+	assert_eq!(
+		Iri::new_with_options("http://a/b", &options).err(),
+		Some(iref::Error::TooManySegments)
+	);
+}
+
+#[test]
+fn compare_bytes() {
+	let iri = Iri::new("http://example.org/foo").unwrap();
+	let iri_ref = IriRefBuf::new("../foo").unwrap();
+
+	assert_eq!(iri, b"http://example.org/foo"[..]);
+	assert_ne!(iri, b"http://example.org/bar"[..]);
+	assert_ne!(iri, b"http://example.org/\xff"[..]);
+	assert_eq!(iri.to_owned(), b"http://example.org/foo"[..]);
+	assert_eq!(iri_ref, b"../foo"[..]);
+	assert_ne!(iri_ref, b"\xc3"[..]);
+
+	let bytes: &[u8] = b"http://example.org/foo";
+	assert_eq!(iri, bytes);
+	assert_eq!(iri.to_owned(), bytes);
+}
+
+#[test]
+fn resolve_all() {
+	let base = Iri::new("http://a/b/c/d;p?q").unwrap();
+	let refs = ["g:h", "g", "./g/", "//g", "?y", "", "../..", "/./g"];
+	let resolved: Vec<_> = base
+		.resolve_all(refs.iter().map(|r| IriRef::new(r).unwrap()))
+		.collect();
+
+	assert_eq!(resolved.len(), refs.len());
+	for (r, resolved) in refs.iter().zip(&resolved) {
+		assert_eq!(*resolved, IriRef::new(r).unwrap().resolved(base))
+	}
+}
+
+#[test]
+fn compare_cow() {
+	use std::borrow::Cow;
+
+	let iri_ref_buf = IriRefBuf::new("http://example.org/foo").unwrap();
+	let iri_buf = IriBuf::new("http://example.org/foo").unwrap();
+	let borrowed: Cow<IriRefBuf> = Cow::Borrowed(&iri_ref_buf);
+	let owned: Cow<IriRefBuf> = Cow::Owned(iri_ref_buf.clone());
+	let owned_iri: Cow<IriBuf> = Cow::Owned(iri_buf.clone());
+
+	assert_eq!(borrowed, owned);
+	assert_eq!(borrowed, owned_iri);
+	assert_eq!(owned_iri, borrowed);
+	assert_eq!(borrowed, iri_ref_buf.as_iri_ref());
+	assert_eq!(borrowed, iri_buf.as_iri());
+	assert_eq!(borrowed, iri_ref_buf);
+	assert_eq!(borrowed, iri_buf);
+	assert_eq!(iri_ref_buf.as_iri_ref(), owned);
+	assert_eq!(iri_buf.as_iri(), owned);
+	assert_eq!(iri_ref_buf, owned);
+	assert_eq!(iri_buf, owned);
+	assert_eq!(owned_iri, iri_ref_buf.as_iri_ref());
+	assert_eq!(owned_iri, iri_buf.as_iri());
+	assert_eq!(owned_iri, iri_ref_buf);
+	assert_eq!(owned_iri, iri_buf);
+	assert_eq!(iri_ref_buf.as_iri_ref(), owned_iri);
+	assert_eq!(iri_buf.as_iri(), owned_iri);
+	assert_eq!(iri_ref_buf, owned_iri);
+	assert_eq!(iri_buf, owned_iri);
+	assert_ne!(borrowed, IriRef::new("foo").unwrap());
+}
+
+#[test]
+fn to_ascii() {
+	let iri = Iri::new("http://üser@例え.jp:8080/%C3%A9/é?\u{e000}#ß").unwrap();
+	let (uri, report) = iri.to_ascii();
+
+	#[cfg(not(feature = "idna"))]
+	{
+		assert_eq!(
+			uri.as_str(),
+			"http://%C3%BCser@%E4%BE%8B%E3%81%88.jp:8080/%C3%A9/%C3%A9?%EE%80%80#%C3%9F"
+		);
+		assert_eq!(report.host, 2);
+		assert!(!report.idna);
+	}
+	#[cfg(feature = "idna")]
+	{
+		assert_eq!(
+			uri.as_str(),
+			"http://%C3%BCser@xn--r8jz45g.jp:8080/%C3%A9/%C3%A9?%EE%80%80#%C3%9F"
+		);
+		assert_eq!(report.host, 0);
+		assert!(report.idna);
+	}
+	assert_eq!(report.userinfo, 1);
+	assert_eq!(report.path, 1);
+	assert_eq!(report.query, 1);
+	assert_eq!(report.fragment, 1);
+	assert!(report.is_changed());
+
+	let iri = Iri::new("http://example.org/a?b#c").unwrap();
+	let (uri, report) = iri.to_ascii();
+	assert_eq!(uri, iri);
+	assert!(!report.is_changed());
+
+	// IDNA is only applied to non-ASCII hosts.
+	let iri = Iri::new("http://Ex%C3%A4mple.ORG./").unwrap();
+	let (uri, report) = iri.to_ascii();
+	assert_eq!(uri, iri);
+	assert!(!report.idna);
+}
+
+#[test]
+fn host_and_port() {
+	let iri = IriBuf::new("http://user@[::1]:80/path").unwrap();
+	assert_eq!(iri.host(), Some("[::1]"));
+	assert_eq!(iri.port(), Some("80"));
+
+	let iri = IriBuf::new("http://example.org:/path").unwrap();
+	assert_eq!(iri.host(), Some("example.org"));
+	assert_eq!(iri.port(), Some(""));
+
+	let iri = IriBuf::new("urn:isbn:0451450523").unwrap();
+	assert_eq!(iri.host(), None);
+	assert_eq!(iri.port(), None);
+}
+
+#[test]
+fn fragment_and_query_references() {
+	let base = Iri::new("http://example.org/doc?q#top").unwrap();
+
+	let fragment = IriRefBuf::from_fragment("bottom").unwrap();
+	assert_eq!(fragment.resolved(base), "http://example.org/doc?q#bottom");
+
+	let query = IriRefBuf::from_query("p=2").unwrap();
+	assert_eq!(query.resolved(base), "http://example.org/doc?p=2");
+}
+
+#[test]
+fn try_resolved() {
+	let iri_ref = IriRef::new("http://example.org/a/./b/../c").unwrap();
+	let base = IriRef::new("relative/base").unwrap();
+	assert_eq!(
+		iri_ref.try_resolved(base).unwrap(),
+		"http://example.org/a/c"
+	);
+
+	let iri_ref = IriRefBuf::new("#f").unwrap();
+	assert!(iri_ref.try_resolved(base).is_err());
+	assert_eq!(
+		iri_ref
+			.try_resolved(Iri::new("http://x/y").unwrap())
+			.unwrap(),
+		"http://x/y#f"
+	);
+}
+
+#[test]
+fn as_ref_str_and_bytes() {
+	fn len_str<S: AsRef<str>>(s: S) -> usize {
+		s.as_ref().len()
+	}
+
+	fn len_bytes<S: AsRef<[u8]>>(s: S) -> usize {
+		s.as_ref().len()
+	}
+
+	let iri = Iri::new("http://example.org/a").unwrap();
+	assert_eq!(len_str(iri), 20);
+	assert_eq!(len_bytes(iri), 20);
+	assert_eq!(len_str(iri.as_iri_ref()), 20);
+	assert_eq!(len_bytes(iri.as_iri_ref()), 20);
+	assert_eq!(len_str(iri.to_owned()), 20);
+	assert_eq!(len_bytes(iri.to_owned()), 20);
+	assert_eq!(len_str(IriRefBuf::new("/a").unwrap()), 2);
+	assert_eq!(len_bytes(IriRefBuf::new("/a").unwrap()), 2);
+}
+
+#[test]
+fn set_host() {
+	let mut iri = IriBuf::new("http://a:b@old:81/p").unwrap();
+	iri.set_host("[::1]").unwrap();
+	assert_eq!(iri, "http://a:b@[::1]:81/p");
+	assert_eq!(iri.host(), Some("[::1]"));
+
+	assert_eq!(iri.set_host("a/b"), Err(iref::Error::InvalidHost));
+	assert_eq!(iri, "http://a:b@[::1]:81/p");
+
+	let mut iri = IriBuf::new("foo:rootless/path").unwrap();
+	assert_eq!(iri.set_host("host"), Err(iref::Error::InvalidPath));
+	assert_eq!(iri, "foo:rootless/path");
+
+	let mut iri = IriBuf::new("foo:").unwrap();
+	iri.set_host("host").unwrap();
+	assert_eq!(iri, "foo://host");
+}
+
+#[test]
+fn incremental_authority() {
+	let mut iri_ref = IriRefBuf::new("/p?q#f").unwrap();
+	iri_ref.set_port(None).unwrap();
+	iri_ref.set_userinfo(None).unwrap();
+	assert_eq!(iri_ref, "/p?q#f");
+
+	iri_ref.set_host("h").unwrap();
+	assert_eq!(iri_ref, "//h/p?q#f");
+	iri_ref.set_port(Some("80")).unwrap();
+	iri_ref.set_userinfo(Some("u")).unwrap();
+	assert_eq!(iri_ref, "//u@h:80/p?q#f");
+	assert_eq!(iri_ref.path(), "/p");
+	assert_eq!(iri_ref.query().unwrap(), "q");
+
+	iri_ref.set_port(None).unwrap();
+	assert_eq!(iri_ref.set_port(Some("x")), Err(iref::Error::InvalidPort));
+	assert_eq!(iri_ref, "//u@h/p?q#f");
+
+	let mut iri_ref = IriRefBuf::new("?q").unwrap();
+	iri_ref.set_port(Some("8080")).unwrap();
+	assert_eq!(iri_ref, "//:8080?q");
+	iri_ref.set_host("h").unwrap();
+	assert_eq!(iri_ref, "//h:8080?q");
+
+	let mut iri_ref = IriRefBuf::new("a/b").unwrap();
+	assert_eq!(
+		iri_ref.set_userinfo(Some("u")),
+		Err(iref::Error::InvalidPath)
+	);
+	assert_eq!(iri_ref, "a/b");
+
+	let mut iri = IriBuf::new("mailto:x").unwrap();
+	assert_eq!(
+		iri.set_port(Some("1")),
+		Err(iref::Error::UnexpectedAuthority)
+	);
+}
+
+#[test]
+fn ignoring_fragment() {
+	use std::collections::hash_map::DefaultHasher;
+	use std::hash::Hasher;
+
+	fn hash(iri: Iri) -> u64 {
+		let mut hasher = DefaultHasher::new();
+		iri.hash_ignoring_fragment(&mut hasher);
+		hasher.finish()
+	}
+
+	let base = Iri::new("http://example.org/a?q#f").unwrap();
+	let other_fragment = Iri::new("http://example.org/a?q#g").unwrap();
+	let no_fragment = Iri::new("http://example.org/a?q").unwrap();
+	let other_query = Iri::new("http://example.org/a?r#f").unwrap();
+	let other_both = Iri::new("http://example.org/a?r#g").unwrap();
+
+	assert!(base.eq_ignoring_fragment(other_fragment));
+	assert!(base.eq_ignoring_fragment(no_fragment));
+	assert_eq!(hash(base), hash(other_fragment));
+	assert_eq!(hash(base), hash(no_fragment));
+
+	assert!(!base.eq_ignoring_fragment(other_query));
+	assert!(!base.eq_ignoring_fragment(other_both));
+	assert_ne!(hash(base), hash(other_query));
+	assert_ne!(hash(base), hash(other_both));
+
+	let buf = IriBuf::new("http://example.org/a?q#h").unwrap();
+	assert!(buf.eq_ignoring_fragment(base));
+	assert!(base.eq_ignoring_fragment(&buf));
+}
+
+#[test]
+fn empty_reference() {
+	use std::collections::hash_map::DefaultHasher;
+	use std::hash::{Hash, Hasher};
+
+	fn hash<T: Hash>(value: &T) -> u64 {
+		let mut hasher = DefaultHasher::new();
+		value.hash(&mut hasher);
+		hasher.finish()
+	}
+
+	let parsed = IriRef::new("").unwrap();
+	let default = IriRefBuf::default();
+	assert_eq!(parsed, default);
+	assert_eq!(default, parsed);
+	assert_eq!(IriRefBuf::new("").unwrap(), default);
+	assert_eq!(hash(&parsed), hash(&default));
+	assert_eq!(hash(&IriRefBuf::new("").unwrap()), hash(&default));
+	assert_eq!(
+		IriRefBuf::new("").unwrap().into_raw_parts(),
+		default.clone().into_raw_parts()
+	);
+
+	for iri_ref in [parsed, default.as_iri_ref()].iter() {
+		assert!(iri_ref.path().is_empty());
+		assert!(iri_ref.scheme().is_none());
+		assert!(iri_ref.authority().is_none());
+		assert!(iri_ref.query().is_none());
+		assert!(iri_ref.fragment().is_none());
+	}
+}
+
+#[test]
+fn conversion_matrix() {
+	use std::convert::{TryFrom, TryInto};
+
+	let iri = Iri::new("http://a/b").unwrap();
+	let iri_ref = IriRef::new("http://a/b").unwrap();
+	let relative = IriRef::new("b").unwrap();
+	let iri_buf = IriBuf::new("http://a/b").unwrap();
+	let iri_ref_buf = IriRefBuf::new("http://a/b").unwrap();
+	let relative_buf = IriRefBuf::new("b").unwrap();
+
+	assert_eq!(Iri::from(&iri), iri);
+	assert_eq!(Iri::try_from(&iri_ref).unwrap(), iri);
+	assert_eq!(Iri::try_from(&relative), Err(iref::Error::InvalidScheme));
+
+	assert_eq!(IriRef::from(&iri), iri_ref);
+	assert_eq!(IriRef::from(&iri_ref), iri_ref);
+
+	assert_eq!(IriBuf::from(&iri_buf), iri_buf);
+	assert_eq!(IriBuf::try_from(&iri_ref).unwrap(), iri_buf);
+	assert_eq!(IriBuf::try_from(&iri_ref_buf).unwrap(), iri_buf);
+	assert_eq!(
+		IriBuf::try_from(&relative_buf),
+		Err(iref::Error::InvalidScheme)
+	);
+
+	assert_eq!(IriRefBuf::from(&iri_buf), iri_ref_buf);
+	assert_eq!(IriRefBuf::from(&iri_ref_buf), iri_ref_buf);
+
+	let ptr = iri_buf.as_str().as_ptr();
+	let string = String::from(iri_buf);
+	assert_eq!(string, "http://a/b");
+	assert_eq!(string.as_ptr(), ptr);
+
+	let ptr = iri_ref_buf.as_str().as_ptr();
+	let bytes = Vec::<u8>::from(iri_ref_buf);
+	assert_eq!(bytes, b"http://a/b");
+	assert_eq!(bytes.as_ptr(), ptr);
+
+	assert_eq!(String::from(relative_buf), "b");
+
+	let owned: IriBuf = "http://a/b".try_into().unwrap();
+	assert_eq!(owned, "http://a/b");
+	assert_eq!(IriBuf::try_from("b"), Err(iref::Error::MissingScheme));
+	let owned: IriRefBuf = "b".try_into().unwrap();
+	assert_eq!(owned, "b");
+	assert_eq!(IriRefBuf::try_from("a b"), Err(iref::Error::InvalidPath));
+	assert_eq!(
+		Vec::<u8>::from(IriBuf::new("http://a/b").unwrap()),
+		b"http://a/b"
+	);
+}
+
+#[test]
+fn path_and_query() {
+	let challenges = [
+		("http://a/b/c?d#e", "/b/c?d"),
+		("http://a/b/c#e", "/b/c"),
+		("http://a?q", "?q"),
+		("http://a", ""),
+		("mailto:x@y.z?subject=hi", "x@y.z?subject=hi"),
+	];
+
+	for (input, expected) in challenges.iter() {
+		assert_eq!(Iri::new(input).unwrap().path_and_query(), *expected);
+		assert_eq!(IriBuf::new(input).unwrap().path_and_query(), *expected);
+	}
+
+	assert_eq!(IriRef::new("../x?#").unwrap().path_and_query(), "../x?");
+}
+
+#[test]
+fn protocol_relative() {
+	let iri_ref = IriRef::new("//cdn.example.com/lib.js").unwrap();
+	assert!(iri_ref.is_protocol_relative());
+	assert!(iri_ref.scheme().is_none());
+	assert_eq!(iri_ref.authority().unwrap(), "cdn.example.com");
+	assert_eq!(iri_ref.path(), "/lib.js");
+
+	let challenges = [
+		("https://example.org/a/b", "https://cdn.example.com/lib.js"),
+		("http://example.org", "http://cdn.example.com/lib.js"),
+		(
+			"http://u@example.org:8080/a?q#f",
+			"http://cdn.example.com/lib.js",
+		),
+	];
+
+	for (base, expected) in challenges.iter() {
+		let base = Iri::new(base).unwrap();
+		assert_eq!(iri_ref.resolved(base), *expected);
+	}
+
+	let base = Iri::new("https://example.org/a/b?q").unwrap();
+	assert_eq!(IriRef::new("//h").unwrap().resolved(base), "https://h");
+	assert_eq!(
+		IriRef::new("//h/x/../y?z#f").unwrap().resolved(base),
+		"https://h/y?z#f"
+	);
+	assert_eq!(IriRef::new("//").unwrap().resolved(base), "https://");
+
+	assert!(IriRefBuf::new("//h")
+		.unwrap()
+		.as_iri_ref()
+		.is_protocol_relative());
+	assert!(!IriRef::new("http://h").unwrap().is_protocol_relative());
+	assert!(!IriRef::new("h/x").unwrap().is_protocol_relative());
+}
+
+#[test]
+fn decoded_components() {
+	use std::borrow::Cow;
+
+	let iri = Iri::new("http://a/b%2Fc/d?e=%C3%A9#g%20h").unwrap();
+	assert!(matches!(iri.path_decoded(), Cow::Owned(ref path) if path == "/b/c/d"));
+	assert!(matches!(iri.query_decoded(), Some(Cow::Owned(ref query)) if query == "e=é"));
+	assert!(matches!(iri.fragment_decoded(), Some(Cow::Owned(ref fragment)) if fragment == "g h"));
+
+	let iri = IriBuf::new("http://a/b/c?d=é#f").unwrap();
+	assert!(matches!(iri.path_decoded(), Cow::Borrowed("/b/c")));
+	assert!(matches!(iri.query_decoded(), Some(Cow::Borrowed("d=é"))));
+	assert!(matches!(iri.fragment_decoded(), Some(Cow::Borrowed("f"))));
+
+	let iri_ref = IriRef::new("").unwrap();
+	assert!(matches!(iri_ref.path_decoded(), Cow::Borrowed("")));
+	assert!(iri_ref.query_decoded().is_none());
+	assert!(iri_ref.fragment_decoded().is_none());
+
+	let iri = Iri::new("http://a/%FF%C3?%FFb#%C3%A9%FF").unwrap();
+	assert_eq!(iri.path_decoded(), "/\u{fffd}\u{fffd}");
+	assert_eq!(iri.query_decoded().unwrap(), "\u{fffd}b");
+	assert_eq!(iri.fragment_decoded().unwrap(), "é\u{fffd}");
+}
+
+#[test]
+fn try_normalize() {
+	let challenges = [
+		("../a", None),
+		("/../a", None),
+		("a/../..", None),
+		("//h/a/../../b", None),
+		("a/b/../../c", Some("c")),
+		("./a/./b/..", Some("a/")),
+		("/a/../b?q#f", Some("/b?q#f")),
+		("http://h/../a", Some("http://h/a")),
+		("foo:../a", Some("foo:../a")),
+	];
+
+	for (input, expected) in challenges.iter() {
+		let mut iri_ref = IriRefBuf::new(input).unwrap();
+		match expected {
+			Some(expected) => {
+				iri_ref.try_normalize().unwrap();
+				assert_eq!(iri_ref.as_str(), *expected)
+			}
+			None => {
+				assert_eq!(iri_ref.try_normalize(), Err(iref::Error::PathEscapesRoot));
+				assert_eq!(iri_ref.as_str(), *input)
+			}
+		}
+	}
+}
+
+#[test]
+fn inspect_mutable_handles() {
+	use std::convert::TryInto;
+
+	let mut iri = IriBuf::new("http://a:1/b?q").unwrap();
+
+	let mut authority = iri.authority_mut().unwrap();
+	authority.set_port(Some("8080".try_into().unwrap()));
+	assert_eq!(authority, "a:8080");
+	assert_eq!(authority.as_authority().as_bytes(), b"a:8080");
+	authority.set_host("b".try_into().unwrap());
+	assert_eq!(authority.as_str(), "b:8080");
+	assert_eq!(authority.to_string(), "b:8080");
+
+	let mut path = iri.path_mut();
+	path.push("c".try_into().unwrap());
+	assert_eq!(path, "/b/c");
+	assert_eq!(path.as_str(), "/b/c");
+	path.pop();
+	assert_eq!(path, "/b/");
+	assert!(path != "/b/c");
+
+	assert_eq!(iri, "http://b:8080/b/?q");
+}
+
+#[test]
+fn resolve_local() {
+	let base = Iri::new("https://example.org/a/b?q#f").unwrap();
+
+	let challenges = [
+		("", Some("https://example.org/a/b?q")),
+		("#g", Some("https://example.org/a/b?q#g")),
+		("c", Some("https://example.org/a/c")),
+		("../../../c", Some("https://example.org/c")),
+		("/x?y", Some("https://example.org/x?y")),
+		("./a:b", Some("https://example.org/a/a:b")),
+		("https://example.org/c", None),
+		("http://evil/", None),
+		("javascript:alert(1)", None),
+		("//evil/", None),
+		("//", None),
+	];
+
+	for (reference, expected) in challenges.iter() {
+		let reference = IriRef::new(reference).unwrap();
+		match expected {
+			Some(expected) => assert_eq!(base.resolve_local(reference).unwrap(), *expected),
+			None => assert_eq!(
+				base.resolve_local(reference),
+				Err(iref::Error::NonLocalReference)
+			),
+		}
+	}
+}
+
+#[test]
+fn same_resource() {
+	let challenges = [
+		("http://example.org", "http://example.org/", true),
+		("HTTP://Example.ORG:80/a", "http://example.org/a", true),
+		("https://example.org:443", "https://example.org:/", true),
+		(
+			"http://example.org/a/./b/../%63",
+			"http://example.org/a/c",
+			true,
+		),
+		(
+			"http://example.org/a?q=%41",
+			"http://example.org/a?q=A",
+			true,
+		),
+		("http://example.org/a#x", "http://example.org/a#y", true),
+		("http://example.org/", "https://example.org/", false),
+		("http://example.org:8080/", "http://example.org/", false),
+		(
+			"http://User@example.org/",
+			"http://user@example.org/",
+			false,
+		),
+		("http://example.org/A", "http://example.org/a", false),
+		("http://example.org/a", "http://example.org/a/", false),
+		("http://example.org/a?", "http://example.org/a", false),
+		("urn:a", "urn:a", true),
+		("urn:", "urn:/", false),
+	];
+
+	for (a, b, expected) in challenges.iter() {
+		let a = Iri::new(a).unwrap();
+		let b = Iri::new(b).unwrap();
+		assert_eq!(a.same_resource(&b), *expected, "{} {}", a, b);
+		assert_eq!(b.same_resource(&a), *expected, "{} {}", b, a);
+	}
+
+	let a = Iri::new("http://example.org#f").unwrap();
+	assert!(a.same_resource_and_fragment(&Iri::new("http://example.org/#%66").unwrap()));
+	assert!(!a.same_resource_and_fragment(&Iri::new("http://example.org/#g").unwrap()));
+	assert!(!a.same_resource_and_fragment(&Iri::new("http://example.org/").unwrap()));
+}
+
+#[test]
+fn set_scheme_adjusting_port() {
+	use iref::Scheme;
+	use std::convert::TryFrom;
+
+	let challenges = [
+		("http://a:80/", "https", "https://a/"),
+		("http://a:080/", "https", "https://a/"),
+		("https://u@a:443?q", "http", "http://u@a?q"),
+		("http://a:443/", "https", "https://a:443/"),
+		("http://a:8080/", "https", "https://a:8080/"),
+		("http://a:/", "https", "https://a:/"),
+		("http://a/", "https", "https://a/"),
+		("http://a:80/", "HTTP", "HTTP://a:80/"),
+		("http://a:80/", "foo", "foo://a:80/"),
+		("foo://a:80/", "https", "https://a:80/"),
+		("mailto:a@b", "https", "https:a@b"),
+	];
+
+	for (input, scheme, expected) in challenges.iter() {
+		let scheme = Scheme::try_from(*scheme).unwrap();
+
+		let mut iri = IriBuf::new(input).unwrap();
+		iri.set_scheme_adjusting_port(scheme);
+		assert_eq!(iri.as_str(), *expected, "{}", input);
+
+		let mut iri = IriBuf::new(input).unwrap();
+		iri.set_scheme(scheme);
+		assert_eq!(iri.port(), IriBuf::new(input).unwrap().port());
+	}
+}
+
+#[test]
+fn canonical_host() {
+	use std::borrow::Cow;
+
+	let challenges = [
+		("http://www.example.com/", "www.example.com"),
+		("http://WWW.Example.COM./", "www.example.com"),
+		("http://ex%41mple.com%2E/", "example.com"),
+		("http://%ff.COM/", "%FF.com"),
+		("http://a%2fB.com/", "a%2Fb.com"),
+		("http://%FF%41%e2%82/", "%FFa%E2%82"),
+		("http://a%20b%21/", "a%20b!"),
+		("http://[::ABCD]:80/", "[::abcd]"),
+		("http://127.0.0.1/", "127.0.0.1"),
+		("http://./", "."),
+		("file:///etc", ""),
+	];
+
+	for (input, expected) in challenges.iter() {
+		let iri = IriBuf::new(input).unwrap();
+		assert_eq!(iri.canonical_host().unwrap(), *expected, "{}", input);
+	}
+
+	let iri = Iri::new("http://www.example.com/").unwrap();
+	assert!(matches!(iri.canonical_host(), Some(Cow::Borrowed(_))));
+
+	let iri = Iri::new("http://例え.JP/").unwrap();
+	#[cfg(not(feature = "idna"))]
+	{
+		assert_eq!(iri.canonical_host().unwrap(), "例え.jp");
+		let iri = Iri::new("http://%C3%A9%FF.FR/").unwrap();
+		assert_eq!(iri.canonical_host().unwrap(), "é%FF.fr");
+	}
+	#[cfg(feature = "idna")]
+	{
+		assert_eq!(iri.canonical_host().unwrap(), "xn--r8jz45g.jp");
+		let iri = Iri::new("http://B%C3%BCcher.example\u{3002}/").unwrap();
+		assert_eq!(iri.canonical_host().unwrap(), "xn--bcher-kva.example");
+		let iri = Iri::new("http://%C3%A9%FF.FR/").unwrap();
+		assert_eq!(iri.canonical_host().unwrap(), "é%FF.fr");
+	}
+}
+
+#[test]
+fn malformed_pct_encoded() {
+	use iref::parsing::find_invalid_pct_encoded;
+	use iref::{Error, Fragment, Path, Query, UserInfo};
+	use std::convert::TryFrom;
+
+	let templates = [
+		"http://u{}@example.org/",
+		"http://example.org/a{}/b",
+		"http://example.org/?a{}",
+		"http://example.org/#a{}",
+		"a{}",
+	];
+
+	for template in templates.iter() {
+		for escape in ["%2G", "%g0", "%", "%0", "%%41", "%\u{e9}0"].iter() {
+			let input = template.replace("{}", escape);
+			assert_eq!(
+				IriRef::new(&input),
+				Err(Error::InvalidPercentEncoding),
+				"{}",
+				input
+			);
+			assert_eq!(
+				find_invalid_pct_encoded(input.as_bytes()),
+				Some(template.find("{}").unwrap()),
+				"{}",
+				input
+			);
+		}
+
+		let input = template.replace("{}", "%2f%C3%A9");
+		assert!(IriRef::new(&input).is_ok(), "{}", input);
+		assert_eq!(find_invalid_pct_encoded(input.as_bytes()), None);
+	}
+
+	assert_eq!(
+		UserInfo::try_from("u%2G").err(),
+		Some(Error::InvalidPercentEncoding)
+	);
+	assert_eq!(
+		Path::try_from("/a%").err(),
+		Some(Error::InvalidPercentEncoding)
+	);
+	assert_eq!(
+		Query::try_from("a%g0").err(),
+		Some(Error::InvalidPercentEncoding)
+	);
+	assert_eq!(
+		Fragment::try_from("a%2G").err(),
+		Some(Error::InvalidPercentEncoding)
+	);
+	assert_eq!(find_invalid_pct_encoded(b"a%4"), Some(1));
+}
+
+#[test]
+fn send_sync() {
+	use iref::*;
+
+	fn assert_send_sync<T: Send + Sync>() {}
+
+	assert_send_sync::<Error>();
+	assert_send_sync::<IriBuf>();
+	assert_send_sync::<IriRefBuf>();
+	assert_send_sync::<Iri<'static>>();
+	assert_send_sync::<IriRef<'static>>();
+	assert_send_sync::<Scheme<'static>>();
+	assert_send_sync::<Authority<'static>>();
+	assert_send_sync::<AuthorityBuf>();
+	assert_send_sync::<AuthorityMut<'static>>();
+	assert_send_sync::<UserInfo<'static>>();
+	assert_send_sync::<Host<'static>>();
+	assert_send_sync::<Port<'static>>();
+	assert_send_sync::<Path<'static>>();
+	assert_send_sync::<PathBuf>();
+	assert_send_sync::<PathMut<'static>>();
+	assert_send_sync::<Segment<'static>>();
+	assert_send_sync::<Query<'static>>();
+	assert_send_sync::<QueryBuf>();
+	assert_send_sync::<Fragment<'static>>();
+	assert_send_sync::<FragmentBuf>();
+	assert_send_sync::<IriTemplate>();
+	assert_send_sync::<IriPattern>();
+
+	let iri = std::sync::Arc::new(IriBuf::new("http://example.org/a").unwrap());
+	let shared = iri.clone();
+	let host = std::thread::spawn(move || shared.host().map(str::to_string))
+		.join()
+		.unwrap();
+	assert_eq!(host.as_deref(), Some("example.org"));
+
+	let borrowed = Iri::new("http://example.org/a").unwrap();
+	std::thread::spawn(move || assert_eq!(borrowed.path(), "/a"))
+		.join()
+		.unwrap();
+}
+
+#[test]
+fn from_scheme_and_opaque() {
+	use iref::Scheme;
+	use std::convert::TryFrom;
+
+	let valid = [
+		("mailto", "user@example.org", "mailto:user@example.org"),
+		("tel", "+1-555-0100", "tel:+1-555-0100"),
+		("urn", "isbn:0451450523", "urn:isbn:0451450523"),
+		("urn", "", "urn:"),
+		("data", "a:b/c", "data:a:b/c"),
+	];
+
+	for (scheme, opaque, expected) in valid.iter() {
+		let scheme = Scheme::try_from(*scheme).unwrap();
+		let iri = IriBuf::from_scheme_and_opaque(scheme, opaque).unwrap();
+		assert_eq!(iri, *expected);
+		assert!(iri.authority().is_none());
+		assert_eq!(iri.path(), *opaque);
+	}
+
+	let scheme = Scheme::try_from("urn").unwrap();
+	for opaque in ["/a", "//host/a", "a b", "a?b", "a#b", "%zz"].iter() {
+		assert!(IriBuf::from_scheme_and_opaque(scheme, opaque).is_err());
+	}
+}
+
+#[test]
+fn from_components() {
+	use std::convert::TryInto;
+
+	let inputs = [
+		"http://u@h:1/a/b?q#f",
+		"foo:a:b",
+		"//h",
+		"//h/",
+		"/a",
+		"a/b",
+		"?q",
+		"#f",
+		"",
+		"foo://",
+	];
+
+	for input in inputs.iter() {
+		let expected = IriRef::new(input).unwrap();
+		let iri_ref = IriRefBuf::from_components(
+			expected.scheme(),
+			expected.authority(),
+			expected.path(),
+			expected.query(),
+			expected.fragment(),
+		)
+		.unwrap();
+		assert_eq!(iri_ref.as_str(), *input);
+		assert_eq!(
+			iri_ref.into_raw_parts(),
+			IriRefBuf::new(input).unwrap().into_raw_parts()
+		);
+	}
+
+	let error = |path: &str, scheme: Option<&str>, authority: Option<&str>| {
+		IriRefBuf::from_components(
+			scheme.map(|s| s.try_into().unwrap()),
+			authority.map(|a| a.try_into().unwrap()),
+			path.try_into().unwrap(),
+			None,
+			None,
+		)
+		.err()
+	};
+
+	assert_eq!(error("a", None, Some("h")), Some(iref::Error::InvalidPath));
+	assert_eq!(
+		error("//a", Some("s"), None),
+		Some(iref::Error::InvalidPath)
+	);
+	assert_eq!(error("a:b", None, None), Some(iref::Error::InvalidPath));
+	assert_eq!(error("a:b", Some("s"), None), None);
+	assert_eq!(error("/a:b", None, None), None);
+}
+
+#[test]
+fn empty_path_with_query() {
+	use std::convert::TryInto;
+
+	let iri_ref = IriRef::new("?q").unwrap();
+	assert!(iri_ref.path().is_empty());
+	assert_eq!(iri_ref.path().as_str(), "");
+	assert_eq!(iri_ref.query().unwrap(), "q");
+	assert!(iri_ref.fragment().is_none());
+
+	let iri_ref = IriRef::new("scheme:?q#f").unwrap();
+	assert_eq!(iri_ref.scheme().unwrap(), "scheme");
+	assert_eq!(iri_ref.path().as_str(), "");
+	assert_eq!(iri_ref.query().unwrap(), "q");
+	assert_eq!(iri_ref.fragment().unwrap(), "f");
+
+	let iri_ref = IriRef::new("?").unwrap();
+	assert_eq!(iri_ref.path().as_str(), "");
+	assert_eq!(iri_ref.query().unwrap(), "");
+
+	let iri_ref = IriRef::new("//h?q").unwrap();
+	assert_eq!(iri_ref.authority().unwrap(), "h");
+	assert_eq!(iri_ref.path().as_str(), "");
+	assert_eq!(iri_ref.query().unwrap(), "q");
+
+	let base = Iri::new("http://a/b/c?old#frag").unwrap();
+	assert_eq!(IriRef::new("?q").unwrap().resolved(base), "http://a/b/c?q");
+	assert_eq!(IriRef::new("?").unwrap().resolved(base), "http://a/b/c?");
+	assert_eq!(
+		IriRef::new("?q#g").unwrap().resolved(base),
+		"http://a/b/c?q#g"
+	);
+
+	let mut iri_ref = IriRefBuf::new("?q").unwrap();
+	iri_ref.set_query(Some("longer".try_into().unwrap()));
+	assert_eq!(iri_ref, "?longer");
+	assert_eq!(iri_ref.path().as_str(), "");
+}
+
+#[test]
+fn scheme_matching() {
+	let allowed = ["http", "https", "ftp"];
+
+	assert!(Iri::new("HtTp://a").unwrap().scheme_eq("http"));
+	assert!(IriBuf::new("FTP://a").unwrap().scheme_is_one_of(&allowed));
+	assert!(!IriBuf::new("mailto:a@b")
+		.unwrap()
+		.scheme_is_one_of(&allowed));
+	assert!(!IriRef::new("http").unwrap().scheme_eq("http"));
+	assert!(!IriRefBuf::new("/a").unwrap().scheme_is_one_of(&allowed));
+	assert!(!Iri::new("https://a").unwrap().scheme_eq("http"));
+	assert!(!Iri::new("http://a").unwrap().scheme_is_one_of(&[]));
+}
+
+#[test]
+fn split_fragment() {
+	let inputs = ["http://a/b?c#d", "http://a/b?c", "#", "#f", "", "a?#b"];
+
+	for input in inputs.iter().map(|input| IriRef::new(input).unwrap()) {
+		let (document, fragment) = input.split_fragment();
+		assert!(document.fragment().is_none());
+		assert_eq!(fragment, input.fragment());
+
+		let mut recombined = document.as_str().to_string();
+		if let Some(fragment) = fragment {
+			recombined.push('#');
+			recombined.push_str(fragment.as_str());
+		}
+
+		assert_eq!(recombined, input.as_str());
+		assert_eq!(document.as_str().as_ptr(), input.as_str().as_ptr());
+	}
+}
+
+#[test]
+fn code_point_ranges() {
+	// (code point, allowed in the path, allowed in the query)
+	let challenges = [
+		('\u{9f}', false, false),
+		('\u{a0}', true, true),
+		('\u{d7ff}', true, true),
+		('\u{e000}', false, true),
+		('\u{f8ff}', false, true),
+		('\u{f900}', true, true),
+		('\u{fdcf}', true, true),
+		('\u{fdd0}', false, false),
+		('\u{fdef}', false, false),
+		('\u{fdf0}', true, true),
+		('\u{ffef}', true, true),
+		('\u{fff0}', false, false),
+		('\u{fffe}', false, false),
+		('\u{10000}', true, true),
+		('\u{1fffd}', true, true),
+		('\u{1fffe}', false, false),
+		('\u{e0fff}', false, false),
+		('\u{e1000}', true, true),
+		('\u{efffd}', true, true),
+		('\u{efffe}', false, false),
+		('\u{f0000}', false, true),
+		('\u{ffffd}', false, true),
+		('\u{ffffe}', false, false),
+		('\u{100000}', false, true),
+		('\u{10fffd}', false, true),
+		('\u{10ffff}', false, false),
+	];
+
+	for (c, in_path, in_query) in challenges.iter() {
+		let path = format!("a/{}", c);
+		let query = format!("a?{}", c);
+		assert_eq!(IriRef::new(&path).is_ok(), *in_path, "{:?} in path", c);
+		assert_eq!(IriRef::new(&query).is_ok(), *in_query, "{:?} in query", c);
+		assert!(IriRef::new(&format!("a#{}", c)).is_ok() == *in_path);
+	}
+
+	assert_eq!(
+		IriRef::new(&b"a/\xc0\xaf"[..]),
+		Err(iref::Error::InvalidEncoding)
+	);
+	assert_eq!(
+		IriRef::new(&b"a/\xed\xa0\x80"[..]),
+		Err(iref::Error::InvalidEncoding)
+	);
+}
+
+#[test]
+fn ipv6_literals() {
+	let valid = [
+		"[::]",
+		"[::1]",
+		"[1::]",
+		"[2001:db8::]",
+		"[2001:db8::7]",
+		"[2001:db8::1:7]",
+		"[1:2:3:4:5:6:7:8]",
+		"[1:2:3:4:5:6:7::]",
+		"[1::2:3:4:5:6:7]",
+		"[::1:2:3:4:5:6:7]",
+		"[::1:2:3:4:5:6]",
+		"[1:2:3:4:5:6::]",
+		"[2001:db8::192.0.2.33]",
+		"[::1:2:3:4:5:1.2.3.4]",
+		"[1:2:3:4:5::1.2.3.4]",
+		"[1:2:3:4:5:6:192.0.2.33]",
+	];
+
+	for host in valid.iter() {
+		let buffer = format!("http://{}/", host);
+		let iri = Iri::new(&buffer).expect(host);
+		assert_eq!(iri.authority().unwrap(), *host);
+	}
+
+	let invalid = [
+		"[:1]",
+		"[1:]",
+		"[1:::2]",
+		"[1::2::3]",
+		"[1:2:3:4:5:6:7:8:9]",
+		"[1:2:3:4:5:6:7]",
+		"[1:2:3:4:5:192.0.2.33]",
+		"[1:2:3:4:5:6:7:192.0.2.33]",
+		"[1:2:3:4:5:6:7:1.2.3.4]",
+		"[::1:2:3:4:5:6:1.2.3.4]",
+		"[1:2:3:4:5:6:7:8::]",
+		"[::1:2:3:4:5:6:7:8]",
+		"[1:2:3:4:5:6:7:]",
+		"[1.2.3.4::]",
+	];
+
+	for host in invalid.iter() {
+		assert!(Iri::new(&format!("http://{}/", host)).is_err(), "{}", host);
+	}
+}
+
+#[test]
+fn pct_encoded_authority_round_trip() {
+	use iref::{Host, Port, UserInfo};
+	use std::convert::TryFrom;
+
+	let challenges = [
+		"http://user%40name@example.com/",
+		"http://user%3Aname:pass%40word%25@ex%61mple.com:8080/a?q#f",
+		"http://%E2%82%AC@%C3%A9t%C3%A9.example/",
+		"http://:%20@%2D.example:/",
+		"http://@%41/",
+		"http://u%40@[::1]:1/",
+		"//a%2Fb@h%2Fst",
+		"http://%41%42%43/",
+	];
+
+	for input in challenges.iter() {
+		let original = IriRefBuf::new(input).unwrap();
+		let authority = original.authority().unwrap();
+		let userinfo = authority.userinfo().map(|u| u.as_str().to_string());
+		let host = authority.host().as_str().to_string();
+		let port = authority.port().map(|p| p.as_str().to_string());
+
+		// Accessors give back the exact bytes.
+		let mut rebuilt = String::new();
+		if let Some(userinfo) = &userinfo {
+			rebuilt.push_str(userinfo);
+			rebuilt.push('@');
+		}
+		rebuilt.push_str(&host);
+		if let Some(port) = &port {
+			rebuilt.push(':');
+			rebuilt.push_str(port);
+		}
+		assert_eq!(rebuilt, authority.as_str());
+
+		// Reconstruct with the `AuthorityMut` setters.
+		let mut iri_ref = original.clone();
+		iri_ref.set_authority(Some(iref::Authority::try_from("").unwrap()));
+		{
+			let mut authority = iri_ref.authority_mut().unwrap();
+			authority.set_port(port.as_deref().map(|p| Port::try_from(p).unwrap()));
+			authority.set_host(Host::try_from(host.as_str()).unwrap());
+			authority.set_userinfo(userinfo.as_deref().map(|u| UserInfo::try_from(u).unwrap()));
+		}
+		assert_eq!(iri_ref.as_str(), *input);
+		assert_eq!(iri_ref.authority().unwrap().host(), authority.host());
+		assert_eq!(
+			iri_ref.authority().unwrap().userinfo(),
+			authority.userinfo()
+		);
+
+		// Reconstruct with the string setters.
+		let mut iri_ref = original.clone();
+		iri_ref.set_authority(None);
+		iri_ref.set_host(&host).unwrap();
+		iri_ref.set_userinfo(userinfo.as_deref()).unwrap();
+		iri_ref.set_port(port.as_deref()).unwrap();
+		assert_eq!(iri_ref.as_str(), *input);
+
+		// Reconstruct the user info with the user name and password setters.
+		if let Some(username) = authority.username() {
+			let password = authority.password().map(|p| p.as_str().to_string());
+			let mut iri_ref = original.clone();
+			{
+				let mut authority = iri_ref.authority_mut().unwrap();
+				authority.set_userinfo(None);
+				authority.set_username(username.as_str()).unwrap();
+				authority.set_password(password.as_deref()).unwrap();
+			}
+			assert_eq!(iri_ref.as_str(), *input);
+		}
+
+		// Round trip through the owned authority.
+		let owned = original.authority().unwrap().to_owned();
+		assert_eq!(owned.as_str(), authority.as_str());
+		assert_eq!(owned.host(), authority.host());
+		assert_eq!(owned.userinfo(), authority.userinfo());
+		assert_eq!(owned.port(), authority.port());
+	}
+}
+
+#[test]
+fn new_reporting() {
+	let challenges = [
+		("http://example.org/a/b?q#f", true),
+		("http://example.org", true),
+		("http://user@example.org:8080/\u{e9}%20", true),
+		("http://example.org/%C3%A9", false),
+		("urn:isbn:0451450523", true),
+		("HTTP://example.org/", false),
+		("http://Example.org/", false),
+		("http://User@example.org/", true),
+		("http://example.org/a/./b", false),
+		("http://example.org/a/../b", false),
+		("http://example.org/%7E", false),
+		("http://example.org/%c3%a9", false),
+		("http://example.org/%41", false),
+		("http://example.org/A?B#C", true),
+	];
+
+	for (input, expected) in challenges.iter() {
+		let (iri, normalized) = IriBuf::new_reporting(input).unwrap();
+		assert_eq!(iri, *input);
+		assert_eq!(normalized, *expected, "{}", input);
+	}
+
+	assert_eq!(
+		IriBuf::new_reporting("a/b").err(),
+		Some(iref::Error::MissingScheme)
+	);
+}